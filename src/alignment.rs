@@ -0,0 +1,226 @@
+pub(crate) mod landxml;
+
+use crate::investigation_collection::cross_section::station_offset;
+
+// Maximum chord length (m) when curved elements are densified
+const DENSIFY_STEP: f64 = 0.5;
+
+// Horizontal alignment. Points are (x, y) in the InfraModel convention,
+// x being the northing, as in LandXML.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alignment {
+    pub name: String,
+    pub start_station: f64,
+    pub elements: Vec<AlignmentElement>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+pub enum AlignmentElement {
+    Line {
+        start: (f64, f64),
+        end: (f64, f64),
+    },
+    Arc {
+        start: (f64, f64),
+        center: (f64, f64),
+        length: f64,
+        clockwise: bool,
+    },
+    Spiral {
+        start: (f64, f64),
+        // Start tangent direction is from `start` towards `pi`
+        pi: (f64, f64),
+        length: f64,
+        // None for infinite radius
+        radius_start: Option<f64>,
+        radius_end: Option<f64>,
+        clockwise: bool,
+    },
+}
+
+impl Alignment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn length(&self) -> f64 {
+        self.elements.iter().map(AlignmentElement::length).sum()
+    }
+
+    pub fn polyline(&self) -> Vec<(f64, f64)> {
+        let mut points: Vec<(f64, f64)> = Vec::new();
+
+        for element in &self.elements {
+            let element_points = element.densify(DENSIFY_STEP);
+            let skip = match (points.last(), element_points.first()) {
+                (Some(last), Some(first)) => {
+                    ((last.0 - first.0).powi(2) + (last.1 - first.1).powi(2)).sqrt() < 1e-6
+                }
+                _ => false,
+            };
+            points.extend(element_points.into_iter().skip(skip as usize));
+        }
+
+        points
+    }
+
+    // Station and signed offset (positive to the right), None when the point
+    // is beyond either end of the alignment
+    pub fn station_offset(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        StationingAlignment::new(self).station_offset(x, y)
+    }
+}
+
+// Alignment densified once for repeated station queries
+pub(crate) struct StationingAlignment {
+    start_station: f64,
+    polyline: Vec<(f64, f64)>,
+}
+
+impl StationingAlignment {
+    pub(crate) fn new(alignment: &Alignment) -> Self {
+        StationingAlignment {
+            start_station: alignment.start_station,
+            polyline: alignment.polyline(),
+        }
+    }
+
+    pub(crate) fn station_offset(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        station_offset(&self.polyline, (x, y))
+            .map(|(chainage, offset)| (self.start_station + chainage, offset))
+    }
+}
+
+impl AlignmentElement {
+    pub fn length(&self) -> f64 {
+        match self {
+            AlignmentElement::Line { start, end } => {
+                ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt()
+            }
+            AlignmentElement::Arc { length, .. } | AlignmentElement::Spiral { length, .. } => {
+                *length
+            }
+        }
+    }
+
+    fn densify(&self, step: f64) -> Vec<(f64, f64)> {
+        match self {
+            AlignmentElement::Line { start, end } => vec![*start, *end],
+            AlignmentElement::Arc {
+                start,
+                center,
+                length,
+                clockwise,
+            } => {
+                // Angles in the (easting, northing) plane
+                let radius = ((start.0 - center.0).powi(2) + (start.1 - center.1).powi(2)).sqrt();
+                if radius < f64::EPSILON {
+                    return vec![*start];
+                }
+
+                let start_angle = (start.0 - center.0).atan2(start.1 - center.1);
+                let sweep = if *clockwise {
+                    -length / radius
+                } else {
+                    length / radius
+                };
+                let steps = ((length / step).ceil() as usize).max(1);
+
+                (0..=steps)
+                    .map(|i| {
+                        let angle = start_angle + sweep * i as f64 / steps as f64;
+                        (
+                            center.0 + radius * angle.sin(),
+                            center.1 + radius * angle.cos(),
+                        )
+                    })
+                    .collect()
+            }
+            AlignmentElement::Spiral {
+                start,
+                pi,
+                length,
+                radius_start,
+                radius_end,
+                clockwise,
+            } => {
+                let curvature = |radius: &Option<f64>| match radius {
+                    Some(r) if r.abs() > f64::EPSILON && r.is_finite() => 1.0 / r,
+                    _ => 0.0,
+                };
+                let sign = if *clockwise { -1.0 } else { 1.0 };
+                let (k0, k1) = (sign * curvature(radius_start), sign * curvature(radius_end));
+
+                // Heading in the (easting, northing) plane, counter-clockwise from east
+                let heading = (pi.0 - start.0).atan2(pi.1 - start.1);
+                let steps = ((length / step).ceil() as usize).max(1);
+                let ds = length / steps as f64;
+                let substeps = 10;
+
+                let mut points = vec![*start];
+                let (mut east, mut north) = (start.1, start.0);
+
+                for i in 0..steps {
+                    for j in 0..substeps {
+                        let s = (i as f64 + (j as f64 + 0.5) / substeps as f64) * ds;
+                        let theta = heading + k0 * s + (k1 - k0) * s * s / (2.0 * length);
+                        east += theta.cos() * ds / substeps as f64;
+                        north += theta.sin() * ds / substeps as f64;
+                    }
+                    points.push((north, east));
+                }
+
+                points
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const LANDXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<LandXML xmlns="http://www.landxml.org/schema/LandXML-1.2" version="1.2">
+  <Alignments>
+    <Alignment name="VT1" length="150" staStart="1000">
+      <CoordGeom>
+        <Line length="100">
+          <Start>0 0</Start>
+          <End>100 0</End>
+        </Line>
+        <Spiral length="50" radiusStart="INF" radiusEnd="200" rot="cw" spiType="clothoid">
+          <Start>100 0</Start>
+          <PI>133.33 0</PI>
+          <End>149.92 2.08</End>
+        </Spiral>
+      </CoordGeom>
+    </Alignment>
+  </Alignments>
+</LandXML>"#;
+
+    #[test]
+    fn landxml_alignment() {
+        let alignments = Alignment::from_landxml(LANDXML).unwrap();
+        assert_eq!(alignments.len(), 1);
+
+        let alignment = &alignments[0];
+        assert_eq!(alignment.name, "VT1");
+        assert_eq!(alignment.elements.len(), 2);
+        assert!((alignment.length() - 150.0).abs() < 1e-9);
+
+        let (station, offset) = alignment.station_offset(50.0, 5.0).unwrap();
+        assert!((station - 1050.0).abs() < 1e-6);
+        assert!((offset - 5.0).abs() < 1e-6);
+
+        // Clothoid end point from the series expansion
+        let end = *alignment.polyline().last().unwrap();
+        assert!((end.0 - 149.921875).abs() < 1e-3);
+        assert!((end.1 - 2.081008).abs() < 1e-3);
+
+        assert!(alignment.station_offset(-10.0, 0.0).is_none());
+    }
+}
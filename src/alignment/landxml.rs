@@ -0,0 +1,239 @@
+use crate::{Alignment, AlignmentElement};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs;
+
+#[derive(Default)]
+struct ElementBuilder {
+    kind: String,
+    attributes: HashMap<String, String>,
+    points: HashMap<String, (f64, f64)>,
+}
+
+impl Alignment {
+    pub fn from_file(file_path: &str) -> Result<Vec<Alignment>, String> {
+        let content =
+            fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_landxml(&content)
+    }
+
+    // Horizontal alignments of a LandXML document. Lines, circular arcs and
+    // clothoid spirals are supported.
+    pub fn from_landxml(content: &str) -> Result<Vec<Alignment>, String> {
+        let mut reader = Reader::from_str(content);
+        reader.config_mut().trim_text(true);
+
+        let mut alignments = Vec::new();
+        let mut alignment: Option<Alignment> = None;
+        let mut in_coord_geom = false;
+        let mut element: Option<ElementBuilder> = None;
+        let mut point_tag: Option<String> = None;
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| format!("Invalid LandXML at {}: {}", reader.error_position(), e))?;
+
+            match event {
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    let empty = matches!(event, Event::Empty(_));
+                    let tag = local_name(e);
+
+                    match tag.as_str() {
+                        "Alignment" if !empty => {
+                            let attributes = attributes(e)?;
+                            alignment = Some(Alignment {
+                                name: attributes.get("name").cloned().unwrap_or_default(),
+                                start_station: match attributes.get("staStart") {
+                                    Some(value) => parse_number(value)?,
+                                    None => 0.0,
+                                },
+                                elements: Vec::new(),
+                            });
+                        }
+                        "CoordGeom" if alignment.is_some() && !empty => in_coord_geom = true,
+                        "Line" | "Curve" | "Spiral" if in_coord_geom && !empty => {
+                            element = Some(ElementBuilder {
+                                kind: tag,
+                                attributes: attributes(e)?,
+                                points: HashMap::new(),
+                            });
+                        }
+                        "Start" | "End" | "Center" | "PI" if element.is_some() => {
+                            if empty || attributes(e)?.contains_key("pntRef") {
+                                return Err(format!(
+                                    "Point references are not supported ({})",
+                                    tag
+                                ));
+                            }
+                            point_tag = Some(tag);
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Text(e) => {
+                    if let (Some(tag), Some(element)) = (point_tag.as_ref(), element.as_mut()) {
+                        let text = e.unescape().map_err(|e| e.to_string())?;
+                        element.points.insert(tag.clone(), parse_point(&text)?);
+                    }
+                }
+                Event::End(ref e) => {
+                    let name = e.local_name();
+                    match name.as_ref() {
+                        b"Start" | b"End" | b"Center" | b"PI" => point_tag = None,
+                        b"Line" | b"Curve" | b"Spiral" => {
+                            if let (Some(builder), Some(alignment)) =
+                                (element.take(), alignment.as_mut())
+                            {
+                                alignment.elements.push(builder.build()?);
+                            }
+                        }
+                        b"CoordGeom" => in_coord_geom = false,
+                        b"Alignment" => {
+                            if let Some(alignment) = alignment.take() {
+                                alignments.push(alignment);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(alignments)
+    }
+}
+
+impl ElementBuilder {
+    fn point(&self, name: &str) -> Result<(f64, f64), String> {
+        self.points
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("{} is missing {}", self.kind, name))
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, String> {
+        self.attributes
+            .get(name)
+            .map(|value| parse_number(value))
+            .transpose()
+    }
+
+    fn clockwise(&self) -> Result<bool, String> {
+        match self.attributes.get("rot").map(String::as_str) {
+            Some("cw") => Ok(true),
+            Some("ccw") => Ok(false),
+            Some(other) => Err(format!("Invalid rotation: {}", other)),
+            None => Err(format!("{} is missing rotation", self.kind)),
+        }
+    }
+
+    fn radius(&self, name: &str) -> Result<Option<f64>, String> {
+        match self.attributes.get(name).map(String::as_str) {
+            None | Some("INF") | Some("inf") => Ok(None),
+            Some(value) => {
+                let radius = parse_number(value)?;
+                Ok((radius != 0.0 && radius.is_finite()).then_some(radius.abs()))
+            }
+        }
+    }
+
+    fn build(self) -> Result<AlignmentElement, String> {
+        match self.kind.as_str() {
+            "Line" => Ok(AlignmentElement::Line {
+                start: self.point("Start")?,
+                end: self.point("End")?,
+            }),
+            "Curve" => {
+                let start = self.point("Start")?;
+                let center = self.point("Center")?;
+                let clockwise = self.clockwise()?;
+
+                let length = match self.number("length")? {
+                    Some(length) => length,
+                    None => {
+                        let end = self.point("End")?;
+                        let radius =
+                            ((start.0 - center.0).powi(2) + (start.1 - center.1).powi(2)).sqrt();
+                        let start_angle = (start.0 - center.0).atan2(start.1 - center.1);
+                        let end_angle = (end.0 - center.0).atan2(end.1 - center.1);
+                        let sweep = if clockwise {
+                            start_angle - end_angle
+                        } else {
+                            end_angle - start_angle
+                        };
+                        radius * sweep.rem_euclid(2.0 * PI)
+                    }
+                };
+
+                Ok(AlignmentElement::Arc {
+                    start,
+                    center,
+                    length,
+                    clockwise,
+                })
+            }
+            "Spiral" => {
+                if let Some(kind) = self.attributes.get("spiType") {
+                    if kind != "clothoid" {
+                        return Err(format!("Unsupported spiral type: {}", kind));
+                    }
+                }
+
+                Ok(AlignmentElement::Spiral {
+                    start: self.point("Start")?,
+                    pi: self.point("PI")?,
+                    length: self
+                        .number("length")?
+                        .ok_or_else(|| "Spiral is missing length".to_string())?,
+                    radius_start: self.radius("radiusStart")?,
+                    radius_end: self.radius("radiusEnd")?,
+                    clockwise: self.clockwise()?,
+                })
+            }
+            other => Err(format!("Unsupported alignment element: {}", other)),
+        }
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).to_string()
+}
+
+fn attributes(element: &BytesStart) -> Result<HashMap<String, String>, String> {
+    let mut attributes = HashMap::new();
+
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+        attributes.insert(
+            String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string(),
+            value.to_string(),
+        );
+    }
+
+    Ok(attributes)
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid number: {}", value))
+}
+
+// LandXML points are "northing easting [elevation]"
+fn parse_point(text: &str) -> Result<(f64, f64), String> {
+    let values: Vec<&str> = text.split_whitespace().collect();
+
+    match values.as_slice() {
+        [x, y, ..] => Ok((parse_number(x)?, parse_number(y)?)),
+        _ => Err(format!("Invalid point: {}", text)),
+    }
+}
@@ -0,0 +1,556 @@
+pub(crate) mod ags;
+pub(crate) mod csv;
+pub(crate) mod diggs;
+pub(crate) mod dxf;
+pub(crate) mod geojson;
+#[cfg(feature = "geopackage")]
+pub(crate) mod geopackage;
+pub(crate) mod ifc;
+pub(crate) mod kml;
+
+use crate::infra_file::write::InfraToken;
+use crate::{
+    CoordinateSystem, Investigation, InvestigationCollection, ObservationValues, ParseResult,
+    Transformer,
+};
+
+use chrono::NaiveDate;
+use quick_xml::escape::escape;
+
+use std::fs;
+
+// Values shared by the exporters
+
+pub(crate) fn token<T: InfraToken>(value: &ParseResult<T>) -> Option<String> {
+    match value {
+        ParseResult::Parsed(value) => Some(value.infra_token()).filter(|token| token != "-"),
+        ParseResult::Fallback(original) => Some(original.clone()),
+        ParseResult::None => None,
+    }
+}
+
+pub(crate) fn parsed<T: Clone>(value: &ParseResult<T>) -> Option<T> {
+    match value {
+        ParseResult::Parsed(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+// Soil layers as "0.00-1.20 Sa; 1.20-3.40 Ct"
+pub(crate) fn soil_layer_summary(investigation: &Investigation) -> String {
+    investigation
+        .soil_layers
+        .iter()
+        .map(|layer| {
+            format!(
+                "{:.2}-{:.2} {}",
+                layer.top_depth, layer.bottom_depth, layer.soil_type
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// Position in `target`, or in the investigation's own system when None
+pub(crate) fn position(
+    investigation: &Investigation,
+    target: Option<&CoordinateSystem>,
+    transformer: &Transformer,
+) -> Option<[f64; 2]> {
+    match target {
+        Some(target) => {
+            InvestigationCollection::indexed_position(investigation, target, transformer)
+        }
+        None => investigation.coordinates.known_xy().map(|(x, y)| [x, y]),
+    }
+}
+
+// Coordinate system shared by all investigations
+pub(crate) fn common_coordinate_system(
+    investigations: &[Investigation],
+) -> Result<ParseResult<CoordinateSystem>, String> {
+    let mut systems = investigations
+        .iter()
+        .map(|investigation| &investigation.spatial.coordinate_system);
+
+    match systems.next() {
+        None => Ok(ParseResult::None),
+        Some(first) if systems.all(|system| system == first) => Ok(first.clone()),
+        Some(_) => Err(
+            "Investigations are in different coordinate systems, harmonise them or give a target system"
+                .to_string(),
+        ),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum FieldValue {
+    Real(f64),
+    Integer(i64),
+    Text(String),
+    Date(NaiveDate),
+}
+
+impl FieldValue {
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            FieldValue::Real(value) => Some(*value),
+            FieldValue::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Real(value) => write!(f, "{}", value),
+            FieldValue::Integer(value) => write!(f, "{}", value),
+            FieldValue::Text(value) => write!(f, "{}", value),
+            FieldValue::Date(value) => write!(f, "{}", value.format("%Y-%m-%d")),
+        }
+    }
+}
+
+pub(crate) trait ToFieldValue {
+    fn to_field_value(&self) -> FieldValue;
+}
+
+impl ToFieldValue for f32 {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Real(decimal(*self))
+    }
+}
+
+// Through the shortest decimal form so that 0.1f32 stays 0.1
+pub(crate) fn decimal(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+impl ToFieldValue for f64 {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Real(*self)
+    }
+}
+
+impl ToFieldValue for i32 {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Integer(*self as i64)
+    }
+}
+
+impl ToFieldValue for String {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Text(self.clone())
+    }
+}
+
+impl ToFieldValue for NaiveDate {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Date(*self)
+    }
+}
+
+pub(crate) fn text(value: Option<FieldValue>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+// Parsed and fallback values of a repeated field in one text
+pub(crate) fn joined(values: &[ParseResult<String>]) -> Option<FieldValue> {
+    let values: Vec<&str> = values
+        .iter()
+        .filter_map(|value| match value {
+            ParseResult::Parsed(value) | ParseResult::Fallback(value) => Some(value.as_str()),
+            ParseResult::None => None,
+        })
+        .collect();
+
+    (!values.is_empty()).then(|| FieldValue::Text(values.join(" | ")))
+}
+
+pub(crate) fn write(file_path: &str, content: impl AsRef<[u8]>) -> Result<(), String> {
+    fs::write(file_path, content)
+        .map_err(|e| format!("Failed to write file '{}': {}", file_path, e))
+}
+
+// Unparsed values are kept as their original text
+pub(crate) fn field<T: ToFieldValue>(value: &ParseResult<T>) -> Option<FieldValue> {
+    match value {
+        ParseResult::Parsed(value) => Some(value.to_field_value()),
+        ParseResult::Fallback(original) => Some(FieldValue::Text(original.clone())),
+        ParseResult::None => None,
+    }
+}
+
+// Every named value field of the observation variants, in column order
+pub(crate) const OBSERVATION_FIELDS: [&str; 35] = [
+    "depth",
+    "start_depth",
+    "end_depth",
+    "load",
+    "hits",
+    "half_turns",
+    "torque",
+    "shear_str",
+    "disturb_shear_str",
+    "sensitivity",
+    "residual_str",
+    "total_resistance",
+    "sleeve_friction",
+    "tip_resistance",
+    "pore_water_pressure",
+    "pressure",
+    "mode",
+    "time",
+    "advance_rate",
+    "compressive_force",
+    "flushing_pressure",
+    "water_consumption",
+    "rotation_speed",
+    "surface_elev",
+    "elev",
+    "pipe_top_elev",
+    "pipe_bot_elev",
+    "sieve_len",
+    "water_type",
+    "modulus",
+    "fail_pressure",
+    "stones",
+    "boulders",
+    "max_width",
+    "min_width",
+];
+
+// Fields of `OBSERVATION_FIELDS` holding the depth or the depth interval
+pub(crate) const DEPTH_FIELDS: [&str; 3] = ["depth", "start_depth", "end_depth"];
+
+// Named values of an observation row, see `OBSERVATION_FIELDS`. Soil type,
+// sample id, date and measurer are left to the caller.
+pub(crate) fn observation_fields(
+    values: &ObservationValues,
+) -> Vec<(&'static str, Option<FieldValue>)> {
+    match values {
+        ObservationValues::None => Vec::new(),
+        ObservationValues::PA {
+            depth,
+            load,
+            hits,
+            half_turns,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("load", field(load)),
+            ("hits", field(hits)),
+            ("half_turns", field(half_turns)),
+        ],
+        ObservationValues::PI { depth, .. }
+        | ObservationValues::PT { depth, .. }
+        | ObservationValues::TR { depth, .. } => vec![("depth", field(depth))],
+        ObservationValues::LY {
+            depth, load, hits, ..
+        } => vec![
+            ("depth", field(depth)),
+            ("load", field(load)),
+            ("hits", field(hits)),
+        ],
+        ObservationValues::SI {
+            depth,
+            shear_str,
+            disturb_shear_str,
+            sensitivity,
+            residual_str,
+        } => vec![
+            ("depth", field(depth)),
+            ("shear_str", field(shear_str)),
+            ("disturb_shear_str", field(disturb_shear_str)),
+            ("sensitivity", field(sensitivity)),
+            ("residual_str", field(residual_str)),
+        ],
+        ObservationValues::HE { depth, hits, .. } => {
+            vec![("depth", field(depth)), ("hits", field(hits))]
+        }
+        ObservationValues::HK {
+            depth,
+            hits,
+            torque,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("hits", field(hits)),
+            ("torque", field(torque)),
+        ],
+        ObservationValues::PR {
+            depth,
+            total_resistance,
+            sleeve_friction,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("total_resistance", field(total_resistance)),
+            ("sleeve_friction", field(sleeve_friction)),
+        ],
+        ObservationValues::CP {
+            depth,
+            total_resistance,
+            sleeve_friction,
+            tip_resistance,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("total_resistance", field(total_resistance)),
+            ("sleeve_friction", field(sleeve_friction)),
+            ("tip_resistance", field(tip_resistance)),
+        ],
+        ObservationValues::CU {
+            depth,
+            total_resistance,
+            sleeve_friction,
+            tip_resistance,
+            pore_water_pressure,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("total_resistance", field(total_resistance)),
+            ("sleeve_friction", field(sleeve_friction)),
+            ("tip_resistance", field(tip_resistance)),
+            ("pore_water_pressure", field(pore_water_pressure)),
+        ],
+        ObservationValues::HP {
+            depth,
+            hits,
+            pressure,
+            torque,
+            mode,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("hits", field(hits)),
+            ("pressure", field(pressure)),
+            ("torque", field(torque)),
+            ("mode", field(mode)),
+        ],
+        ObservationValues::PO { depth, time, .. } => {
+            vec![("depth", field(depth)), ("time", field(time))]
+        }
+        ObservationValues::MW {
+            depth,
+            advance_rate,
+            compressive_force,
+            flushing_pressure,
+            water_consumption,
+            torque,
+            rotation_speed,
+            hits,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("advance_rate", field(advance_rate)),
+            ("compressive_force", field(compressive_force)),
+            ("flushing_pressure", field(flushing_pressure)),
+            ("water_consumption", field(water_consumption)),
+            ("torque", field(torque)),
+            ("rotation_speed", field(rotation_speed)),
+            // The parser keeps MW hits as text, numeric ones share the
+            // integer hits column with the other methods
+            (
+                "hits",
+                match hits {
+                    ParseResult::Parsed(value) => value
+                        .parse::<i32>()
+                        .ok()
+                        .map(|hits| hits.to_field_value())
+                        .or_else(|| field(hits)),
+                    _ => field(hits),
+                },
+            ),
+        ],
+        ObservationValues::VP {
+            surface_elev,
+            pipe_top_elev,
+            pipe_bot_elev,
+            sieve_len,
+            ..
+        }
+        | ObservationValues::VO {
+            surface_elev,
+            pipe_top_elev,
+            pipe_bot_elev,
+            sieve_len,
+            ..
+        }
+        | ObservationValues::HU {
+            surface_elev,
+            pipe_top_elev,
+            pipe_bot_elev,
+            sieve_len,
+            ..
+        } => vec![
+            ("surface_elev", field(surface_elev)),
+            ("pipe_top_elev", field(pipe_top_elev)),
+            ("pipe_bot_elev", field(pipe_bot_elev)),
+            ("sieve_len", field(sieve_len)),
+        ],
+        ObservationValues::VK {
+            surface_elev,
+            water_type,
+            ..
+        } => vec![
+            ("surface_elev", field(surface_elev)),
+            ("water_type", field(water_type)),
+        ],
+        ObservationValues::VPK { surface_elev, .. } => {
+            vec![("surface_elev", field(surface_elev))]
+        }
+        ObservationValues::HV {
+            depth, pressure, ..
+        } => vec![("depth", field(depth)), ("pressure", field(pressure))],
+        ObservationValues::PS {
+            depth,
+            modulus,
+            fail_pressure,
+        } => vec![
+            ("depth", field(depth)),
+            ("modulus", field(modulus)),
+            ("fail_pressure", field(fail_pressure)),
+        ],
+        ObservationValues::PM { elev, .. } => vec![("elev", field(elev))],
+        ObservationValues::KO {
+            depth,
+            stones,
+            boulders,
+            max_width,
+            min_width,
+            ..
+        } => vec![
+            ("depth", field(depth)),
+            ("stones", field(stones)),
+            ("boulders", field(boulders)),
+            ("max_width", field(max_width)),
+            ("min_width", field(min_width)),
+        ],
+        ObservationValues::KE {
+            start_depth,
+            end_depth,
+        }
+        | ObservationValues::KR {
+            start_depth,
+            end_depth,
+        }
+        | ObservationValues::NO {
+            start_depth,
+            end_depth,
+            ..
+        }
+        | ObservationValues::NE {
+            start_depth,
+            end_depth,
+            ..
+        } => vec![
+            ("start_depth", field(start_depth)),
+            ("end_depth", field(end_depth)),
+        ],
+    }
+}
+
+// Date and measurer of monitoring observations, sample id of samples
+pub(crate) fn observation_date(values: &ObservationValues) -> Option<FieldValue> {
+    match values {
+        ObservationValues::VP { date, .. }
+        | ObservationValues::VO { date, .. }
+        | ObservationValues::VK { date, .. }
+        | ObservationValues::VPK { date, .. }
+        | ObservationValues::HV { date, .. }
+        | ObservationValues::HU { date, .. }
+        | ObservationValues::PM { date, .. } => field(date),
+        _ => None,
+    }
+}
+
+pub(crate) fn observation_measurer(values: &ObservationValues) -> Option<FieldValue> {
+    match values {
+        ObservationValues::VP { measurer, .. }
+        | ObservationValues::VO { measurer, .. }
+        | ObservationValues::HV { measurer, .. }
+        | ObservationValues::HU { measurer, .. }
+        | ObservationValues::PM { measurer, .. } => field(measurer),
+        _ => None,
+    }
+}
+
+pub(crate) fn sample_id(values: &ObservationValues) -> Option<FieldValue> {
+    match values {
+        ObservationValues::NO { sample_id, .. } | ObservationValues::NE { sample_id, .. } => {
+            field(sample_id)
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn soil_type(values: &ObservationValues) -> Option<FieldValue> {
+    match values {
+        ObservationValues::PA { soil_type, .. }
+        | ObservationValues::PI { soil_type, .. }
+        | ObservationValues::LY { soil_type, .. }
+        | ObservationValues::HE { soil_type, .. }
+        | ObservationValues::HK { soil_type, .. }
+        | ObservationValues::PT { soil_type, .. }
+        | ObservationValues::TR { soil_type, .. }
+        | ObservationValues::PR { soil_type, .. }
+        | ObservationValues::CP { soil_type, .. }
+        | ObservationValues::CU { soil_type, .. }
+        | ObservationValues::HP { soil_type, .. }
+        | ObservationValues::PO { soil_type, .. }
+        | ObservationValues::MW { soil_type, .. }
+        | ObservationValues::KO { soil_type, .. }
+        | ObservationValues::NO { soil_type, .. }
+        | ObservationValues::NE { soil_type, .. } => field(soil_type),
+        _ => None,
+    }
+}
+
+// Indented XML with escaped text and attribute values
+#[derive(Default)]
+pub(crate) struct XmlWriter {
+    pub(crate) output: String,
+    stack: Vec<String>,
+}
+
+impl XmlWriter {
+    fn indent(&mut self) {
+        self.output.push_str(&"  ".repeat(self.stack.len()));
+    }
+
+    fn start_tag(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.indent();
+        self.output.push('<');
+        self.output.push_str(name);
+        for (key, value) in attributes {
+            self.output
+                .push_str(&format!(" {}=\"{}\"", key, escape(*value)));
+        }
+    }
+
+    pub(crate) fn open(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.start_tag(name, attributes);
+        self.output.push_str(">\n");
+        self.stack.push(name.to_string());
+    }
+
+    pub(crate) fn close(&mut self) {
+        if let Some(name) = self.stack.pop() {
+            self.indent();
+            self.output.push_str(&format!("</{}>\n", name));
+        }
+    }
+
+    pub(crate) fn empty(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.start_tag(name, attributes);
+        self.output.push_str("/>\n");
+    }
+
+    pub(crate) fn text(&mut self, name: &str, attributes: &[(&str, &str)], text: &str) {
+        self.start_tag(name, attributes);
+        self.output
+            .push_str(&format!(">{}</{}>\n", escape(text), name));
+    }
+}
@@ -0,0 +1,1194 @@
+use crate::export::{parsed, position, token, write};
+use crate::{
+    CoordinateSystem, InfraFile, Investigation, InvestigationCollection, LabResult, MethodToken,
+    ObservationValues, ParseResult, Transformer,
+};
+
+use chrono::Local;
+
+use std::collections::{BTreeMap, HashSet};
+
+// AGS4 groups are written as quoted CSV with CRLF line endings. Numeric
+// headings only take parsed values, fallback text is left out.
+
+struct Group {
+    name: &'static str,
+    // Heading, unit and data type
+    headings: Vec<(&'static str, &'static str, &'static str)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Group {
+    fn new(name: &'static str, headings: &[(&'static str, &'static str, &'static str)]) -> Self {
+        Group {
+            name,
+            headings: headings.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+}
+
+// Codes of PA (pick list) headings, documented in ABBR
+#[derive(Default)]
+struct Abbreviations {
+    codes: BTreeMap<(&'static str, String), String>,
+}
+
+impl Abbreviations {
+    fn code(&mut self, heading: &'static str, code: &str, description: &str) -> String {
+        if !code.is_empty() {
+            self.codes
+                .entry((heading, code.to_string()))
+                .or_insert_with(|| description.to_string());
+        }
+        code.to_string()
+    }
+}
+
+impl InvestigationCollection {
+    pub fn to_ags(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_ags_with(target, &Transformer::default())
+    }
+
+    pub fn to_ags_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        ags(&self.investigations, target, transformer)
+    }
+
+    pub fn write_ags(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_ags(target)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_ags(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_ags_with(target, &Transformer::default())
+    }
+
+    pub fn to_ags_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        ags(&self.investigations, target, transformer)
+    }
+
+    pub fn write_ags(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_ags(target)?)
+    }
+}
+
+fn number(value: Option<f32>, decimals: usize) -> String {
+    value
+        .map(|value| format!("{:.*}", decimals, value))
+        .unwrap_or_default()
+}
+
+fn float(value: &ParseResult<f32>, decimals: usize) -> String {
+    number(parsed(value), decimals)
+}
+
+fn significant(value: Option<f32>, figures: i32) -> String {
+    match value {
+        Some(value) if value != 0.0 => {
+            let magnitude = value.abs().log10().floor() as i32;
+            number(Some(value), (figures - 1 - magnitude).max(0) as usize)
+        }
+        Some(value) => number(Some(value), (figures - 1) as usize),
+        None => String::new(),
+    }
+}
+
+fn integer(value: &ParseResult<i32>) -> String {
+    parsed(value)
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+fn text<T: crate::infra_file::write::InfraToken>(value: &ParseResult<T>) -> String {
+    token(value).unwrap_or_default()
+}
+
+fn remarks(parts: &[(&str, String)]) -> String {
+    parts
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn record(fields: &[&str]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            format!(
+                "\"{}\"",
+                field.replace(['\r', '\n'], " ").replace('"', "\"\"")
+            )
+        })
+        .collect();
+    format!("{}\r\n", quoted.join(","))
+}
+
+fn method_name(method: &ParseResult<MethodToken>) -> String {
+    match method {
+        ParseResult::Parsed(method) => method.to_string(),
+        _ => "Unknown method".to_string(),
+    }
+}
+
+// LOCA_TYPE from the AGS abbreviation list where one fits. Other methods get
+// a user-defined "FI-" code, so that an InfraModel token never takes the
+// meaning of a standard code, e.g. CP is cable percussion in AGS.
+fn loca_type(method: &ParseResult<MethodToken>) -> (String, String) {
+    let standard = match method {
+        ParseResult::Parsed(MethodToken::CP | MethodToken::CU) => {
+            Some(("CPT", "Cone penetration test"))
+        }
+        ParseResult::Parsed(MethodToken::HE | MethodToken::HK | MethodToken::LY) => {
+            Some(("DP", "Dynamic probe"))
+        }
+        ParseResult::Parsed(MethodToken::KO) => Some(("TP", "Trial pit/trench")),
+        ParseResult::Parsed(MethodToken::KE | MethodToken::KR) => Some(("RC", "Rotary core")),
+        ParseResult::Parsed(MethodToken::PO | MethodToken::MW) => Some(("RO", "Rotary open hole")),
+        _ => None,
+    };
+
+    match standard {
+        Some((code, description)) => (code.to_string(), description.to_string()),
+        None if text(method).is_empty() => (String::new(), String::new()),
+        None => (
+            format!("FI-{}", text(method)),
+            format!("{} (InfraModel {})", method_name(method), text(method)),
+        ),
+    }
+}
+
+fn ags(
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+) -> Result<String, String> {
+    let mut abbreviations = Abbreviations::default();
+
+    let mut loca = Group::new(
+        "LOCA",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("LOCA_TYPE", "", "PA"),
+            ("LOCA_STAT", "", "PA"),
+            ("LOCA_NATE", "m", "2DP"),
+            ("LOCA_NATN", "m", "2DP"),
+            ("LOCA_GREF", "", "PA"),
+            ("LOCA_GL", "m", "2DP"),
+            ("LOCA_DATM", "", "PA"),
+            ("LOCA_REM", "", "X"),
+            ("LOCA_FDEP", "m", "2DP"),
+            ("LOCA_STAR", "yyyy-mm-dd", "DT"),
+            ("LOCA_TERM", "", "X"),
+        ],
+    );
+    let mut dprg = Group::new(
+        "DPRG",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("DPRG_TYPE", "", "PA"),
+            ("DPRG_REM", "", "X"),
+        ],
+    );
+    let mut dprb = Group::new(
+        "DPRB",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("DPRB_DEP", "m", "2DP"),
+            ("DPRB_BLOW", "", "0DP"),
+            ("DPRB_TORQ", "Nm", "0DP"),
+            ("DPRB_REM", "", "X"),
+        ],
+    );
+    // AGS4 has no weight sounding groups, these are defined in DICT
+    let mut wstg = Group::new(
+        "WSTG",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("WSTG_TYPE", "", "PA"),
+            ("WSTG_REM", "", "X"),
+        ],
+    );
+    let mut wstb = Group::new(
+        "WSTB",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("WSTB_DEP", "m", "2DP"),
+            ("WSTB_LOAD", "kN", "2DP"),
+            ("WSTB_HTRN", "", "0DP"),
+            ("WSTB_BLOW", "", "0DP"),
+            ("WSTB_REM", "", "X"),
+        ],
+    );
+    let mut scpg = Group::new(
+        "SCPG",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("SCPG_TESN", "", "X"),
+            ("SCPG_TYPE", "", "PA"),
+            ("SCPG_REM", "", "X"),
+        ],
+    );
+    let mut scpt = Group::new(
+        "SCPT",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("SCPG_TESN", "", "X"),
+            ("SCPT_DPTH", "m", "2DP"),
+            ("SCPT_RES", "MPa", "2DP"),
+            ("SCPT_FRES", "kPa", "1DP"),
+            ("SCPT_PWP2", "kPa", "1DP"),
+            ("SCPT_REM", "", "X"),
+        ],
+    );
+    let mut ivan = Group::new(
+        "IVAN",
+        &[
+            ("LOCA_ID", "", "ID"),
+            ("IVAN_TESN", "", "X"),
+            ("IVAN_DPTH", "m", "2DP"),
+            ("IVAN_TYPE", "", "PA"),
+            ("IVAN_IVAN", "kPa", "1DP"),
+            ("IVAN_IVAR", "kPa", "1DP"),
+            ("IVAN_REM", "", "X"),
+        ],
+    );
+    let sample_keys = [
+        ("LOCA_ID", "", "ID"),
+        ("SAMP_TOP", "m", "2DP"),
+        ("SAMP_REF", "", "X"),
+        ("SAMP_TYPE", "", "PA"),
+        ("SAMP_ID", "", "ID"),
+    ];
+    let specimen_keys = [
+        sample_keys.as_slice(),
+        &[("SPEC_REF", "", "X"), ("SPEC_DPTH", "m", "2DP")],
+    ]
+    .concat();
+    let with_keys = |keys: &[(&'static str, &'static str, &'static str)],
+                     headings: &[(&'static str, &'static str, &'static str)]| {
+        [keys, headings].concat()
+    };
+    let mut samp = Group::new(
+        "SAMP",
+        &with_keys(
+            &sample_keys,
+            &[
+                ("SAMP_BASE", "m", "2DP"),
+                ("SAMP_DESC", "", "X"),
+                ("SAMP_REM", "", "X"),
+            ],
+        ),
+    );
+    let mut grag = Group::new("GRAG", &with_keys(&specimen_keys, &[("GRAG_REM", "", "X")]));
+    let mut grat = Group::new(
+        "GRAT",
+        &with_keys(
+            &specimen_keys,
+            &[("GRAT_SIZE", "mm", "3SF"), ("GRAT_PERP", "%", "0DP")],
+        ),
+    );
+    let mut lnmc = Group::new(
+        "LNMC",
+        &with_keys(&specimen_keys, &[("LNMC_MC", "%", "2SF")]),
+    );
+    let mut llpl = Group::new(
+        "LLPL",
+        &with_keys(
+            &specimen_keys,
+            &[
+                ("LLPL_LL", "%", "0DP"),
+                ("LLPL_PL", "%", "0DP"),
+                ("LLPL_PI", "%", "0DP"),
+            ],
+        ),
+    );
+    let mut lden = Group::new(
+        "LDEN",
+        &with_keys(&specimen_keys, &[("LDEN_BDEN", "Mg/m3", "2DP")]),
+    );
+    let mut lloi = Group::new(
+        "LLOI",
+        &with_keys(&specimen_keys, &[("LLOI_LOI", "%", "1DP")]),
+    );
+
+    let mut identifiers: HashSet<String> = HashSet::new();
+
+    for (index, investigation) in investigations.iter().enumerate() {
+        let method = &investigation.method.token;
+        let method_code = text(method);
+        let method_description = method_name(method);
+        let loca_type = loca_type(method);
+
+        // LOCA_ID is the point id, made unique with the method and index
+        let point_id = token(&investigation.coordinates.point_id)
+            .unwrap_or_else(|| format!("LOC{}", index + 1));
+        let loca_id = [
+            point_id.clone(),
+            format!("{}-{}", point_id, method_code),
+            format!("{}-{}", point_id, index + 1),
+        ]
+        .into_iter()
+        .find(|candidate| !identifiers.contains(candidate))
+        .unwrap_or_else(|| format!("{}-{}-{}", point_id, method_code, index + 1));
+        identifiers.insert(loca_id.clone());
+
+        let output_system = match &target {
+            Some(target) => Some(target.clone()),
+            None => parsed(&investigation.spatial.coordinate_system),
+        };
+        let decimals = if output_system == Some(CoordinateSystem::WGS84) {
+            7
+        } else {
+            2
+        };
+        let [northing, easting] = match position(investigation, target.as_ref(), transformer) {
+            Some([x, y]) => [format!("{:.*}", decimals, x), format!("{:.*}", decimals, y)],
+            None => [String::new(), String::new()],
+        };
+        let grid = match &output_system {
+            Some(system) => abbreviations.code(
+                "LOCA_GREF",
+                &system.to_string(),
+                &system
+                    .epsg()
+                    .map(|code| format!("{}, EPSG:{}", system, code))
+                    .unwrap_or_else(|| system.to_string()),
+            ),
+            None => String::new(),
+        };
+        let datum = match &investigation.spatial.elevation_system {
+            ParseResult::Parsed(system) => abbreviations.code(
+                "LOCA_DATM",
+                &system.to_string(),
+                &system
+                    .epsg()
+                    .map(|code| format!("{}, EPSG:{}", system, code))
+                    .unwrap_or_else(|| system.to_string()),
+            ),
+            _ => String::new(),
+        };
+        let termination = match &investigation.termination.token {
+            ParseResult::Parsed(termination) => {
+                format!(
+                    "{} ({})",
+                    text(&investigation.termination.token),
+                    termination
+                )
+            }
+            other => text(other),
+        };
+
+        loca.push(vec![
+            loca_id.clone(),
+            abbreviations.code("LOCA_TYPE", &loca_type.0, &loca_type.1),
+            abbreviations.code("LOCA_STAT", "FINAL", "Final"),
+            easting,
+            northing,
+            grid,
+            float(&investigation.coordinates.start_elevation, 2),
+            datum,
+            remarks(&[
+                ("Work", text(&investigation.work.id)),
+                ("Owner", text(&investigation.organisations.owner_name)),
+                (
+                    "Investigator",
+                    text(&investigation.organisations.investigator_name),
+                ),
+            ]),
+            number(investigation.total_depth, 2),
+            parsed(&investigation.coordinates.date)
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            termination,
+        ]);
+
+        // Probe and test groups have one general row per location
+        let mut has_observations = false;
+        let mut sample_count = 0;
+
+        for observation in &investigation.observations {
+            has_observations = true;
+            match &observation.values {
+                ObservationValues::HE { depth, hits, .. } => dprb.push(vec![
+                    loca_id.clone(),
+                    float(depth, 2),
+                    integer(hits),
+                    String::new(),
+                    String::new(),
+                ]),
+                ObservationValues::HK {
+                    depth,
+                    hits,
+                    torque,
+                    ..
+                } => dprb.push(vec![
+                    loca_id.clone(),
+                    float(depth, 2),
+                    integer(hits),
+                    float(torque, 0),
+                    String::new(),
+                ]),
+                ObservationValues::LY {
+                    depth, load, hits, ..
+                } => dprb.push(vec![
+                    loca_id.clone(),
+                    float(depth, 2),
+                    integer(hits),
+                    String::new(),
+                    remarks(&[("Load (kN)", float(load, 2))]),
+                ]),
+                ObservationValues::PA {
+                    depth,
+                    load,
+                    hits,
+                    half_turns,
+                    ..
+                } => wstb.push(vec![
+                    loca_id.clone(),
+                    float(depth, 2),
+                    float(load, 2),
+                    integer(half_turns),
+                    integer(hits),
+                    String::new(),
+                ]),
+                ObservationValues::CP {
+                    depth,
+                    total_resistance,
+                    sleeve_friction,
+                    tip_resistance,
+                    ..
+                } => scpt.push(vec![
+                    loca_id.clone(),
+                    "1".to_string(),
+                    float(depth, 2),
+                    float(tip_resistance, 2),
+                    float(sleeve_friction, 1),
+                    String::new(),
+                    remarks(&[("Total resistance (kN)", float(total_resistance, 2))]),
+                ]),
+                ObservationValues::CU {
+                    depth,
+                    total_resistance,
+                    sleeve_friction,
+                    tip_resistance,
+                    pore_water_pressure,
+                    ..
+                } => scpt.push(vec![
+                    loca_id.clone(),
+                    "1".to_string(),
+                    float(depth, 2),
+                    float(tip_resistance, 2),
+                    float(sleeve_friction, 1),
+                    float(pore_water_pressure, 1),
+                    remarks(&[("Total resistance (kN)", float(total_resistance, 2))]),
+                ]),
+                ObservationValues::SI {
+                    depth,
+                    shear_str,
+                    disturb_shear_str,
+                    sensitivity,
+                    residual_str,
+                } => ivan.push(vec![
+                    loca_id.clone(),
+                    (ivan.rows.len() + 1).to_string(),
+                    float(depth, 2),
+                    abbreviations.code("IVAN_TYPE", "SI", &MethodToken::SI.to_string()),
+                    float(shear_str, 1),
+                    float(disturb_shear_str, 1),
+                    remarks(&[
+                        ("Sensitivity", float(sensitivity, 1)),
+                        ("Residual strength (kPa)", float(residual_str, 1)),
+                    ]),
+                ]),
+                ObservationValues::NO {
+                    start_depth,
+                    sample_id,
+                    end_depth,
+                    soil_type,
+                    lab_values,
+                }
+                | ObservationValues::NE {
+                    start_depth,
+                    sample_id,
+                    end_depth,
+                    soil_type,
+                    lab_values,
+                } => {
+                    sample_count += 1;
+                    let (sample_type, description) =
+                        if matches!(observation.values, ObservationValues::NE { .. }) {
+                            ("U", "Undisturbed sample")
+                        } else {
+                            ("D", "Disturbed sample")
+                        };
+                    let top = float(start_depth, 2);
+                    let reference = token(sample_id).unwrap_or_else(|| sample_count.to_string());
+                    let keys = vec![
+                        loca_id.clone(),
+                        top.clone(),
+                        reference.clone(),
+                        abbreviations.code("SAMP_TYPE", sample_type, description),
+                        format!("{}-{}", loca_id, reference),
+                    ];
+                    let specimen = [keys.clone(), vec!["1".to_string(), top]].concat();
+
+                    let mut unmatched = Vec::new();
+                    let mut grain_size = false;
+                    let mut limits: [Option<f32>; 2] = [None, None];
+
+                    for lab_value in lab_values {
+                        let ParseResult::Parsed(lab_value) = lab_value else {
+                            continue;
+                        };
+                        match lab_value {
+                            LabResult::GrainSize {
+                                grain_mm,
+                                pass_percent,
+                            } => {
+                                grain_size = true;
+                                grat.push(
+                                    [
+                                        specimen.clone(),
+                                        vec![
+                                            significant(parsed(grain_mm), 3),
+                                            float(pass_percent, 0),
+                                        ],
+                                    ]
+                                    .concat(),
+                                );
+                            }
+                            LabResult::WaterContent { water_content } => lnmc.push(
+                                [
+                                    specimen.clone(),
+                                    vec![significant(parsed(water_content), 2)],
+                                ]
+                                .concat(),
+                            ),
+                            LabResult::Other {
+                                attribute,
+                                result,
+                                unit,
+                            } => {
+                                let value = parsed(result).and_then(|result| {
+                                    result.replace(',', ".").parse::<f32>().ok()
+                                });
+                                let unit = text(unit);
+                                match (lab_heading(&text(attribute)), value) {
+                                    (Some("LNMC_MC"), Some(value)) => lnmc.push(
+                                        [specimen.clone(), vec![significant(Some(value), 2)]]
+                                            .concat(),
+                                    ),
+                                    (Some("LLPL_LL"), Some(value)) => limits[0] = Some(value),
+                                    (Some("LLPL_PL"), Some(value)) => limits[1] = Some(value),
+                                    (Some("LDEN_BDEN"), Some(value)) => {
+                                        // Densities are often given in kg/m3
+                                        let value = if unit.to_lowercase().starts_with("kg") {
+                                            value / 1000.0
+                                        } else {
+                                            value
+                                        };
+                                        lden.push(
+                                            [specimen.clone(), vec![number(Some(value), 2)]]
+                                                .concat(),
+                                        )
+                                    }
+                                    (Some("LLOI_LOI"), Some(value)) => lloi.push(
+                                        [specimen.clone(), vec![number(Some(value), 1)]].concat(),
+                                    ),
+                                    _ => unmatched.push(
+                                        format!("{} {} {}", text(attribute), text(result), unit)
+                                            .trim()
+                                            .to_string(),
+                                    ),
+                                }
+                            }
+                            LabResult::None => {}
+                        }
+                    }
+
+                    if grain_size {
+                        grag.push([specimen.clone(), vec![String::new()]].concat());
+                    }
+                    if limits.iter().any(Option::is_some) {
+                        let index = match limits {
+                            [Some(liquid), Some(plastic)] => Some(liquid - plastic),
+                            _ => None,
+                        };
+                        llpl.push(
+                            [
+                                specimen.clone(),
+                                vec![number(limits[0], 0), number(limits[1], 0), number(index, 0)],
+                            ]
+                            .concat(),
+                        );
+                    }
+
+                    samp.push(
+                        [
+                            keys,
+                            vec![
+                                float(end_depth, 2),
+                                text(soil_type),
+                                // Lab values without an AGS4 group
+                                unmatched.join("; "),
+                            ],
+                        ]
+                        .concat(),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if !has_observations {
+            continue;
+        }
+
+        match parsed(method) {
+            Some(MethodToken::HE | MethodToken::HK | MethodToken::LY) => dprg.push(vec![
+                loca_id.clone(),
+                abbreviations.code("DPRG_TYPE", &method_code, &method_description),
+                "Blows per 0.2 m".to_string(),
+            ]),
+            Some(MethodToken::PA) => wstg.push(vec![
+                loca_id.clone(),
+                abbreviations.code("WSTG_TYPE", &method_code, &method_description),
+                "Half turns per 0.2 m".to_string(),
+            ]),
+            Some(MethodToken::CP | MethodToken::CU) => scpg.push(vec![
+                loca_id.clone(),
+                "1".to_string(),
+                abbreviations.code("SCPG_TYPE", &method_code, &method_description),
+                String::new(),
+            ]),
+            _ => {}
+        }
+    }
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let first = investigations.first();
+    let first_text = |value: fn(&Investigation) -> &ParseResult<String>| {
+        first
+            .map(|investigation| text(value(investigation)))
+            .unwrap_or_default()
+    };
+
+    let mut proj = Group::new(
+        "PROJ",
+        &[
+            ("PROJ_ID", "", "ID"),
+            ("PROJ_NAME", "", "X"),
+            ("PROJ_CLNT", "", "X"),
+            ("PROJ_CONT", "", "X"),
+            ("PROJ_MEMO", "", "X"),
+        ],
+    );
+    let project_id = first_text(|investigation| &investigation.work.id);
+    proj.push(vec![
+        if project_id.is_empty() {
+            "1".to_string()
+        } else {
+            project_id
+        },
+        first_text(|investigation| &investigation.work.name),
+        first_text(|investigation| &investigation.organisations.owner_name),
+        first_text(|investigation| &investigation.organisations.investigator_name),
+        "Converted from InfraModel ground investigation data".to_string(),
+    ]);
+
+    let mut tran = Group::new(
+        "TRAN",
+        &[
+            ("TRAN_ISNO", "", "X"),
+            ("TRAN_DATE", "yyyy-mm-dd", "DT"),
+            ("TRAN_PROD", "", "X"),
+            ("TRAN_STAT", "", "PA"),
+            ("TRAN_DESC", "", "X"),
+            ("TRAN_AGS", "", "X"),
+            ("TRAN_RECSEP", "", "X"),
+            ("TRAN_DLIM", "", "X"),
+            ("TRAN_RCON", "", "X"),
+        ],
+    );
+    tran.push(vec![
+        "1".to_string(),
+        today,
+        first_text(|investigation| &investigation.organisations.investigator_name),
+        abbreviations.code("TRAN_STAT", "FINAL", "Final"),
+        "Ground investigations".to_string(),
+        "4.1".to_string(),
+        ",".to_string(),
+        "\"".to_string(),
+        "+".to_string(),
+    ]);
+
+    let mut dict = Group::new(
+        "DICT",
+        &[
+            ("DICT_TYPE", "", "PA"),
+            ("DICT_GRP", "", "X"),
+            ("DICT_HDNG", "", "X"),
+            ("DICT_STAT", "", "PA"),
+            ("DICT_DTYP", "", "PT"),
+            ("DICT_DESC", "", "X"),
+            ("DICT_UNIT", "", "PU"),
+            ("DICT_PGRP", "", "X"),
+        ],
+    );
+    if !wstg.rows.is_empty() {
+        let definitions = [
+            (
+                "GROUP",
+                "WSTG",
+                "",
+                "",
+                "",
+                "Weight sounding test - General",
+                "",
+                "LOCA",
+            ),
+            (
+                "HEADING",
+                "WSTG",
+                "LOCA_ID",
+                "KEY",
+                "ID",
+                "Location identifier",
+                "",
+                "",
+            ),
+            (
+                "HEADING",
+                "WSTG",
+                "WSTG_TYPE",
+                "OTHER",
+                "PA",
+                "Test type",
+                "",
+                "",
+            ),
+            (
+                "HEADING", "WSTG", "WSTG_REM", "OTHER", "X", "Remarks", "", "",
+            ),
+            (
+                "GROUP",
+                "WSTB",
+                "",
+                "",
+                "",
+                "Weight sounding test - Results",
+                "",
+                "WSTG",
+            ),
+            (
+                "HEADING",
+                "WSTB",
+                "LOCA_ID",
+                "KEY",
+                "ID",
+                "Location identifier",
+                "",
+                "",
+            ),
+            (
+                "HEADING", "WSTB", "WSTB_DEP", "KEY", "2DP", "Depth", "m", "",
+            ),
+            (
+                "HEADING",
+                "WSTB",
+                "WSTB_LOAD",
+                "OTHER",
+                "2DP",
+                "Load",
+                "kN",
+                "",
+            ),
+            (
+                "HEADING",
+                "WSTB",
+                "WSTB_HTRN",
+                "OTHER",
+                "0DP",
+                "Half turns per 0.2 m",
+                "",
+                "",
+            ),
+            (
+                "HEADING",
+                "WSTB",
+                "WSTB_BLOW",
+                "OTHER",
+                "0DP",
+                "Blows per 0.2 m",
+                "",
+                "",
+            ),
+            (
+                "HEADING", "WSTB", "WSTB_REM", "OTHER", "X", "Remarks", "", "",
+            ),
+        ];
+        for (kind, group, heading, status, data_type, description, unit, parent) in definitions {
+            dict.push(vec![
+                abbreviations.code(
+                    "DICT_TYPE",
+                    kind,
+                    if kind == "GROUP" {
+                        "Flag to indicate definition is a GROUP"
+                    } else {
+                        "Flag to indicate definition is a HEADING"
+                    },
+                ),
+                group.to_string(),
+                heading.to_string(),
+                abbreviations.code(
+                    "DICT_STAT",
+                    status,
+                    if status == "KEY" {
+                        "Key field"
+                    } else {
+                        "Other field"
+                    },
+                ),
+                data_type.to_string(),
+                description.to_string(),
+                unit.to_string(),
+                parent.to_string(),
+            ]);
+        }
+    }
+
+    let data_groups = [
+        loca, dprg, dprb, wstg, wstb, scpg, scpt, ivan, samp, grag, grat, lnmc, llpl, lden, lloi,
+    ];
+    let mut groups: Vec<Group> = vec![proj, tran];
+    if !dict.rows.is_empty() {
+        groups.push(dict);
+    }
+    groups.extend(
+        data_groups
+            .into_iter()
+            .filter(|group| !group.rows.is_empty()),
+    );
+
+    let mut abbr = Group::new(
+        "ABBR",
+        &[
+            ("ABBR_HDNG", "", "X"),
+            ("ABBR_CODE", "", "X"),
+            ("ABBR_DESC", "", "X"),
+        ],
+    );
+    for ((heading, code), description) in &abbreviations.codes {
+        abbr.push(vec![heading.to_string(), code.clone(), description.clone()]);
+    }
+
+    let mut unit = Group::new("UNIT", &[("UNIT_UNIT", "", "X"), ("UNIT_DESC", "", "X")]);
+    let mut type_group = Group::new("TYPE", &[("TYPE_TYPE", "", "X"), ("TYPE_DESC", "", "X")]);
+
+    let all_headings: Vec<(&str, &str, &str)> = groups
+        .iter()
+        .chain([&abbr])
+        .flat_map(|group| group.headings.iter().copied())
+        .chain(unit.headings.iter().copied())
+        .collect();
+
+    let mut units: Vec<&str> = all_headings.iter().map(|heading| heading.1).collect();
+    units.sort();
+    units.dedup();
+    for value in units.into_iter().filter(|unit| !unit.is_empty()) {
+        unit.push(vec![value.to_string(), unit_description(value).to_string()]);
+    }
+
+    let mut types: Vec<&str> = all_headings.iter().map(|heading| heading.2).collect();
+    types.sort();
+    types.dedup();
+    for value in types {
+        type_group.push(vec![value.to_string(), type_description(value).to_string()]);
+    }
+
+    let mut output = String::new();
+    let ordered = groups
+        .iter()
+        .take(2)
+        .chain([&unit, &type_group, &abbr])
+        .chain(groups.iter().skip(2));
+
+    for group in ordered {
+        if !output.is_empty() {
+            output.push_str("\r\n");
+        }
+        output.push_str(&record(&["GROUP", group.name]));
+
+        let mut heading = vec!["HEADING"];
+        let mut units = vec!["UNIT"];
+        let mut types = vec!["TYPE"];
+        for (name, unit, data_type) in &group.headings {
+            heading.push(name);
+            units.push(unit);
+            types.push(data_type);
+        }
+        output.push_str(&record(&heading));
+        output.push_str(&record(&units));
+        output.push_str(&record(&types));
+
+        for row in &group.rows {
+            let mut data = vec!["DATA"];
+            data.extend(row.iter().map(String::as_str));
+            output.push_str(&record(&data));
+        }
+    }
+
+    Ok(output)
+}
+
+// Lab attributes of LB rows with a matching AGS4 heading
+fn lab_heading(attribute: &str) -> Option<&'static str> {
+    match attribute.trim().to_lowercase().as_str() {
+        "w" | "wn" | "w%" => Some("LNMC_MC"),
+        "wl" | "ll" => Some("LLPL_LL"),
+        "wp" | "pl" => Some("LLPL_PL"),
+        "rho" | "ρ" | "p" | "tiheys" => Some("LDEN_BDEN"),
+        "hu" | "humus" | "loi" | "org" => Some("LLOI_LOI"),
+        _ => None,
+    }
+}
+
+fn unit_description(unit: &str) -> &'static str {
+    match unit {
+        "m" => "metres",
+        "mm" => "millimetres",
+        "kN" => "kilonewtons",
+        "kPa" => "kilopascals",
+        "MPa" => "megapascals",
+        "Nm" => "newton metres",
+        "%" => "percentage",
+        "Mg/m3" => "megagrams per cubic metre",
+        "yyyy-mm-dd" => "year month day",
+        _ => "",
+    }
+}
+
+fn type_description(data_type: &str) -> &'static str {
+    match data_type {
+        "ID" => "Unique identifier",
+        "PA" => "Text listed in ABBR Group",
+        "PT" => "Text listed in TYPE Group",
+        "PU" => "Text listed in UNIT Group",
+        "X" => "Text",
+        "DT" => "Date time in international format",
+        "0DP" => "Value; required number of decimal places, 0",
+        "1DP" => "Value; required number of decimal places, 1",
+        "2DP" => "Value; required number of decimal places, 2",
+        "2SF" => "Value; required number of significant figures, 2",
+        "3SF" => "Value; required number of significant figures, 3",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TY W1 Test_work
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+0.2 0.5 12 Sa
+0.4 -8 0 Sa
+-1 KI
+TT NO 1 N1
+XY 6672010.0 385010.0 12.40 01012020 N1
+0.5 S1 1.0 Mr
+RK 0.063 12.5
+LB w 23.4 %
+LB wL 40 %
+LB wP 22 %
+-1 MS
+";
+
+    #[test]
+    fn ags_structure() {
+        let file = InfraFile::parse_str(INFRA);
+
+        let ags = file.to_ags(None).unwrap();
+        assert!(ags.ends_with("\r\n"));
+
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(ags.as_bytes());
+        let records: Vec<Vec<String>> = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect();
+
+        // GROUP is always followed by HEADING, UNIT and TYPE of the same width
+        let mut groups: Vec<(String, Vec<String>, Vec<Vec<String>>)> = Vec::new();
+        for (index, record) in records.iter().enumerate() {
+            match record[0].as_str() {
+                "GROUP" => {
+                    let [heading, unit, data_type] = [1, 2, 3].map(|i| &records[index + i]);
+                    assert_eq!(
+                        (heading[0].as_str(), unit[0].as_str(), data_type[0].as_str()),
+                        ("HEADING", "UNIT", "TYPE")
+                    );
+                    assert_eq!(heading.len(), unit.len());
+                    assert_eq!(heading.len(), data_type.len());
+                    groups.push((record[1].clone(), records[index + 1].clone(), Vec::new()));
+                }
+                "DATA" => {
+                    let group = groups.last_mut().unwrap();
+                    assert_eq!(record.len(), group.1.len());
+                    group.2.push(record.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let names: Vec<&str> = groups.iter().map(|group| group.0.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "PROJ", "TRAN", "UNIT", "TYPE", "ABBR", "DICT", "LOCA", "WSTG", "WSTB", "SAMP",
+                "GRAG", "GRAT", "LNMC", "LLPL"
+            ]
+        );
+
+        let group = |name: &str| {
+            groups
+                .iter()
+                .find(|group| group.0 == name)
+                .map(|group| &group.2)
+                .unwrap()
+        };
+        let column = |name: &str, heading: &str| -> Vec<String> {
+            let (_, headings, rows) = groups.iter().find(|group| group.0 == name).unwrap();
+            let index = headings.iter().position(|h| h == heading).unwrap();
+            rows.iter().map(|row| row[index].clone()).collect()
+        };
+
+        // Every unit and data type in use is listed
+        let units = column("UNIT", "UNIT_UNIT");
+        let types = column("TYPE", "TYPE_TYPE");
+        for record in &records {
+            match record[0].as_str() {
+                "UNIT" => assert!(record[1..]
+                    .iter()
+                    .all(|unit| unit.is_empty() || units.contains(unit))),
+                "TYPE" => assert!(record[1..].iter().all(|t| types.contains(t))),
+                _ => {}
+            }
+        }
+
+        // Weight sounding maps to the WSTG and WSTB groups defined in DICT
+        assert_eq!(
+            group("WSTG")[0],
+            vec!["DATA", "P1", "PA", "Half turns per 0.2 m"]
+        );
+        assert_eq!(
+            *group("WSTB"),
+            vec![
+                vec!["DATA", "P1", "0.20", "0.50", "12", "", ""],
+                vec!["DATA", "P1", "0.40", "", "0", "8", ""],
+            ]
+        );
+        let dictionary = column("DICT", "DICT_GRP");
+        assert!(["WSTG", "WSTB"]
+            .iter()
+            .all(|name| dictionary.iter().any(|group| group == name)));
+
+        assert_eq!(column("LOCA", "LOCA_NATE"), vec!["385000.00", "385010.00"]);
+        assert_eq!(column("LOCA", "LOCA_GREF"), vec!["ETRS-TM35", "ETRS-TM35"]);
+        assert_eq!(column("LNMC", "LNMC_MC"), vec!["23"]);
+        assert_eq!(column("LLPL", "LLPL_PI"), vec!["18"]);
+        assert_eq!(column("GRAT", "GRAT_SIZE"), vec!["0.0630"]);
+        assert_eq!(column("PROJ", "PROJ_ID"), vec!["W1"]);
+    }
+
+    #[test]
+    fn loca_types() {
+        let file = InfraFile::parse_str(
+            "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT CP 1 C1
+XY 6672000.0 385000.0 12.50 01012020 C1
+-1 KI
+TT HE 1 H1
+XY 6672010.0 385000.0 12.50 01012020 H1
+-1 KI
+TT KO 1 K1
+XY 6672020.0 385000.0 12.50 01012020 K1
+-1 KI
+TT PA 1 P1
+XY 6672030.0 385000.0 12.50 01012020 P1
+-1 KI
+",
+        );
+        let ags = file.to_ags(None).unwrap();
+
+        // Values of one heading in a group, from the DATA rows
+        let column = |name: &str, heading: &str| -> Vec<String> {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(ags.as_bytes());
+            let (mut group, mut index) = (String::new(), None);
+            let mut values = Vec::new();
+            for record in reader.records() {
+                let record = record.unwrap();
+                match &record[0] {
+                    "GROUP" => group = record[1].to_string(),
+                    "HEADING" if group == name => {
+                        index = record.iter().position(|h| h == heading);
+                    }
+                    "DATA" if group == name => values.push(record[index.unwrap()].to_string()),
+                    _ => {}
+                }
+            }
+            values
+        };
+
+        // CP is cable percussion in AGS, the InfraModel CPT maps to CPT
+        assert_eq!(
+            column("LOCA", "LOCA_TYPE"),
+            vec!["CPT", "DP", "TP", "FI-PA"]
+        );
+
+        let abbreviations: Vec<(String, String)> = column("ABBR", "ABBR_HDNG")
+            .into_iter()
+            .zip(column("ABBR", "ABBR_CODE"))
+            .filter(|(heading, _)| heading == "LOCA_TYPE")
+            .collect();
+        let codes: Vec<&str> = abbreviations
+            .iter()
+            .map(|(_, code)| code.as_str())
+            .collect();
+        assert_eq!(codes, vec!["CPT", "DP", "FI-PA", "TP"]);
+        assert!(column("ABBR", "ABBR_DESC")
+            .contains(&"Weight sounding test (InfraModel PA)".to_string()));
+    }
+}
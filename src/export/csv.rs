@@ -0,0 +1,337 @@
+use crate::export::{
+    field, joined, observation_date, observation_fields, observation_measurer, parsed, sample_id,
+    soil_layer_summary, soil_type, text, token, write, FieldValue, DEPTH_FIELDS,
+    OBSERVATION_FIELDS,
+};
+use crate::{InvestigationCollection, ParseResult};
+
+use csv::Writer;
+
+impl InvestigationCollection {
+    // One row per observation with the identifiers of its investigation and
+    // a column for every value of the observation variants
+    pub fn to_observations_csv(&self) -> Result<String, String> {
+        let mut writer = Writer::from_writer(Vec::new());
+
+        let value_columns: Vec<&str> = OBSERVATION_FIELDS
+            .iter()
+            .filter(|name| !DEPTH_FIELDS.contains(name))
+            .copied()
+            .collect();
+
+        let mut header = vec![
+            "investigation",
+            "file",
+            "point_id",
+            "x",
+            "y",
+            "z",
+            "coordinate_system",
+            "elevation_system",
+            "method",
+            "observation",
+            "depth",
+            "depth_to",
+            "elevation",
+        ];
+        header.extend(&value_columns);
+        header.extend([
+            "date",
+            "measurer",
+            "sample_id",
+            "soil_type",
+            "unofficial_soil_type",
+            "water_observed_depth",
+            "water_observed_type",
+            "notes",
+            "free_text",
+        ]);
+        writer.write_record(&header).map_err(|e| e.to_string())?;
+
+        for (index, investigation) in self.investigations.iter().enumerate() {
+            let identifiers = [
+                index.to_string(),
+                investigation.file_info.path.clone().unwrap_or_default(),
+                token(&investigation.coordinates.point_id).unwrap_or_default(),
+                text(field(&investigation.coordinates.x)),
+                text(field(&investigation.coordinates.y)),
+                text(field(&investigation.coordinates.start_elevation)),
+                token(&investigation.spatial.coordinate_system).unwrap_or_default(),
+                token(&investigation.spatial.elevation_system).unwrap_or_default(),
+                token(&investigation.method.token).unwrap_or_default(),
+            ];
+            let start_elevation = parsed(&investigation.coordinates.start_elevation);
+
+            for (observation_index, observation) in investigation.observations.iter().enumerate() {
+                let fields = observation_fields(&observation.values);
+                let value = |name: &str| {
+                    fields
+                        .iter()
+                        .find(|(field_name, _)| *field_name == name)
+                        .and_then(|(_, value)| value.clone())
+                };
+
+                let depth = value("depth").or_else(|| value("start_depth"));
+                let elevation = match (start_elevation, depth.as_ref().and_then(FieldValue::as_f64))
+                {
+                    (Some(start), Some(depth)) => Some(start as f64 - depth),
+                    _ => observation
+                        .values
+                        .get_parsed_elevation()
+                        .map(|elevation| elevation as f64),
+                };
+
+                let mut record = identifiers.to_vec();
+                record.extend([
+                    observation_index.to_string(),
+                    text(depth),
+                    text(value("end_depth")),
+                    elevation.map(round).unwrap_or_default(),
+                ]);
+                record.extend(value_columns.iter().map(|name| text(value(name))));
+
+                let (water_depth, water_type) = match &observation.water_observed {
+                    ParseResult::Parsed(water) => (text(field(&water.depth)), token(&water.token)),
+                    _ => (String::new(), None),
+                };
+
+                record.extend([
+                    text(observation_date(&observation.values)),
+                    text(observation_measurer(&observation.values)),
+                    text(sample_id(&observation.values)),
+                    text(soil_type(&observation.values)),
+                    text(joined(&observation.unofficial_soil_type)),
+                    water_depth,
+                    water_type.unwrap_or_default(),
+                    text(joined(&observation.notes)),
+                    text(joined(&observation.free_text)),
+                ]);
+
+                writer.write_record(&record).map_err(|e| e.to_string())?;
+            }
+        }
+
+        into_string(writer)
+    }
+
+    // One row per investigation
+    pub fn to_investigations_csv(&self) -> Result<String, String> {
+        let mut writer = Writer::from_writer(Vec::new());
+
+        writer
+            .write_record([
+                "investigation",
+                "file",
+                "point_id",
+                "x",
+                "y",
+                "z",
+                "coordinate_system",
+                "elevation_system",
+                "method",
+                "date",
+                "owner",
+                "investigator",
+                "work_id",
+                "work_name",
+                "record_number",
+                "line",
+                "stake",
+                "offset",
+                "observations",
+                "total_depth",
+                "sounded_depth",
+                "termination",
+                "water_depth",
+                "soil_layers",
+                "notes",
+            ])
+            .map_err(|e| e.to_string())?;
+
+        for (index, investigation) in self.investigations.iter().enumerate() {
+            let water_depth = investigation
+                .first_water_observed()
+                .and_then(|water| field(&water.depth));
+
+            let record = [
+                index.to_string(),
+                investigation.file_info.path.clone().unwrap_or_default(),
+                token(&investigation.coordinates.point_id).unwrap_or_default(),
+                text(field(&investigation.coordinates.x)),
+                text(field(&investigation.coordinates.y)),
+                text(field(&investigation.coordinates.start_elevation)),
+                token(&investigation.spatial.coordinate_system).unwrap_or_default(),
+                token(&investigation.spatial.elevation_system).unwrap_or_default(),
+                token(&investigation.method.token).unwrap_or_default(),
+                text(field(&investigation.coordinates.date)),
+                token(&investigation.organisations.owner_name).unwrap_or_default(),
+                token(&investigation.organisations.investigator_name).unwrap_or_default(),
+                token(&investigation.work.id).unwrap_or_default(),
+                token(&investigation.work.name).unwrap_or_default(),
+                text(field(&investigation.record.number)),
+                token(&investigation.line.name).unwrap_or_default(),
+                text(field(&investigation.line.stake)),
+                text(field(&investigation.line.distance)),
+                investigation.observations.len().to_string(),
+                investigation
+                    .total_depth
+                    .map(|depth| depth.to_string())
+                    .unwrap_or_default(),
+                investigation
+                    .sounded_depth
+                    .map(|depth| depth.to_string())
+                    .unwrap_or_default(),
+                token(&investigation.termination.token).unwrap_or_default(),
+                text(water_depth),
+                soil_layer_summary(investigation),
+                text(joined(&investigation.notes)),
+            ];
+
+            writer.write_record(&record).map_err(|e| e.to_string())?;
+        }
+
+        into_string(writer)
+    }
+
+    pub fn write_observations_csv(&self, file_path: &str) -> Result<(), String> {
+        write(file_path, &self.to_observations_csv()?)
+    }
+
+    pub fn write_investigations_csv(&self, file_path: &str) -> Result<(), String> {
+        write(file_path, &self.to_investigations_csv()?)
+    }
+}
+
+// Elevations are derived from f32 values, millimetres are enough
+fn round(value: f64) -> String {
+    ((value * 1000.0).round() / 1000.0).to_string()
+}
+
+fn into_string(writer: Writer<Vec<u8>>) -> Result<String, String> {
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.123 385000.456 12.34 01012020 P1
+LN L1 125.5 -2.25
+1.0 100 10 Sa
+VS 1.4 VO
+2.0 -5 20 SaSi
+-1 KI
+TT MW 1 M1
+XY 6672010.0 385010.0 11.5 02012020 M1
+0.5 1.2 3.4 5.6 7.8 9.1 60 1 Sa
+-1 KA
+";
+
+    // Header and the rows as maps from the column name
+    fn read(csv: &str) -> (Vec<String>, Vec<std::collections::HashMap<String, String>>) {
+        let mut reader = ::csv::Reader::from_reader(csv.as_bytes());
+        let header: Vec<String> = reader
+            .headers()
+            .unwrap()
+            .iter()
+            .map(str::to_string)
+            .collect();
+        let rows = reader
+            .records()
+            .map(|record| {
+                header
+                    .iter()
+                    .cloned()
+                    .zip(record.unwrap().iter().map(str::to_string))
+                    .collect()
+            })
+            .collect();
+        (header, rows)
+    }
+
+    #[test]
+    fn observations_csv() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+        let (header, rows) = read(&collection.to_observations_csv().unwrap());
+
+        // Every column once, MW hits share the hits column
+        let mut unique = header.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), header.len());
+        assert!(header.contains(&"hits".to_string()));
+        assert!(!header.contains(&"mw_hits".to_string()));
+        assert!(!header.contains(&"start_depth".to_string()));
+        assert_eq!(rows.len(), 3);
+
+        let first = &rows[0];
+        assert_eq!(first["investigation"], "0");
+        assert_eq!(first["point_id"], "P1");
+        assert_eq!(first["x"], "6672000.123");
+        assert_eq!(first["y"], "385000.456");
+        assert_eq!(first["coordinate_system"], "TM35");
+        assert_eq!(first["elevation_system"], "N2000");
+        assert_eq!(first["method"], "PA");
+        assert_eq!(first["observation"], "0");
+        assert_eq!(first["depth"], "1");
+        assert_eq!(first["elevation"], "11.34");
+        assert_eq!(first["load"], "100");
+        assert_eq!(first["half_turns"], "10");
+        assert_eq!(first["hits"], "");
+        assert_eq!(first["soil_type"], "Sa");
+        assert_eq!(first["water_observed_depth"], "1.4");
+        assert_eq!(first["water_observed_type"], "VO");
+
+        let second = &rows[1];
+        assert_eq!(second["observation"], "1");
+        assert_eq!(second["elevation"], "10.34");
+        assert_eq!(second["load"], "");
+        assert_eq!(second["hits"], "5");
+        assert_eq!(second["water_observed_depth"], "");
+
+        let mw = &rows[2];
+        assert_eq!(mw["investigation"], "1");
+        assert_eq!(mw["method"], "MW");
+        assert_eq!(mw["depth"], "0.5");
+        assert_eq!(mw["advance_rate"], "1.2");
+        assert_eq!(mw["torque"], "9.1");
+        assert_eq!(mw["rotation_speed"], "60");
+        assert_eq!(mw["hits"], "1");
+        assert_eq!(mw["soil_type"], "Sa");
+    }
+
+    #[test]
+    fn investigations_csv() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+        let (header, rows) = read(&collection.to_investigations_csv().unwrap());
+
+        assert_eq!(header.len(), 25);
+        assert_eq!(rows.len(), 2);
+
+        let first = &rows[0];
+        assert_eq!(first["point_id"], "P1");
+        assert_eq!(first["x"], "6672000.123");
+        assert_eq!(first["z"], "12.34");
+        assert_eq!(first["date"], "2020-01-01");
+        assert_eq!(first["line"], "L1");
+        assert_eq!(first["stake"], "125.5");
+        assert_eq!(first["offset"], "-2.25");
+        assert_eq!(first["observations"], "2");
+        assert_eq!(first["total_depth"], "2");
+        assert_eq!(first["sounded_depth"], "2");
+        assert_eq!(first["termination"], "KI");
+        assert_eq!(first["water_depth"], "1.4");
+        assert_eq!(first["soil_layers"], "0.00-1.00 Sa; 1.00-2.00 SaSi");
+
+        let second = &rows[1];
+        assert_eq!(second["point_id"], "M1");
+        assert_eq!(second["method"], "MW");
+        assert_eq!(second["line"], "");
+        assert_eq!(second["termination"], "KA");
+        assert_eq!(second["water_depth"], "");
+    }
+}
@@ -0,0 +1,715 @@
+use crate::export::{parsed, position, token, write, XmlWriter};
+use crate::{
+    CoordinateSystem, InfraFile, Investigation, InvestigationCollection, MethodToken,
+    ObservationValues, ParseResult, Transformer,
+};
+
+use chrono::Local;
+
+const DIGGS_NAMESPACE: &str = "http://diggsml.org/schemas/2.6";
+const PROPERTY_CODES: &str = "https://diggsml.org/def/codes/DIGGS/0.1/properties.xml";
+
+impl InvestigationCollection {
+    pub fn to_diggs(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_diggs_with(target, &Transformer::default())
+    }
+
+    pub fn to_diggs_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        diggs(&self.investigations, target, transformer)
+    }
+
+    pub fn write_diggs(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_diggs(target)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_diggs(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_diggs_with(target, &Transformer::default())
+    }
+
+    pub fn to_diggs_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        diggs(&self.investigations, target, transformer)
+    }
+
+    pub fn write_diggs(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_diggs(target)?)
+    }
+}
+
+// EPSG axis order, the TM zones are easting first and the Finnish
+// Gauss-Krüger, KKJ and geographic systems northing (latitude) first
+fn axis_order(system: &CoordinateSystem, x: f64, y: f64) -> (f64, f64) {
+    match system {
+        CoordinateSystem::TM34 | CoordinateSystem::TM35 | CoordinateSystem::TM36 => (y, x),
+        _ => (x, y),
+    }
+}
+
+fn srs_name(system: &CoordinateSystem, vertical: Option<u32>) -> Option<String> {
+    let horizontal = system.epsg()?;
+    Some(match vertical {
+        Some(vertical) => format!(
+            "urn:ogc:def:crs,crs:EPSG::{},crs:EPSG::{}",
+            horizontal, vertical
+        ),
+        None => format!("urn:ogc:def:crs:EPSG::{}", horizontal),
+    })
+}
+
+fn is_sounding(method: &ParseResult<MethodToken>) -> bool {
+    matches!(
+        method,
+        ParseResult::Parsed(
+            MethodToken::PA
+                | MethodToken::PI
+                | MethodToken::LY
+                | MethodToken::SI
+                | MethodToken::HE
+                | MethodToken::HK
+                | MethodToken::PT
+                | MethodToken::TR
+                | MethodToken::PR
+                | MethodToken::CP
+                | MethodToken::CU
+                | MethodToken::HP
+                | MethodToken::PO
+                | MethodToken::MW
+                | MethodToken::PS
+        )
+    )
+}
+
+fn format_value(value: Option<f32>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+// Property name, DIGGS property class and unit
+type Property = (&'static str, &'static str, &'static str);
+
+struct TestResults {
+    procedure: Option<&'static str>,
+    properties: Vec<Property>,
+    rows: Vec<(f32, Vec<Option<f32>>)>,
+}
+
+fn test_results(investigation: &Investigation) -> Option<TestResults> {
+    let (procedure, properties): (Option<&'static str>, Vec<Property>) =
+        match parsed(&investigation.method.token)? {
+            MethodToken::CP | MethodToken::CU | MethodToken::PR => (
+                Some("StaticConeTest"),
+                vec![
+                    ("qc", "cone_tip_resistance", "MPa"),
+                    ("fs", "sleeve_friction", "kPa"),
+                    ("u2", "pore_pressure_u2", "kPa"),
+                    ("Qt", "total_resistance", "kN"),
+                ],
+            ),
+            MethodToken::HE | MethodToken::HK | MethodToken::LY | MethodToken::HP => (
+                Some("DynamicProbeTest"),
+                vec![("N20", "blow_count", "blows/0.2m"), ("T", "torque", "N.m")],
+            ),
+            MethodToken::PA => (
+                None,
+                vec![
+                    ("F", "load", "kN"),
+                    ("ht", "half_turn_count", "half-turns/0.2m"),
+                ],
+            ),
+            _ => return None,
+        };
+
+    let mut rows = Vec::new();
+    for observation in &investigation.observations {
+        let values = &observation.values;
+        let Some(depth) = values.get_parsed_depth() else {
+            continue;
+        };
+        let row = match values {
+            ObservationValues::CP {
+                total_resistance,
+                sleeve_friction,
+                tip_resistance,
+                ..
+            } => vec![
+                parsed(tip_resistance),
+                parsed(sleeve_friction),
+                None,
+                parsed(total_resistance),
+            ],
+            ObservationValues::CU {
+                total_resistance,
+                sleeve_friction,
+                tip_resistance,
+                pore_water_pressure,
+                ..
+            } => vec![
+                parsed(tip_resistance),
+                parsed(sleeve_friction),
+                parsed(pore_water_pressure),
+                parsed(total_resistance),
+            ],
+            ObservationValues::PR {
+                total_resistance,
+                sleeve_friction,
+                ..
+            } => vec![
+                None,
+                parsed(sleeve_friction),
+                None,
+                parsed(total_resistance),
+            ],
+            ObservationValues::HE { hits, .. } | ObservationValues::LY { hits, .. } => {
+                vec![parsed(hits).map(|hits| hits as f32), None]
+            }
+            ObservationValues::HK { hits, torque, .. }
+            | ObservationValues::HP { hits, torque, .. } => {
+                vec![parsed(hits).map(|hits| hits as f32), parsed(torque)]
+            }
+            ObservationValues::PA {
+                load, half_turns, ..
+            } => vec![parsed(load), parsed(half_turns).map(|turns| turns as f32)],
+            _ => continue,
+        };
+        rows.push((depth, row));
+    }
+
+    if rows.is_empty() {
+        return None;
+    }
+
+    Some(TestResults {
+        procedure,
+        properties,
+        rows,
+    })
+}
+
+fn diggs(
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+) -> Result<String, String> {
+    let mut xml = XmlWriter::default();
+    xml.output
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    let schema_location = format!(
+        "{} https://diggsml.org/schemas/2.6/Diggs.xsd",
+        DIGGS_NAMESPACE
+    );
+    xml.open(
+        "Diggs",
+        &[
+            ("xmlns", DIGGS_NAMESPACE),
+            ("xmlns:diggs", DIGGS_NAMESPACE),
+            ("xmlns:gml", "http://www.opengis.net/gml/3.2"),
+            ("xmlns:glr", "http://www.opengis.net/gml/3.3/lr"),
+            ("xmlns:xlink", "http://www.w3.org/1999/xlink"),
+            ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+            ("xsi:schemaLocation", &schema_location),
+            ("gml:id", "diggs"),
+        ],
+    );
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    xml.open("documentInformation", &[]);
+    xml.open("DocumentInformation", &[("gml:id", "document")]);
+    xml.text("creationDate", &[], &today);
+    xml.close();
+    xml.close();
+
+    let first = investigations.first();
+    let project_name = first
+        .and_then(|investigation| token(&investigation.work.name))
+        .or_else(|| first.and_then(|investigation| token(&investigation.work.id)))
+        .unwrap_or_else(|| "Ground investigations".to_string());
+    xml.open("project", &[]);
+    xml.open("Project", &[("gml:id", "project")]);
+    xml.text("gml:name", &[], &project_name);
+    if let Some(owner) =
+        first.and_then(|investigation| token(&investigation.organisations.owner_name))
+    {
+        xml.open("role", &[]);
+        xml.open("Role", &[]);
+        xml.text("rolePerformed", &[], "client");
+        xml.open("businessAssociate", &[]);
+        xml.open("BusinessAssociate", &[("gml:id", "client")]);
+        xml.text("gml:name", &[], &owner);
+        xml.close();
+        xml.close();
+        xml.close();
+        xml.close();
+    }
+    xml.close();
+    xml.close();
+
+    let mut samples = Vec::new();
+    let mut measurements = Vec::new();
+    let mut lithologies = Vec::new();
+
+    for (index, investigation) in investigations.iter().enumerate() {
+        let id = format!("sf{}", index + 1);
+        let lrs = format!("{}-lrs", id);
+        let element = if is_sounding(&investigation.method.token) {
+            "Sounding"
+        } else {
+            "Borehole"
+        };
+
+        let output_system = match &target {
+            Some(target) => Some(target.clone()),
+            None => parsed(&investigation.spatial.coordinate_system),
+        };
+        let elevation = parsed(&investigation.coordinates.start_elevation);
+        let vertical = match &investigation.spatial.elevation_system {
+            ParseResult::Parsed(system) => system.epsg(),
+            _ => None,
+        };
+        let point = position(investigation, target.as_ref(), transformer);
+        let mut has_centerline = false;
+
+        xml.open("samplingFeature", &[]);
+        xml.open(element, &[("gml:id", &id)]);
+        xml.text(
+            "gml:name",
+            &[],
+            &token(&investigation.coordinates.point_id).unwrap_or_else(|| id.clone()),
+        );
+        if let Some(method) = parsed(&investigation.method.token) {
+            xml.text("gml:description", &[], &method.to_string());
+        }
+        xml.text("investigationTarget", &[], "Natural Ground");
+        xml.empty("projectRef", &[("xlink:href", "#project")]);
+
+        if let (Some([x, y]), Some(system)) = (point, &output_system) {
+            let (first_axis, second_axis) = axis_order(system, x, y);
+            let decimals = if *system == CoordinateSystem::WGS84 {
+                8
+            } else {
+                3
+            };
+            let horizontal = format!("{:.*} {:.*}", decimals, first_axis, decimals, second_axis);
+            let vertical = vertical.filter(|_| elevation.is_some());
+            let srs = srs_name(system, vertical);
+            let dimension = if vertical.is_some() { "3" } else { "2" };
+            let mut attributes = vec![("srsDimension", dimension)];
+            if let Some(srs) = &srs {
+                attributes.push(("srsName", srs));
+            }
+
+            let top = match (vertical, elevation) {
+                (Some(_), Some(elevation)) => format!("{} {}", horizontal, elevation),
+                _ => horizontal.clone(),
+            };
+
+            let point_id = format!("{}-point", id);
+            xml.open("referencePoint", &[]);
+            xml.open(
+                "PointLocation",
+                &[[("gml:id", point_id.as_str())].as_slice(), &attributes].concat(),
+            );
+            xml.text("gml:pos", &[], &top);
+            xml.close();
+            xml.close();
+
+            // Vertical centre line from the ground surface to the bottom
+            if let (Some(_), Some(elevation), Some(depth)) =
+                (vertical, elevation, investigation.total_depth)
+            {
+                let line_id = format!("{}-centerline", id);
+                has_centerline = true;
+                xml.open("centerLine", &[]);
+                xml.open(
+                    "LinearExtent",
+                    &[[("gml:id", line_id.as_str())].as_slice(), &attributes].concat(),
+                );
+                xml.text(
+                    "gml:posList",
+                    &[],
+                    &format!("{} {} {}", top, horizontal, elevation - depth),
+                );
+                xml.close();
+                xml.close();
+            }
+        }
+
+        xml.open("linearReferencing", &[]);
+        xml.open("LinearSpatialReferenceSystem", &[("gml:id", &lrs)]);
+        xml.text(
+            "gml:identifier",
+            &[("codeSpace", "urn:diggs:def:lrs")],
+            &format!("{}-depth", id),
+        );
+        if has_centerline {
+            xml.empty(
+                "glr:linearElement",
+                &[("xlink:href", &format!("#{}-centerline", id))],
+            );
+        }
+        xml.open("glr:linearReferencingMethod", &[]);
+        xml.open(
+            "glr:LinearReferencingMethod",
+            &[("gml:id", &format!("{}-lrm", id))],
+        );
+        xml.text("glr:name", &[], "depth");
+        xml.text("glr:type", &[], "absolute");
+        xml.text("glr:units", &[], "m");
+        xml.close();
+        xml.close();
+        xml.close();
+        xml.close();
+
+        if let Some(depth) = investigation.total_depth {
+            xml.text("totalMeasuredDepth", &[("uom", "m")], &depth.to_string());
+        }
+        if let Some(date) = parsed(&investigation.coordinates.date) {
+            xml.open("whenConstructed", &[]);
+            xml.open("TimeInterval", &[("gml:id", &format!("{}-time", id))]);
+            xml.text("start", &[], &date.format("%Y-%m-%d").to_string());
+            xml.close();
+            xml.close();
+        }
+        xml.close();
+        xml.close();
+
+        if let Some(results) = test_results(investigation) {
+            measurements.push((id.clone(), lrs.clone(), investigation, results));
+        }
+        if !investigation.soil_layers.is_empty() {
+            lithologies.push((id.clone(), lrs.clone(), investigation));
+        }
+        for (observation_index, observation) in investigation.observations.iter().enumerate() {
+            if let ObservationValues::NO { .. } | ObservationValues::NE { .. } = observation.values
+            {
+                samples.push((id.clone(), lrs.clone(), observation_index, observation));
+            }
+        }
+    }
+
+    for (id, lrs, index, observation) in &samples {
+        let (start_depth, end_depth, sample_id, soil_type, method) = match &observation.values {
+            ObservationValues::NO {
+                start_depth,
+                sample_id,
+                end_depth,
+                soil_type,
+                ..
+            } => (
+                start_depth,
+                end_depth,
+                sample_id,
+                soil_type,
+                "Disturbed sample",
+            ),
+            ObservationValues::NE {
+                start_depth,
+                sample_id,
+                end_depth,
+                soil_type,
+                ..
+            } => (
+                start_depth,
+                end_depth,
+                sample_id,
+                soil_type,
+                "Undisturbed sample",
+            ),
+            _ => continue,
+        };
+
+        let sample = format!("{}-sample{}", id, index + 1);
+        xml.open("sample", &[]);
+        xml.open("Sample", &[("gml:id", &sample)]);
+        xml.text(
+            "gml:name",
+            &[],
+            &token(sample_id).unwrap_or_else(|| sample.clone()),
+        );
+        xml.text("investigationTarget", &[], "Natural Ground");
+        xml.empty("projectRef", &[("xlink:href", "#project")]);
+        xml.empty("samplingFeatureRef", &[("xlink:href", &format!("#{}", id))]);
+        if let Some(start) = parsed(start_depth) {
+            let end = parsed(end_depth).unwrap_or(start);
+            xml.open("samplingLocation", &[]);
+            xml.open(
+                "LinearExtent",
+                &[
+                    ("gml:id", &format!("{}-location", sample)),
+                    ("srsName", &format!("#{}", lrs)),
+                    ("srsDimension", "1"),
+                ],
+            );
+            xml.text("gml:posList", &[], &format!("{} {}", start, end));
+            xml.close();
+            xml.close();
+        }
+        xml.text("samplingMethod", &[], method);
+        if let Some(soil_type) = token(soil_type) {
+            xml.text("sampleDescription", &[], &soil_type);
+        }
+        xml.close();
+        xml.close();
+    }
+
+    for (id, lrs, investigation, results) in &measurements {
+        let test = format!("{}-test", id);
+        xml.open("measurement", &[]);
+        xml.open("Test", &[("gml:id", &test)]);
+        xml.text(
+            "gml:name",
+            &[],
+            &parsed(&investigation.method.token)
+                .map(|method| method.to_string())
+                .unwrap_or_default(),
+        );
+        xml.text("investigationTarget", &[], "Natural Ground");
+        xml.empty("projectRef", &[("xlink:href", "#project")]);
+        xml.empty("samplingFeatureRef", &[("xlink:href", &format!("#{}", id))]);
+
+        xml.open("outcome", &[]);
+        xml.open("TestResult", &[("gml:id", &format!("{}-result", test))]);
+        xml.open("location", &[]);
+        xml.open(
+            "MultiPointLocation",
+            &[("gml:id", &format!("{}-locations", test))],
+        );
+        xml.open("gml:pointMembers", &[]);
+        for (row, (depth, _)) in results.rows.iter().enumerate() {
+            xml.open(
+                "gml:Point",
+                &[
+                    ("gml:id", &format!("{}-point{}", test, row + 1)),
+                    ("srsName", &format!("#{}", lrs)),
+                    ("srsDimension", "1"),
+                ],
+            );
+            xml.text("gml:pos", &[], &depth.to_string());
+            xml.close();
+        }
+        xml.close();
+        xml.close();
+        xml.close();
+
+        xml.open("results", &[]);
+        xml.open("ResultSet", &[]);
+        xml.open("parameters", &[]);
+        xml.open(
+            "PropertyParameters",
+            &[("gml:id", &format!("{}-parameters", test))],
+        );
+        xml.open("properties", &[]);
+        for (property_index, (name, class, unit)) in results.properties.iter().enumerate() {
+            xml.open("Property", &[("index", &(property_index + 1).to_string())]);
+            xml.text("propertyName", &[], name);
+            xml.text("typeData", &[], "double");
+            xml.text("propertyClass", &[("codeSpace", PROPERTY_CODES)], class);
+            xml.text("uom", &[], unit);
+            xml.close();
+        }
+        xml.close();
+        xml.close();
+        xml.close();
+
+        let values: Vec<String> = results
+            .rows
+            .iter()
+            .map(|(_, values)| {
+                values
+                    .iter()
+                    .map(|value| format_value(*value))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        xml.text(
+            "dataValues",
+            &[("cs", ","), ("ts", " "), ("decimal", ".")],
+            &values.join(" "),
+        );
+        xml.close();
+        xml.close();
+        xml.close();
+        xml.close();
+
+        if let Some(procedure) = results.procedure {
+            xml.open("procedure", &[]);
+            xml.open(procedure, &[("gml:id", &format!("{}-procedure", test))]);
+            if let Some(standard) = token(&investigation.method.standard) {
+                xml.text("testProcedureMethod", &[], &standard);
+            }
+            xml.close();
+            xml.close();
+        }
+        xml.close();
+        xml.close();
+    }
+
+    for (id, lrs, investigation) in &lithologies {
+        let system = format!("{}-lithology", id);
+        xml.open("observation", &[]);
+        xml.open("LithologySystem", &[("gml:id", &system)]);
+        xml.text("gml:name", &[], "Soil layers");
+        xml.empty("samplingFeatureRef", &[("xlink:href", &format!("#{}", id))]);
+        for (layer_index, layer) in investigation.soil_layers.iter().enumerate() {
+            let layer_id = format!("{}{}", system, layer_index + 1);
+            xml.open("lithologyObservation", &[]);
+            xml.open("LithologyObservation", &[("gml:id", &layer_id)]);
+            xml.open("location", &[]);
+            xml.open(
+                "LinearExtent",
+                &[
+                    ("gml:id", &format!("{}-location", layer_id)),
+                    ("srsName", &format!("#{}", lrs)),
+                    ("srsDimension", "1"),
+                ],
+            );
+            xml.text(
+                "gml:posList",
+                &[],
+                &format!("{} {}", layer.top_depth, layer.bottom_depth),
+            );
+            xml.close();
+            xml.close();
+            xml.text("lithDescription", &[], &layer.soil_type);
+            xml.text("gml:description", &[], &layer.source.to_string());
+            xml.close();
+            xml.close();
+        }
+        xml.close();
+        xml.close();
+    }
+
+    xml.close();
+    Ok(xml.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    use std::collections::HashSet;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT CP 1 C1
+XY 6672000.0 385000.0 12.50 01012020 C1
+0.2 5.0 20 1.2 Sa
+0.4 6.0 25 1.5 Sa
+-1 MS
+TT NO 1 N1
+XY 6672010.0 385010.0 12.40 01012020 N1
+0.5 S1 1.0 Mr
+-1 MS
+";
+
+    // Structure checks that need no schema: well-formed, unique gml:ids and
+    // local references that resolve
+    #[test]
+    fn diggs_structure() {
+        let file = InfraFile::parse_str(INFRA);
+
+        let xml = file.to_diggs(None).unwrap();
+
+        let mut reader = Reader::from_str(&xml);
+        let mut ids = HashSet::new();
+        let mut references = Vec::new();
+        let mut elements = HashSet::new();
+
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) | Event::Empty(element) => {
+                    elements.insert(String::from_utf8_lossy(element.name().as_ref()).to_string());
+                    for attribute in element.attributes() {
+                        let attribute = attribute.unwrap();
+                        let value = attribute.unescape_value().unwrap().to_string();
+                        match attribute.key.as_ref() {
+                            b"gml:id" => assert!(ids.insert(value), "duplicate gml:id"),
+                            b"xlink:href" | b"srsName" if value.starts_with('#') => {
+                                references.push(value[1..].to_string())
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        for reference in references {
+            assert!(ids.contains(&reference), "unresolved #{}", reference);
+        }
+        for element in [
+            "Diggs",
+            "Sounding",
+            "Borehole",
+            "Test",
+            "Sample",
+            "LithologySystem",
+        ] {
+            assert!(elements.contains(element), "missing {}", element);
+        }
+        assert!(xml.contains("<gml:pos>385000.000 6672000.000 12.5</gml:pos>"));
+        assert!(xml.contains(">1.2,20,,5 1.5,25,,6</dataValues>"));
+    }
+
+    // Checked with xmllint, against the DIGGS 2.6 schemas when they are placed
+    // under tests/diggs/2.6 with their GML imports (from
+    // https://diggsml.org/schemas/2.6/), otherwise for well-formedness only
+    #[test]
+    fn diggs_schema() {
+        if std::process::Command::new("xmllint")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("xmllint is not installed, skipping the DIGGS schema check");
+            return;
+        }
+
+        let file = InfraFile::parse_str(INFRA);
+        let output = temp_path("diggs_schema.xml");
+        file.write_diggs(output.to_str().unwrap(), None).unwrap();
+
+        let schema =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/diggs/2.6/Diggs.xsd");
+        let mut command = std::process::Command::new("xmllint");
+        command.arg("--noout");
+        if schema.exists() {
+            command.arg("--schema").arg(&schema);
+        } else {
+            eprintln!(
+                "{} is missing, checking well-formedness only",
+                schema.display()
+            );
+        }
+        let result = command.arg(&output).output().unwrap();
+        std::fs::remove_file(&output).ok();
+
+        assert!(
+            result.status.success(),
+            "{}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+    }
+}
@@ -0,0 +1,771 @@
+use crate::export::{common_coordinate_system, parsed, position, token, write};
+use crate::{
+    CoordinateSystem, InfraFile, Investigation, InvestigationCollection, MethodToken,
+    ProfileInvestigation, Transformer,
+};
+
+use crate::infra_file::write::InfraToken;
+
+// Symbol radius and text height in drawing units (metres)
+const SYMBOL_SIZE: f64 = 1.0;
+const TEXT_HEIGHT: f64 = 1.0;
+// Profile view below the plan, resistance graphs scaled to this width
+const PROFILE_GAP: f64 = 50.0;
+const RESISTANCE_WIDTH: f64 = 5.0;
+
+const PROFILE_LAYERS: [(&str, i32); 4] = [
+    ("IM_PROFILE_STICK", 7),
+    ("IM_PROFILE_SOIL", 3),
+    ("IM_PROFILE_RESISTANCE", 1),
+    ("IM_PROFILE_TEXT", 7),
+];
+
+const ATTRIBUTES: [(&str, &str, f64); 3] = [
+    ("POINT_ID", "Point id", 0.5),
+    ("ELEVATION", "Start elevation", -0.7),
+    ("TERMINATION", "Termination", -1.9),
+];
+
+impl InvestigationCollection {
+    // Plan drawing with a block symbol per investigation
+    pub fn to_dxf(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_dxf_with(target, &Transformer::default(), None)
+    }
+
+    // With `profile` the soundings of a cross section or line profile are
+    // also drawn as sticks below the plan
+    pub fn to_dxf_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+        profile: Option<&[ProfileInvestigation<'_>]>,
+    ) -> Result<String, String> {
+        dxf(&self.investigations, target, transformer, profile)
+    }
+
+    pub fn write_dxf(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_dxf(target)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_dxf(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        dxf(&self.investigations, target, &Transformer::default(), None)
+    }
+
+    pub fn write_dxf(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_dxf(target)?)
+    }
+}
+
+enum Shape {
+    Circle(f64),
+    Line([f64; 4]),
+    Polygon(Vec<[f64; 2]>),
+}
+
+// Outline and marking of the method symbols, in units of SYMBOL_SIZE
+fn symbol(method: Option<MethodToken>) -> Vec<Shape> {
+    use MethodToken::*;
+
+    let crossed = || {
+        vec![
+            Shape::Circle(1.0),
+            Shape::Line([-0.7, -0.7, 0.7, 0.7]),
+            Shape::Line([-0.7, 0.7, 0.7, -0.7]),
+        ]
+    };
+    let square = |size: f64| {
+        Shape::Polygon(vec![
+            [-size, -size],
+            [size, -size],
+            [size, size],
+            [-size, size],
+        ])
+    };
+
+    match method {
+        Some(PA) => vec![Shape::Circle(1.0), Shape::Line([0.0, -1.0, 0.0, 1.0])],
+        Some(PI | PT | TR) => vec![Shape::Circle(1.0), Shape::Line([-1.0, 0.0, 1.0, 0.0])],
+        Some(LY | HE | HK | HP) => vec![
+            Shape::Circle(1.0),
+            Shape::Polygon(vec![[-0.7, -0.5], [0.7, -0.5], [0.0, 0.7]]),
+        ],
+        Some(PR | CP | CU) => vec![Shape::Circle(1.0), square(0.5)],
+        Some(SI) => vec![
+            Shape::Circle(1.0),
+            Shape::Line([-1.0, 0.0, 1.0, 0.0]),
+            Shape::Line([0.0, -1.0, 0.0, 1.0]),
+        ],
+        Some(PO | MW) => crossed(),
+        Some(NO | NE | KE | KR) => vec![square(1.0), square(0.4)],
+        Some(KO) => vec![square(1.0)],
+        Some(VP | VO | VK | VPK | HV | HU) => {
+            vec![Shape::Polygon(vec![[-1.0, 0.8], [1.0, 0.8], [0.0, -0.9]])]
+        }
+        Some(PS | PM) => vec![Shape::Polygon(vec![
+            [0.0, -1.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [-1.0, 0.0],
+        ])],
+        Some(None) | Option::None => crossed(),
+    }
+}
+
+fn block_name(method: Option<MethodToken>) -> String {
+    match method {
+        Some(method) if method != MethodToken::None => {
+            format!("IM_{}", method.infra_token())
+        }
+        _ => "IM_UNKNOWN".to_string(),
+    }
+}
+
+// DXF text is written in the ANSI code page, other characters as \U+ escapes
+fn text(value: &str) -> String {
+    value
+        .chars()
+        .map(|character| match character {
+            '\r' | '\n' => " ".to_string(),
+            ' '..='~' => character.to_string(),
+            _ => format!("\\U+{:04X}", character as u32),
+        })
+        .collect()
+}
+
+fn number(value: f64) -> String {
+    let value = (value * 1e6).round() / 1e6 + 0.0;
+    let text = format!("{}", value);
+    if text.contains('.') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+// Group code and value pairs of one section, with handles
+#[derive(Default)]
+struct DxfWriter {
+    output: String,
+    handle: u32,
+}
+
+impl DxfWriter {
+    fn pair(&mut self, code: i32, value: &str) {
+        self.output.push_str(&format!("{:>3}\n{}\n", code, value));
+    }
+
+    fn pairs(&mut self, pairs: &[(i32, String)]) {
+        for (code, value) in pairs {
+            self.pair(*code, value);
+        }
+    }
+
+    fn handle(&mut self) -> String {
+        self.handle += 1;
+        format!("{:X}", self.handle)
+    }
+
+    fn point(&mut self, code: i32, x: f64, y: f64) {
+        self.pair(code, &number(x));
+        self.pair(code + 10, &number(y));
+        self.pair(code + 20, "0.0");
+    }
+
+    fn entity(&mut self, kind: &str, owner: &str, layer: &str) -> String {
+        let handle = self.handle();
+        self.pair(0, kind);
+        self.pair(5, &handle);
+        self.pair(330, owner);
+        self.pair(100, "AcDbEntity");
+        self.pair(8, layer);
+        handle
+    }
+
+    fn line(&mut self, owner: &str, layer: &str, [x1, y1, x2, y2]: [f64; 4]) {
+        self.entity("LINE", owner, layer);
+        self.pair(100, "AcDbLine");
+        self.point(10, x1, y1);
+        self.point(11, x2, y2);
+    }
+
+    fn circle(&mut self, owner: &str, layer: &str, x: f64, y: f64, radius: f64) {
+        self.entity("CIRCLE", owner, layer);
+        self.pair(100, "AcDbCircle");
+        self.point(10, x, y);
+        self.pair(40, &number(radius));
+    }
+
+    fn polyline(&mut self, owner: &str, layer: &str, points: &[[f64; 2]], closed: bool) {
+        self.entity("LWPOLYLINE", owner, layer);
+        self.pair(100, "AcDbPolyline");
+        self.pair(90, &points.len().to_string());
+        self.pair(70, if closed { "1" } else { "0" });
+        for [x, y] in points {
+            self.pair(10, &number(*x));
+            self.pair(20, &number(*y));
+        }
+    }
+
+    // Left aligned text, or right aligned with `right`
+    fn text(&mut self, owner: &str, layer: &str, x: f64, y: f64, value: &str, right: bool) {
+        self.entity("TEXT", owner, layer);
+        self.pair(100, "AcDbText");
+        self.point(10, x, y);
+        self.pair(40, &number(TEXT_HEIGHT));
+        self.pair(1, &text(value));
+        if right {
+            self.pair(72, "2");
+            self.point(11, x, y);
+        }
+        self.pair(100, "AcDbText");
+    }
+
+    fn table(&mut self, name: &str, entries: usize) -> String {
+        let handle = self.handle();
+        self.pairs(&[
+            (0, "TABLE".to_string()),
+            (2, name.to_string()),
+            (5, handle.clone()),
+            (330, "0".to_string()),
+            (100, "AcDbSymbolTable".to_string()),
+            (70, entries.to_string()),
+        ]);
+        handle
+    }
+
+    fn record(&mut self, kind: &str, table: &str, subclass: &str, name: &str) -> String {
+        let handle = self.handle();
+        self.pairs(&[
+            (0, kind.to_string()),
+            (5, handle.clone()),
+            (330, table.to_string()),
+            (100, "AcDbSymbolTableRecord".to_string()),
+            (100, subclass.to_string()),
+            (2, name.to_string()),
+            (70, "0".to_string()),
+        ]);
+        handle
+    }
+}
+
+struct Symbol {
+    block: String,
+    layer: String,
+    position: [f64; 2],
+    attributes: [String; 3],
+}
+
+fn dxf(
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+    profile: Option<&[ProfileInvestigation<'_>]>,
+) -> Result<String, String> {
+    if target.is_none() {
+        common_coordinate_system(investigations)?;
+    }
+
+    let symbols: Vec<Symbol> = investigations
+        .iter()
+        .filter_map(|investigation| {
+            let [x, y] = position(investigation, target.as_ref(), transformer)?;
+            let method = parsed(&investigation.method.token);
+            Some(Symbol {
+                block: block_name(method),
+                layer: block_name(method),
+                // Easting is the drawing x axis
+                position: [y, x],
+                attributes: [
+                    token(&investigation.coordinates.point_id).unwrap_or_default(),
+                    parsed(&investigation.coordinates.start_elevation)
+                        .map(|elevation| format!("{:.2}", elevation))
+                        .unwrap_or_default(),
+                    token(&investigation.termination.token).unwrap_or_default(),
+                ],
+            })
+        })
+        .collect();
+
+    let mut methods: Vec<Option<MethodToken>> = investigations
+        .iter()
+        .map(|investigation| parsed(&investigation.method.token))
+        .collect();
+    methods.sort_by_key(|method| block_name(*method));
+    methods.dedup_by_key(|method| block_name(*method));
+
+    let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+    for symbol in &symbols {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(symbol.position[axis] - SYMBOL_SIZE);
+            max[axis] = max[axis].max(symbol.position[axis] + SYMBOL_SIZE);
+        }
+    }
+    if symbols.is_empty() {
+        (min, max) = ([0.0; 2], [0.0; 2]);
+    }
+
+    let mut layers: Vec<(String, i32)> = vec![("0".to_string(), 7)];
+    layers.extend(methods.iter().map(|method| (block_name(*method), 7)));
+    if profile.is_some() {
+        layers.extend(
+            PROFILE_LAYERS
+                .iter()
+                .map(|(name, color)| (name.to_string(), *color)),
+        );
+    }
+
+    let mut dxf = DxfWriter::default();
+    let mut body = DxfWriter {
+        handle: 0x100,
+        ..Default::default()
+    };
+
+    // Block records first, the block and entity owners refer to them
+    let mut tables = DxfWriter {
+        handle: 0x10,
+        ..Default::default()
+    };
+    tables.pairs(&[(0, "SECTION".to_string()), (2, "TABLES".to_string())]);
+
+    let vport = tables.table("VPORT", 1);
+    tables.record("VPORT", &vport, "AcDbViewportTableRecord", "*Active");
+    let height = (max[1] - min[1]).max(max[0] - min[0]).max(10.0) * 1.1;
+    tables.point(12, (min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0);
+    tables.pairs(&[(40, number(height)), (41, "1.5".to_string())]);
+    tables.pair(0, "ENDTAB");
+
+    let ltype = tables.table("LTYPE", 3);
+    for name in ["ByBlock", "ByLayer", "Continuous"] {
+        tables.record("LTYPE", &ltype, "AcDbLinetypeTableRecord", name);
+        tables.pairs(&[
+            (
+                3,
+                if name == "Continuous" {
+                    "Solid line"
+                } else {
+                    ""
+                }
+                .to_string(),
+            ),
+            (72, "65".to_string()),
+            (73, "0".to_string()),
+            (40, "0.0".to_string()),
+        ]);
+    }
+    tables.pair(0, "ENDTAB");
+
+    let layer_table = tables.table("LAYER", layers.len());
+    for (name, color) in &layers {
+        tables.record("LAYER", &layer_table, "AcDbLayerTableRecord", name);
+        tables.pairs(&[(62, color.to_string()), (6, "Continuous".to_string())]);
+    }
+    tables.pair(0, "ENDTAB");
+
+    let style = tables.table("STYLE", 1);
+    tables.record("STYLE", &style, "AcDbTextStyleTableRecord", "Standard");
+    tables.pairs(&[
+        (40, "0.0".to_string()),
+        (41, "1.0".to_string()),
+        (50, "0.0".to_string()),
+        (71, "0".to_string()),
+        (42, number(TEXT_HEIGHT)),
+        (3, "txt".to_string()),
+        (4, String::new()),
+    ]);
+    tables.pair(0, "ENDTAB");
+
+    tables.table("VIEW", 0);
+    tables.pair(0, "ENDTAB");
+    tables.table("UCS", 0);
+    tables.pair(0, "ENDTAB");
+
+    let appid = tables.table("APPID", 1);
+    tables.record("APPID", &appid, "AcDbRegAppTableRecord", "ACAD");
+    tables.pair(0, "ENDTAB");
+
+    let dimstyle = tables.table("DIMSTYLE", 1);
+    tables.pair(100, "AcDbDimStyleTable");
+    let dimstyle_handle = tables.handle();
+    tables.pairs(&[
+        (0, "DIMSTYLE".to_string()),
+        (105, dimstyle_handle),
+        (330, dimstyle),
+        (100, "AcDbSymbolTableRecord".to_string()),
+        (100, "AcDbDimStyleTableRecord".to_string()),
+        (2, "Standard".to_string()),
+        (70, "0".to_string()),
+    ]);
+    tables.pair(0, "ENDTAB");
+
+    let block_names: Vec<String> = ["*Model_Space".to_string(), "*Paper_Space".to_string()]
+        .into_iter()
+        .chain(methods.iter().map(|method| block_name(*method)))
+        .collect();
+    let block_table = tables.table("BLOCK_RECORD", block_names.len());
+    let block_records: Vec<String> = block_names
+        .iter()
+        .map(|name| tables.record("BLOCK_RECORD", &block_table, "AcDbBlockTableRecord", name))
+        .collect();
+    tables.pair(0, "ENDTAB");
+    tables.pair(0, "ENDSEC");
+
+    // Blocks
+    body.pairs(&[(0, "SECTION".to_string()), (2, "BLOCKS".to_string())]);
+    for (index, name) in block_names.iter().enumerate() {
+        let owner = block_records[index].clone();
+        body.entity("BLOCK", &owner, "0");
+        body.pair(100, "AcDbBlockBegin");
+        body.pair(2, name);
+        body.pair(70, if index >= 2 { "2" } else { "0" });
+        body.point(10, 0.0, 0.0);
+        body.pair(3, name);
+        body.pair(1, "");
+
+        if index >= 2 {
+            for shape in symbol(methods[index - 2]) {
+                match shape {
+                    Shape::Circle(radius) => {
+                        body.circle(&owner, "0", 0.0, 0.0, radius * SYMBOL_SIZE)
+                    }
+                    Shape::Line(line) => body.line(&owner, "0", line.map(|v| v * SYMBOL_SIZE)),
+                    Shape::Polygon(points) => {
+                        let points: Vec<[f64; 2]> = points
+                            .iter()
+                            .map(|[x, y]| [x * SYMBOL_SIZE, y * SYMBOL_SIZE])
+                            .collect();
+                        body.polyline(&owner, "0", &points, true)
+                    }
+                }
+            }
+            for (tag, prompt, offset) in ATTRIBUTES {
+                body.entity("ATTDEF", &owner, "0");
+                body.pair(100, "AcDbText");
+                body.point(10, 1.5 * SYMBOL_SIZE, offset * TEXT_HEIGHT);
+                body.pair(40, &number(TEXT_HEIGHT));
+                body.pair(1, "");
+                body.pair(100, "AcDbAttributeDefinition");
+                body.pair(3, prompt);
+                body.pair(2, tag);
+                body.pair(70, "0");
+            }
+        }
+
+        body.entity("ENDBLK", &owner, "0");
+        body.pair(100, "AcDbBlockEnd");
+    }
+    body.pair(0, "ENDSEC");
+
+    // Entities
+    let model = block_records[0].clone();
+    body.pairs(&[(0, "SECTION".to_string()), (2, "ENTITIES".to_string())]);
+    for symbol in &symbols {
+        let [x, y] = symbol.position;
+        let insert = body.entity("INSERT", &model, &symbol.layer);
+        body.pair(100, "AcDbBlockReference");
+        body.pair(66, "1");
+        body.pair(2, &symbol.block);
+        body.point(10, x, y);
+        for ((tag, _, offset), value) in ATTRIBUTES.iter().zip(&symbol.attributes) {
+            body.entity("ATTRIB", &insert, &symbol.layer);
+            body.pair(100, "AcDbText");
+            body.point(10, x + 1.5 * SYMBOL_SIZE, y + offset * TEXT_HEIGHT);
+            body.pair(40, &number(TEXT_HEIGHT));
+            body.pair(1, &text(value));
+            body.pair(100, "AcDbAttribute");
+            body.pair(2, tag);
+            body.pair(70, "0");
+        }
+        body.entity("SEQEND", &insert, &symbol.layer);
+    }
+
+    if let Some(profile) = profile {
+        profile_view(&mut body, &model, profile, min);
+    }
+    body.pair(0, "ENDSEC");
+
+    body.pairs(&[(0, "SECTION".to_string()), (2, "OBJECTS".to_string())]);
+    let dictionary = body.handle();
+    let groups = body.handle();
+    body.pairs(&[
+        (0, "DICTIONARY".to_string()),
+        (5, dictionary.clone()),
+        (330, "0".to_string()),
+        (100, "AcDbDictionary".to_string()),
+        (281, "1".to_string()),
+        (3, "ACAD_GROUP".to_string()),
+        (350, groups.clone()),
+        (0, "DICTIONARY".to_string()),
+        (5, groups),
+        (330, dictionary),
+        (100, "AcDbDictionary".to_string()),
+        (281, "1".to_string()),
+    ]);
+    body.pair(0, "ENDSEC");
+    body.pair(0, "EOF");
+
+    dxf.pairs(&[(0, "SECTION".to_string()), (2, "HEADER".to_string())]);
+    dxf.pairs(&[
+        (9, "$ACADVER".to_string()),
+        (1, "AC1015".to_string()),
+        (9, "$DWGCODEPAGE".to_string()),
+        (3, "ANSI_1252".to_string()),
+        (9, "$INSUNITS".to_string()),
+        (70, "6".to_string()),
+        (9, "$HANDSEED".to_string()),
+        (5, format!("{:X}", body.handle + 1)),
+    ]);
+    dxf.pair(9, "$EXTMIN");
+    dxf.point(10, min[0], min[1]);
+    dxf.pair(9, "$EXTMAX");
+    dxf.point(10, max[0], max[1]);
+    dxf.pair(0, "ENDSEC");
+    dxf.pairs(&[
+        (0, "SECTION".to_string()),
+        (2, "CLASSES".to_string()),
+        (0, "ENDSEC".to_string()),
+    ]);
+
+    if tables.handle >= 0x100 {
+        return Err("Too many table entries for the DXF handle range".to_string());
+    }
+
+    Ok(format!("{}{}{}", dxf.output, tables.output, body.output))
+}
+
+// Soundings as vertical sticks at their chainage, elevations at true scale
+fn profile_view(
+    dxf: &mut DxfWriter,
+    model: &str,
+    profile: &[ProfileInvestigation<'_>],
+    plan_min: [f64; 2],
+) {
+    let [stick_layer, soil_layer, resistance_layer, text_layer] =
+        PROFILE_LAYERS.map(|layer| layer.0);
+
+    let first_chainage = profile
+        .iter()
+        .map(|investigation| investigation.chainage)
+        .fold(f64::MAX, f64::min);
+    let top = profile
+        .iter()
+        .filter_map(|investigation| investigation.start_elevation)
+        .fold(f64::MIN, f64::max);
+    if first_chainage == f64::MAX || top == f64::MIN {
+        return;
+    }
+
+    let origin_y = plan_min[1] - PROFILE_GAP - top;
+
+    for investigation in profile {
+        let Some(elevation) = investigation.start_elevation else {
+            continue;
+        };
+        let x = plan_min[0] + investigation.chainage - first_chainage;
+        let y = |elevation: f64| origin_y + elevation;
+        let depth = investigation
+            .investigation
+            .total_depth
+            .map(|depth| depth as f64)
+            .unwrap_or(0.0);
+
+        dxf.line(
+            model,
+            stick_layer,
+            [x, y(elevation), x, y(elevation - depth)],
+        );
+        if let Some(point_id) = token(&investigation.investigation.coordinates.point_id) {
+            dxf.text(
+                model,
+                text_layer,
+                x,
+                y(elevation) + TEXT_HEIGHT,
+                &point_id,
+                false,
+            );
+        }
+        if let Some(termination) = token(&investigation.investigation.termination.token) {
+            dxf.text(
+                model,
+                text_layer,
+                x,
+                y(elevation - depth) - 2.0 * TEXT_HEIGHT,
+                &termination,
+                false,
+            );
+        }
+
+        for layer in investigation.soil_layers {
+            let top = elevation - layer.top_depth as f64;
+            let bottom = elevation - layer.bottom_depth as f64;
+            dxf.line(model, soil_layer, [x - 0.5, y(bottom), x, y(bottom)]);
+            dxf.text(
+                model,
+                soil_layer,
+                x - 0.7,
+                y((top + bottom) / 2.0) - TEXT_HEIGHT / 2.0,
+                &layer.soil_type,
+                true,
+            );
+        }
+
+        // Resistance scaled so that the largest value of the sounding spans
+        // RESISTANCE_WIDTH, the largest value is labelled at the bottom
+        let resistances: Vec<(f64, f64)> = investigation
+            .observations
+            .iter()
+            .filter_map(|observation| {
+                let resistance = observation.observation.values.get_parsed_resistance()?;
+                Some((observation.depth?, resistance as f64))
+            })
+            .collect();
+        let largest = resistances
+            .iter()
+            .map(|(_, resistance)| *resistance)
+            .fold(0.0, f64::max);
+        if resistances.len() > 1 && largest > 0.0 {
+            let points: Vec<[f64; 2]> = resistances
+                .iter()
+                .map(|(depth, resistance)| {
+                    [
+                        x + resistance / largest * RESISTANCE_WIDTH,
+                        y(elevation - depth),
+                    ]
+                })
+                .collect();
+            dxf.polyline(model, resistance_layer, &points, false);
+            dxf.text(
+                model,
+                resistance_layer,
+                x + RESISTANCE_WIDTH,
+                y(elevation - depth) - 2.0 * TEXT_HEIGHT,
+                &format!("max {}", largest),
+                true,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::collections::BTreeMap;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+LN L1 0.0 0.0
+1.0 100 10 Sa
+2.0 55 20 SaSi
+-1 KI
+TT PA 1 P2
+XY 6672020.0 385000.0 12.40 01012020 P2
+LN L1 20.0 0.0
+1.0 100 10 Mr
+-1 KI
+TT SI 1 S1
+XY 6672010.0 385010.0 12.40 01012020 S1
+1.0 20 5 4 3
+-1 MS
+";
+
+    // Section name -> (entity type, layer) of every entity in it
+    fn entities(dxf: &str) -> BTreeMap<String, Vec<(String, String)>> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        let pairs: Vec<(i32, &str)> = lines
+            .chunks(2)
+            .map(|pair| (pair[0].trim().parse().unwrap(), pair[1]))
+            .collect();
+
+        let mut sections: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        let mut section = String::new();
+        for (index, (code, value)) in pairs.iter().enumerate() {
+            match (code, *value) {
+                (2, name) if index > 0 && pairs[index - 1] == (0, "SECTION") => {
+                    section = name.to_string()
+                }
+                (0, "SECTION" | "ENDSEC" | "EOF") => {}
+                (0, kind) => {
+                    let layer = pairs[index + 1..]
+                        .iter()
+                        .take_while(|(code, _)| *code != 0)
+                        .find(|(code, _)| *code == 8)
+                        .map(|(_, layer)| layer.to_string())
+                        .unwrap_or_default();
+                    sections
+                        .entry(section.clone())
+                        .or_default()
+                        .push((kind.to_string(), layer));
+                }
+                _ => {}
+            }
+        }
+        sections
+    }
+
+    fn count(entities: &[(String, String)], kind: &str, layer: Option<&str>) -> usize {
+        entities
+            .iter()
+            .filter(|entity| entity.0 == kind && layer.is_none_or(|layer| entity.1 == layer))
+            .count()
+    }
+
+    #[test]
+    fn dxf_entities() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+
+        let dxf = collection.to_dxf(None).unwrap();
+        assert!(dxf.ends_with("  0\nEOF\n"));
+        let sections = entities(&dxf);
+
+        let layers: Vec<String> = dxf
+            .split("AcDbLayerTableRecord")
+            .skip(1)
+            .map(|record| record.lines().nth(2).unwrap().to_string())
+            .collect();
+        assert_eq!(layers, vec!["0", "IM_PA", "IM_SI"]);
+
+        // Block per method besides the model and paper space
+        let blocks = &sections["BLOCKS"];
+        assert_eq!(count(blocks, "BLOCK", None), 4);
+        assert_eq!(count(blocks, "ATTDEF", None), 6);
+
+        let model = &sections["ENTITIES"];
+        assert_eq!(count(model, "INSERT", Some("IM_PA")), 2);
+        assert_eq!(count(model, "INSERT", Some("IM_SI")), 1);
+        assert_eq!(count(model, "ATTRIB", None), 9);
+        assert_eq!(count(model, "SEQEND", None), 3);
+        assert_eq!(model.len(), 15);
+
+        // Profile sticks of the line below the plan
+        let profile = collection.line_profile("L1", None);
+        let dxf = collection
+            .to_dxf_with(None, &Transformer::default(), Some(&profile))
+            .unwrap();
+        let model = &entities(&dxf)["ENTITIES"];
+        assert!(dxf.contains("IM_PROFILE_STICK"));
+
+        let soil_layers: usize = profile
+            .iter()
+            .map(|investigation| investigation.soil_layers.len())
+            .sum();
+        assert!(soil_layers > 0);
+        assert_eq!(count(model, "LINE", Some("IM_PROFILE_STICK")), 2);
+        assert_eq!(count(model, "TEXT", Some("IM_PROFILE_TEXT")), 4);
+        assert_eq!(count(model, "LINE", Some("IM_PROFILE_SOIL")), soil_layers);
+        assert_eq!(count(model, "TEXT", Some("IM_PROFILE_SOIL")), soil_layers);
+        assert_eq!(count(model, "LWPOLYLINE", Some("IM_PROFILE_RESISTANCE")), 1);
+        assert_eq!(count(model, "TEXT", Some("IM_PROFILE_RESISTANCE")), 1);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::export::{
+    common_coordinate_system, decimal, parsed, position, soil_layer_summary, token, write,
+};
+use crate::{CoordinateSystem, InfraFile, Investigation, InvestigationCollection, Transformer};
+
+use serde_json::{json, Map, Value};
+
+impl InvestigationCollection {
+    // FeatureCollection of investigation points. With `target` the points are
+    // transformed to that system; WGS84 gives RFC 7946 output. Without it all
+    // investigations must share one coordinate system.
+    pub fn to_geojson(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_geojson_with(target, &Transformer::default())
+    }
+
+    pub fn to_geojson_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        feature_collection(&self.investigations, target, transformer)
+    }
+
+    pub fn write_geojson(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_geojson(target)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_geojson(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_geojson_with(target, &Transformer::default())
+    }
+
+    pub fn to_geojson_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        feature_collection(&self.investigations, target, transformer)
+    }
+
+    pub fn write_geojson(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_geojson(target)?)
+    }
+}
+
+fn feature_collection(
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+) -> Result<String, String> {
+    let coordinate_system = match &target {
+        Some(target) => Some(target.clone()),
+        None => parsed(&common_coordinate_system(investigations)?),
+    };
+
+    let features: Vec<Value> = investigations
+        .iter()
+        .map(|investigation| feature(investigation, target.as_ref(), transformer))
+        .collect();
+
+    let mut collection = Map::new();
+    collection.insert("type".to_string(), json!("FeatureCollection"));
+
+    // RFC 7946 has no CRS member, the pre-RFC named CRS is still read by GIS
+    // software for projected output
+    if let Some(code) = coordinate_system
+        .as_ref()
+        .filter(|system| **system != CoordinateSystem::WGS84)
+        .and_then(CoordinateSystem::epsg)
+    {
+        collection.insert(
+            "crs".to_string(),
+            json!({
+                "type": "name",
+                "properties": { "name": format!("urn:ogc:def:crs:EPSG::{}", code) }
+            }),
+        );
+    }
+
+    collection.insert("features".to_string(), Value::Array(features));
+
+    serde_json::to_string_pretty(&Value::Object(collection)).map_err(|e| e.to_string())
+}
+
+fn feature(
+    investigation: &Investigation,
+    target: Option<&CoordinateSystem>,
+    transformer: &Transformer,
+) -> Value {
+    let output_system = match target {
+        Some(target) => Some(target.clone()),
+        None => parsed(&investigation.spatial.coordinate_system),
+    };
+    let geographic = output_system == Some(CoordinateSystem::WGS84);
+
+    // GeoJSON positions are easting (longitude) first
+    let geometry = match position(investigation, target, transformer) {
+        Some([x, y]) => {
+            let decimals = if geographic { 1e7 } else { 1e3 };
+            json!({
+                "type": "Point",
+                "coordinates": [(y * decimals).round() / decimals, (x * decimals).round() / decimals]
+            })
+        }
+        None => Value::Null,
+    };
+
+    let water = investigation
+        .first_water_observed()
+        .and_then(|water| parsed(&water.depth))
+        .map(decimal);
+
+    let properties = json!({
+        "file": investigation.file_info.path,
+        "point_id": token(&investigation.coordinates.point_id),
+        "method": token(&investigation.method.token),
+        "method_name": parsed(&investigation.method.token).map(|token| token.to_string()),
+        "date": parsed(&investigation.coordinates.date).map(|date| date.format("%Y-%m-%d").to_string()),
+        "start_elevation": parsed(&investigation.coordinates.start_elevation).map(decimal),
+        "coordinate_system": token(&investigation.spatial.coordinate_system),
+        "elevation_system": token(&investigation.spatial.elevation_system),
+        "total_depth": investigation.total_depth.map(decimal),
+        "sounded_depth": investigation.sounded_depth.map(decimal),
+        "termination": token(&investigation.termination.token),
+        "termination_name": parsed(&investigation.termination.token).map(|token| token.to_string()),
+        "investigator": token(&investigation.organisations.investigator_name),
+        "owner": token(&investigation.organisations.owner_name),
+        "work_id": token(&investigation.work.id),
+        "work_name": token(&investigation.work.name),
+        "line": token(&investigation.line.name),
+        "stake": parsed(&investigation.line.stake).map(decimal),
+        "offset": parsed(&investigation.line.distance).map(decimal),
+        "water_depth": water,
+        "soil_layers": soil_layer_summary(investigation),
+    });
+
+    json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.123 385000.456 12.34 01012020 P1
+LN L1 125.3 -2.1
+1.0 100 10 Sa
+2.2 55 20 SaSi
+-1 KI
+";
+
+    #[test]
+    fn geojson_properties() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+
+        let geojson: serde_json::Value =
+            serde_json::from_str(&collection.to_geojson(None).unwrap()).unwrap();
+        assert_eq!(
+            geojson["crs"]["properties"]["name"],
+            "urn:ogc:def:crs:EPSG::3067"
+        );
+
+        let feature = &geojson["features"][0];
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([385000.456, 6672000.123])
+        );
+
+        // f32 values are written in their shortest decimal form
+        let properties = &feature["properties"];
+        assert_eq!(properties["point_id"], "P1");
+        assert_eq!(properties["start_elevation"], 12.34);
+        assert_eq!(properties["stake"], 125.3);
+        assert_eq!(properties["offset"], -2.1);
+        assert_eq!(properties["total_depth"], 2.2);
+        assert_eq!(properties["termination"], "KI");
+    }
+}
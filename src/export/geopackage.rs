@@ -0,0 +1,890 @@
+use crate::export::{
+    common_coordinate_system, decimal, field, joined, observation_date, observation_fields,
+    observation_measurer, parsed, position, sample_id, soil_layer_summary, soil_type, token,
+    FieldValue, DEPTH_FIELDS, OBSERVATION_FIELDS,
+};
+use crate::{
+    CoordinateSystem, ElevationSystem, InfraFile, Investigation, InvestigationCollection,
+    LabResult, ObservationValues, ParseResult, Transformer,
+};
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+
+use std::fs;
+use std::path::Path;
+
+// "GPKG" and version 1.4.0
+const APPLICATION_ID: i32 = 0x4750_4B47;
+const USER_VERSION: i32 = 10400;
+
+impl InvestigationCollection {
+    // Writes an OGC GeoPackage with an investigation point layer and
+    // observations, soil layers, samples, lab results and groundwater tables
+    // linked to it. With `target` the points are transformed to that system,
+    // otherwise all investigations must share one coordinate system. An
+    // existing file is replaced.
+    pub fn write_geopackage(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        self.write_geopackage_with(file_path, target, &Transformer::default())
+    }
+
+    pub fn write_geopackage_with(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        write_geopackage(file_path, &self.investigations, target, transformer)
+    }
+}
+
+impl InfraFile {
+    pub fn write_geopackage(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        self.write_geopackage_with(file_path, target, &Transformer::default())
+    }
+
+    pub fn write_geopackage_with(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        write_geopackage(file_path, &self.investigations, target, transformer)
+    }
+}
+
+fn write_geopackage(
+    file_path: &str,
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+) -> Result<(), String> {
+    let coordinate_system = match &target {
+        Some(target) => Some(target.clone()),
+        None => parsed(&common_coordinate_system(investigations)?),
+    };
+
+    if Path::new(file_path).exists() {
+        fs::remove_file(file_path)
+            .map_err(|e| format!("Failed to replace file '{}': {}", file_path, e))?;
+    }
+
+    let mut connection = Connection::open(file_path)
+        .map_err(|e| format!("Failed to create file '{}': {}", file_path, e))?;
+    let transaction = connection.transaction().map_err(sql_error)?;
+
+    create_metadata(&transaction)?;
+    let srs_id = insert_srs(&transaction, coordinate_system.as_ref(), investigations)?;
+    create_tables(&transaction, srs_id)?;
+
+    let mut extent: Option<(f64, f64, f64, f64)> = None;
+
+    for (index, investigation) in investigations.iter().enumerate() {
+        let fid = index as i64 + 1;
+        let point = position(investigation, target.as_ref(), transformer);
+
+        if let Some([x, y]) = point {
+            // GeoPackage axes are easting (longitude) first
+            extent = Some(match extent {
+                None => (y, x, y, x),
+                Some((a, b, c, d)) => (a.min(y), b.min(x), c.max(y), d.max(x)),
+            });
+        }
+
+        insert_investigation(&transaction, fid, investigation, point, srs_id)?;
+        insert_observations(&transaction, fid, investigation)?;
+        insert_soil_layers(&transaction, fid, investigation)?;
+    }
+
+    if let Some((min_x, min_y, max_x, max_y)) = extent {
+        transaction
+            .execute(
+                "UPDATE gpkg_contents SET min_x = ?1, min_y = ?2, max_x = ?3, max_y = ?4
+                 WHERE table_name = 'investigations'",
+                params![min_x, min_y, max_x, max_y],
+            )
+            .map_err(sql_error)?;
+    }
+
+    transaction.commit().map_err(sql_error)
+}
+
+fn sql_error(error: rusqlite::Error) -> String {
+    format!("GeoPackage error: {}", error)
+}
+
+fn create_metadata(transaction: &Transaction) -> Result<(), String> {
+    transaction
+        .execute_batch(&format!(
+            "PRAGMA application_id = {};
+             PRAGMA user_version = {};
+             CREATE TABLE gpkg_spatial_ref_sys (
+                 srs_name TEXT NOT NULL,
+                 srs_id INTEGER PRIMARY KEY,
+                 organization TEXT NOT NULL,
+                 organization_coordsys_id INTEGER NOT NULL,
+                 definition TEXT NOT NULL,
+                 description TEXT
+             );
+             CREATE TABLE gpkg_contents (
+                 table_name TEXT NOT NULL PRIMARY KEY,
+                 data_type TEXT NOT NULL,
+                 identifier TEXT UNIQUE,
+                 description TEXT DEFAULT '',
+                 last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                 min_x DOUBLE,
+                 min_y DOUBLE,
+                 max_x DOUBLE,
+                 max_y DOUBLE,
+                 srs_id INTEGER,
+                 CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+             );
+             CREATE TABLE gpkg_geometry_columns (
+                 table_name TEXT NOT NULL,
+                 column_name TEXT NOT NULL,
+                 geometry_type_name TEXT NOT NULL,
+                 srs_id INTEGER NOT NULL,
+                 z TINYINT NOT NULL,
+                 m TINYINT NOT NULL,
+                 CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+                 CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+                 CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+                 CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+             );",
+            APPLICATION_ID, USER_VERSION
+        ))
+        .map_err(sql_error)?;
+
+    let wgs84 = CoordinateSystem::WGS84.to_wkt().unwrap_or_default();
+    let mut statement = transaction
+        .prepare(
+            "INSERT INTO gpkg_spatial_ref_sys
+             (srs_name, srs_id, organization, organization_coordsys_id, definition, description)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .map_err(sql_error)?;
+
+    for row in [
+        (
+            "WGS 84 geodetic",
+            4326,
+            "EPSG",
+            4326,
+            wgs84.as_str(),
+            "longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid",
+        ),
+        (
+            "Undefined cartesian SRS",
+            -1,
+            "NONE",
+            -1,
+            "undefined",
+            "undefined cartesian coordinate reference system",
+        ),
+        (
+            "Undefined geographic SRS",
+            0,
+            "NONE",
+            0,
+            "undefined",
+            "undefined geographic coordinate reference system",
+        ),
+    ] {
+        statement
+            .execute(params![row.0, row.1, row.2, row.3, row.4, row.5])
+            .map_err(sql_error)?;
+    }
+
+    Ok(())
+}
+
+// Spatial reference of the point layer, -1 (undefined) when the coordinate
+// system has no EPSG code
+fn insert_srs(
+    transaction: &Transaction,
+    coordinate_system: Option<&CoordinateSystem>,
+    investigations: &[Investigation],
+) -> Result<i64, String> {
+    let Some(system) = coordinate_system else {
+        return Ok(-1);
+    };
+    let Some(code) = system.epsg() else {
+        return Ok(-1);
+    };
+
+    // Heights are attributes, the elevation system is only described
+    let mut elevation_systems = investigations
+        .iter()
+        .map(|investigation| &investigation.spatial.elevation_system);
+    let elevation_system: Option<ElevationSystem> = match elevation_systems.next() {
+        Some(ParseResult::Parsed(first))
+            if elevation_systems.all(|system| system == &ParseResult::Parsed(first.clone())) =>
+        {
+            Some(first.clone())
+        }
+        _ => None,
+    };
+
+    let description = match elevation_system {
+        Some(elevation_system) => format!("{}, elevations in {}", system, elevation_system),
+        None => system.to_string(),
+    };
+
+    transaction
+        .execute(
+            "INSERT OR IGNORE INTO gpkg_spatial_ref_sys
+             (srs_name, srs_id, organization, organization_coordsys_id, definition, description)
+             VALUES (?1, ?2, 'EPSG', ?2, ?3, ?4)",
+            params![
+                system.to_string(),
+                code,
+                system.to_wkt().unwrap_or_else(|| "undefined".to_string()),
+                description
+            ],
+        )
+        .map_err(sql_error)?;
+
+    Ok(code as i64)
+}
+
+fn observation_column_type(name: &str) -> &'static str {
+    match name {
+        "hits" | "half_turns" | "time" | "boulders" => "INTEGER",
+        "mode" | "water_type" => "TEXT",
+        _ => "REAL",
+    }
+}
+
+fn create_tables(transaction: &Transaction, srs_id: i64) -> Result<(), String> {
+    let value_columns: Vec<String> = OBSERVATION_FIELDS
+        .iter()
+        .filter(|name| !DEPTH_FIELDS.contains(name))
+        .map(|name| format!("{} {}", name, observation_column_type(name)))
+        .collect();
+
+    transaction
+        .execute_batch(&format!(
+            "CREATE TABLE investigations (
+                 fid INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 geom POINT,
+                 file TEXT,
+                 point_id TEXT,
+                 method TEXT,
+                 method_name TEXT,
+                 date DATE,
+                 start_elevation REAL,
+                 coordinate_system TEXT,
+                 elevation_system TEXT,
+                 total_depth REAL,
+                 sounded_depth REAL,
+                 termination TEXT,
+                 owner TEXT,
+                 investigator TEXT,
+                 work_id TEXT,
+                 work_name TEXT,
+                 line TEXT,
+                 stake REAL,
+                 offset REAL,
+                 soil_layers TEXT
+             );
+             CREATE TABLE observations (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 investigation_id INTEGER NOT NULL REFERENCES investigations(fid),
+                 observation INTEGER NOT NULL,
+                 depth REAL,
+                 depth_to REAL,
+                 elevation REAL,
+                 {},
+                 date DATE,
+                 measurer TEXT,
+                 soil_type TEXT,
+                 unofficial_soil_type TEXT,
+                 notes TEXT,
+                 free_text TEXT
+             );
+             CREATE TABLE soil_layers (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 investigation_id INTEGER NOT NULL REFERENCES investigations(fid),
+                 top_depth REAL,
+                 bottom_depth REAL,
+                 thickness REAL,
+                 top_elevation REAL,
+                 bottom_elevation REAL,
+                 soil_type TEXT,
+                 source TEXT
+             );
+             CREATE TABLE samples (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 investigation_id INTEGER NOT NULL REFERENCES investigations(fid),
+                 observation_id INTEGER NOT NULL REFERENCES observations(id),
+                 method TEXT,
+                 sample_id TEXT,
+                 start_depth REAL,
+                 end_depth REAL,
+                 soil_type TEXT
+             );
+             CREATE TABLE lab_results (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 sample_id INTEGER NOT NULL REFERENCES samples(id),
+                 kind TEXT,
+                 attribute TEXT,
+                 result TEXT,
+                 unit TEXT,
+                 grain_mm REAL,
+                 pass_percent REAL
+             );
+             CREATE TABLE groundwater (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                 investigation_id INTEGER NOT NULL REFERENCES investigations(fid),
+                 observation_id INTEGER NOT NULL REFERENCES observations(id),
+                 source TEXT,
+                 water_type TEXT,
+                 depth REAL,
+                 elevation REAL,
+                 pressure REAL,
+                 date DATE,
+                 measurer TEXT
+             );",
+            value_columns.join(",\n")
+        ))
+        .map_err(sql_error)?;
+
+    transaction
+        .execute(
+            "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, srs_id)
+             VALUES ('investigations', 'features', 'investigations', 'Ground investigations', ?1)",
+            params![srs_id],
+        )
+        .map_err(sql_error)?;
+    transaction
+        .execute(
+            "INSERT INTO gpkg_geometry_columns
+             (table_name, column_name, geometry_type_name, srs_id, z, m)
+             VALUES ('investigations', 'geom', 'POINT', ?1, 0, 0)",
+            params![srs_id],
+        )
+        .map_err(sql_error)?;
+
+    for (table, description) in [
+        ("observations", "Observation rows"),
+        ("soil_layers", "Soil layer intervals"),
+        ("samples", "Samples"),
+        ("lab_results", "Laboratory results of samples"),
+        ("groundwater", "Groundwater readings"),
+    ] {
+        transaction
+            .execute(
+                "INSERT INTO gpkg_contents (table_name, data_type, identifier, description)
+                 VALUES (?1, 'attributes', ?1, ?2)",
+                params![table, description],
+            )
+            .map_err(sql_error)?;
+    }
+
+    Ok(())
+}
+
+// GeoPackage binary header followed by a little-endian WKB point
+fn point_blob(x: f64, y: f64, srs_id: i64) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(29);
+    blob.extend_from_slice(b"GP");
+    blob.push(0);
+    // Little-endian, no envelope
+    blob.push(0b0000_0001);
+    blob.extend_from_slice(&(srs_id as i32).to_le_bytes());
+    blob.push(1);
+    blob.extend_from_slice(&1u32.to_le_bytes());
+    blob.extend_from_slice(&y.to_le_bytes());
+    blob.extend_from_slice(&x.to_le_bytes());
+    blob
+}
+
+fn value(field: Option<FieldValue>) -> Value {
+    match field {
+        Some(FieldValue::Real(value)) => Value::Real(value),
+        Some(FieldValue::Integer(value)) => Value::Integer(value),
+        Some(FieldValue::Text(value)) => Value::Text(value),
+        Some(FieldValue::Date(value)) => Value::Text(value.format("%Y-%m-%d").to_string()),
+        None => Value::Null,
+    }
+}
+
+fn real(value: Option<f32>) -> Value {
+    value
+        .map(|value| Value::Real(decimal(value)))
+        .unwrap_or(Value::Null)
+}
+
+fn insert_investigation(
+    transaction: &Transaction,
+    fid: i64,
+    investigation: &Investigation,
+    point: Option<[f64; 2]>,
+    srs_id: i64,
+) -> Result<(), String> {
+    let geometry = match point {
+        Some([x, y]) => Value::Blob(point_blob(x, y, srs_id)),
+        None => Value::Null,
+    };
+
+    let values = vec![
+        Value::Integer(fid),
+        geometry,
+        value(investigation.file_info.path.clone().map(FieldValue::Text)),
+        value(token(&investigation.coordinates.point_id).map(FieldValue::Text)),
+        value(token(&investigation.method.token).map(FieldValue::Text)),
+        value(
+            parsed(&investigation.method.token)
+                .map(|token| token.to_string())
+                .map(FieldValue::Text),
+        ),
+        value(field(&investigation.coordinates.date)),
+        value(field(&investigation.coordinates.start_elevation)),
+        value(token(&investigation.spatial.coordinate_system).map(FieldValue::Text)),
+        value(token(&investigation.spatial.elevation_system).map(FieldValue::Text)),
+        real(investigation.total_depth),
+        real(investigation.sounded_depth),
+        value(token(&investigation.termination.token).map(FieldValue::Text)),
+        value(token(&investigation.organisations.owner_name).map(FieldValue::Text)),
+        value(token(&investigation.organisations.investigator_name).map(FieldValue::Text)),
+        value(token(&investigation.work.id).map(FieldValue::Text)),
+        value(token(&investigation.work.name).map(FieldValue::Text)),
+        value(token(&investigation.line.name).map(FieldValue::Text)),
+        value(field(&investigation.line.stake)),
+        value(field(&investigation.line.distance)),
+        Value::Text(soil_layer_summary(investigation)),
+    ];
+
+    transaction
+        .execute(
+            "INSERT INTO investigations (fid, geom, file, point_id, method, method_name, date,
+             start_elevation, coordinate_system, elevation_system, total_depth, sounded_depth,
+             termination, owner, investigator, work_id, work_name, line, stake, offset, soil_layers)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+             ?18, ?19, ?20, ?21)",
+            params_from_iter(values),
+        )
+        .map_err(sql_error)?;
+
+    Ok(())
+}
+
+fn insert_observations(
+    transaction: &Transaction,
+    fid: i64,
+    investigation: &Investigation,
+) -> Result<(), String> {
+    let value_columns: Vec<&str> = OBSERVATION_FIELDS
+        .iter()
+        .filter(|name| !DEPTH_FIELDS.contains(name))
+        .copied()
+        .collect();
+
+    let columns = [
+        vec![
+            "investigation_id",
+            "observation",
+            "depth",
+            "depth_to",
+            "elevation",
+        ],
+        value_columns.clone(),
+        vec![
+            "date",
+            "measurer",
+            "soil_type",
+            "unofficial_soil_type",
+            "notes",
+            "free_text",
+        ],
+    ]
+    .concat();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!(
+        "INSERT INTO observations ({}) VALUES ({})",
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+
+    let start_elevation = parsed(&investigation.coordinates.start_elevation);
+
+    for (index, observation) in investigation.observations.iter().enumerate() {
+        let fields = observation_fields(&observation.values);
+        let named = |name: &str| {
+            fields
+                .iter()
+                .find(|(field_name, _)| *field_name == name)
+                .and_then(|(_, value)| value.clone())
+        };
+
+        let depth = named("depth").or_else(|| named("start_depth"));
+        let elevation = match (start_elevation, depth.as_ref().and_then(FieldValue::as_f64)) {
+            (Some(start), Some(depth)) => Some(start as f64 - depth),
+            _ => observation
+                .values
+                .get_parsed_elevation()
+                .map(|elevation| elevation as f64),
+        };
+
+        let mut values = vec![
+            Value::Integer(fid),
+            Value::Integer(index as i64),
+            value(depth.clone()),
+            value(named("end_depth")),
+            elevation
+                .map(|elevation| Value::Real((elevation * 1000.0).round() / 1000.0))
+                .unwrap_or(Value::Null),
+        ];
+        values.extend(value_columns.iter().map(|name| value(named(name))));
+        values.extend([
+            value(observation_date(&observation.values)),
+            value(observation_measurer(&observation.values)),
+            value(soil_type(&observation.values)),
+            value(joined(&observation.unofficial_soil_type)),
+            value(joined(&observation.notes)),
+            value(joined(&observation.free_text)),
+        ]);
+
+        transaction
+            .execute(&sql, params_from_iter(values))
+            .map_err(sql_error)?;
+        let observation_id = transaction.last_insert_rowid();
+
+        if let ParseResult::Parsed(water) = &observation.water_observed {
+            let water_depth = parsed(&water.depth);
+            transaction
+                .execute(
+                    "INSERT INTO groundwater (investigation_id, observation_id, source, water_type,
+                     depth, elevation) VALUES (?1, ?2, 'VS', ?3, ?4, ?5)",
+                    params_from_iter([
+                        Value::Integer(fid),
+                        Value::Integer(observation_id),
+                        value(token(&water.token).map(FieldValue::Text)),
+                        value(field(&water.depth)),
+                        match (start_elevation, water_depth) {
+                            (Some(start), Some(depth)) => real(Some(start - depth)),
+                            _ => Value::Null,
+                        },
+                    ]),
+                )
+                .map_err(sql_error)?;
+        }
+
+        insert_monitoring(
+            transaction,
+            fid,
+            observation_id,
+            investigation,
+            &observation.values,
+        )?;
+        insert_sample(
+            transaction,
+            fid,
+            observation_id,
+            investigation,
+            &observation.values,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Water level and pore pressure readings of standpipes and wells
+fn insert_monitoring(
+    transaction: &Transaction,
+    fid: i64,
+    observation_id: i64,
+    investigation: &Investigation,
+    values: &ObservationValues,
+) -> Result<(), String> {
+    let (depth, elevation, pressure) = match values {
+        ObservationValues::VP { surface_elev, .. }
+        | ObservationValues::VO { surface_elev, .. }
+        | ObservationValues::VK { surface_elev, .. }
+        | ObservationValues::VPK { surface_elev, .. } => {
+            let depth = match (
+                parsed(&investigation.coordinates.start_elevation),
+                parsed(surface_elev),
+            ) {
+                (Some(start), Some(elevation)) => real(Some(start - elevation)),
+                _ => Value::Null,
+            };
+            (depth, value(field(surface_elev)), Value::Null)
+        }
+        ObservationValues::HV {
+            depth, pressure, ..
+        } => (value(field(depth)), Value::Null, value(field(pressure))),
+        _ => return Ok(()),
+    };
+
+    let water_type = match values {
+        ObservationValues::VK { water_type, .. } => value(token(water_type).map(FieldValue::Text)),
+        _ => Value::Null,
+    };
+
+    transaction
+        .execute(
+            "INSERT INTO groundwater (investigation_id, observation_id, source, water_type, depth,
+             elevation, pressure, date, measurer) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params_from_iter([
+                Value::Integer(fid),
+                Value::Integer(observation_id),
+                value(token(&investigation.method.token).map(FieldValue::Text)),
+                water_type,
+                depth,
+                elevation,
+                pressure,
+                value(observation_date(values)),
+                value(observation_measurer(values)),
+            ]),
+        )
+        .map_err(sql_error)?;
+
+    Ok(())
+}
+
+fn insert_sample(
+    transaction: &Transaction,
+    fid: i64,
+    observation_id: i64,
+    investigation: &Investigation,
+    values: &ObservationValues,
+) -> Result<(), String> {
+    let (start_depth, end_depth, lab_values) = match values {
+        ObservationValues::NO {
+            start_depth,
+            end_depth,
+            lab_values,
+            ..
+        }
+        | ObservationValues::NE {
+            start_depth,
+            end_depth,
+            lab_values,
+            ..
+        } => (start_depth, end_depth, lab_values),
+        _ => return Ok(()),
+    };
+
+    transaction
+        .execute(
+            "INSERT INTO samples (investigation_id, observation_id, method, sample_id,
+             start_depth, end_depth, soil_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params_from_iter([
+                Value::Integer(fid),
+                Value::Integer(observation_id),
+                value(token(&investigation.method.token).map(FieldValue::Text)),
+                value(sample_id(values)),
+                value(field(start_depth)),
+                value(field(end_depth)),
+                value(soil_type(values)),
+            ]),
+        )
+        .map_err(sql_error)?;
+    let sample = transaction.last_insert_rowid();
+
+    for lab_value in lab_values {
+        let ParseResult::Parsed(lab_value) = lab_value else {
+            continue;
+        };
+
+        let row = match lab_value {
+            LabResult::GrainSize {
+                grain_mm,
+                pass_percent,
+            } => [
+                Value::Text("grain_size".to_string()),
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                value(field(grain_mm)),
+                value(field(pass_percent)),
+            ],
+            LabResult::WaterContent { water_content } => [
+                Value::Text("water_content".to_string()),
+                Value::Text("w".to_string()),
+                value(field(water_content)),
+                Value::Text("%".to_string()),
+                Value::Null,
+                Value::Null,
+            ],
+            LabResult::Other {
+                attribute,
+                result,
+                unit,
+            } => [
+                Value::Text("other".to_string()),
+                value(field(attribute)),
+                value(field(result)),
+                value(field(unit)),
+                Value::Null,
+                Value::Null,
+            ],
+            LabResult::None => continue,
+        };
+
+        let mut values = vec![Value::Integer(sample)];
+        values.extend(row);
+
+        transaction
+            .execute(
+                "INSERT INTO lab_results (sample_id, kind, attribute, result, unit, grain_mm,
+                 pass_percent) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params_from_iter(values),
+            )
+            .map_err(sql_error)?;
+    }
+
+    Ok(())
+}
+
+fn insert_soil_layers(
+    transaction: &Transaction,
+    fid: i64,
+    investigation: &Investigation,
+) -> Result<(), String> {
+    for layer in &investigation.soil_layers {
+        transaction
+            .execute(
+                "INSERT INTO soil_layers (investigation_id, top_depth, bottom_depth, thickness,
+                 top_elevation, bottom_elevation, soil_type, source)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params_from_iter([
+                    Value::Integer(fid),
+                    real(Some(layer.top_depth)),
+                    real(Some(layer.bottom_depth)),
+                    real(Some(layer.thickness)),
+                    real(layer.top_elevation),
+                    real(layer.bottom_elevation),
+                    Value::Text(layer.soil_type.clone()),
+                    Value::Text(layer.source.to_string()),
+                ]),
+            )
+            .map_err(sql_error)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use rusqlite::Connection;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+1.0 100 10 Sa
+VS 1.4 VO
+2.0 55 20 SaSi
+-1 KI
+TT NO 1 N1
+XY 6672010.0 385010.0 12.40 01012020 N1
+0.5 S1 1.0 Mr
+RK 0.063 12.5
+RK 2 80
+LB w 23.4 %
+LB wL 40 %
+-1 MS
+TT VP 1 V1
+XY 6672020.0 385020.0 12.30 01012020 V1
+10.5 15012020 13.0 8.0 1.0
+-1 KI
+";
+
+    #[test]
+    fn geopackage_tables() {
+        let file = InfraFile::parse_str(INFRA);
+
+        let output = temp_path("geopackage.gpkg");
+        file.write_geopackage(output.to_str().unwrap(), None)
+            .unwrap();
+        let connection = Connection::open(&output).unwrap();
+
+        let pragma = |name: &str| -> i32 {
+            connection
+                .query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(pragma("application_id"), 0x4750_4B47);
+        assert_eq!(pragma("user_version"), 10400);
+
+        let mut statement = connection
+            .prepare("SELECT table_name, data_type, srs_id FROM gpkg_contents ORDER BY table_name")
+            .unwrap();
+        let contents: Vec<(String, String, Option<i64>)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                ("groundwater".to_string(), "attributes".to_string(), None),
+                (
+                    "investigations".to_string(),
+                    "features".to_string(),
+                    Some(3067)
+                ),
+                ("lab_results".to_string(), "attributes".to_string(), None),
+                ("observations".to_string(), "attributes".to_string(), None),
+                ("samples".to_string(), "attributes".to_string(), None),
+                ("soil_layers".to_string(), "attributes".to_string(), None),
+            ]
+        );
+
+        let srs: (i64, String) = connection
+            .query_row(
+                "SELECT srs_id, organization FROM gpkg_geometry_columns
+                 JOIN gpkg_spatial_ref_sys USING (srs_id) WHERE table_name = 'investigations'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(srs, (3067, "EPSG".to_string()));
+
+        let count = |table: &str| -> i64 {
+            connection
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(count("investigations"), 3);
+        assert_eq!(count("observations"), 4);
+        assert_eq!(count("samples"), 1);
+        assert_eq!(count("lab_results"), 4);
+        assert_eq!(count("groundwater"), 2);
+
+        // Point blob: header, srs id and the point easting first
+        let geometry: Vec<u8> = connection
+            .query_row("SELECT geom FROM investigations WHERE fid = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(&geometry[..4], b"GP\x00\x01");
+        assert_eq!(i32::from_le_bytes(geometry[4..8].try_into().unwrap()), 3067);
+        assert_eq!(
+            f64::from_le_bytes(geometry[13..21].try_into().unwrap()),
+            385000.0
+        );
+        assert_eq!(
+            f64::from_le_bytes(geometry[21..29].try_into().unwrap()),
+            6672000.0
+        );
+
+        drop(statement);
+        drop(connection);
+        std::fs::remove_file(&output).ok();
+    }
+}
@@ -0,0 +1,682 @@
+use crate::export::{common_coordinate_system, decimal, parsed, position, token, write};
+use crate::{
+    CoordinateSystem, ElevationSystem, InfraFile, Investigation, InvestigationCollection,
+    ParseResult, SoilLayer, Transformer,
+};
+
+use chrono::Local;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Radius of the strata cylinders, the boreholes themselves are axis lines
+const STRATUM_RADIUS: f64 = 0.05;
+
+impl InvestigationCollection {
+    pub fn to_ifc(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_ifc_with(target, &Transformer::default())
+    }
+
+    pub fn to_ifc_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        ifc(&self.investigations, target, transformer)
+    }
+
+    pub fn write_ifc(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_ifc(target)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_ifc(&self, target: Option<CoordinateSystem>) -> Result<String, String> {
+        self.to_ifc_with(target, &Transformer::default())
+    }
+
+    pub fn to_ifc_with(
+        &self,
+        target: Option<CoordinateSystem>,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        ifc(&self.investigations, target, transformer)
+    }
+
+    pub fn write_ifc(
+        &self,
+        file_path: &str,
+        target: Option<CoordinateSystem>,
+    ) -> Result<(), String> {
+        write(file_path, &self.to_ifc(target)?)
+    }
+}
+
+// ISO 10303-21 string, non-ASCII characters as \X2\ UTF-16 escapes
+fn string(value: &str) -> String {
+    let mut output = String::from("'");
+    for character in value.chars() {
+        match character {
+            '\'' => output.push_str("''"),
+            '\\' => output.push_str("\\\\"),
+            ' '..='~' => output.push(character),
+            _ => {
+                let mut units = [0u16; 2];
+                output.push_str("\\X2\\");
+                for unit in character.encode_utf16(&mut units) {
+                    output.push_str(&format!("{:04X}", unit));
+                }
+                output.push_str("\\X0\\");
+            }
+        }
+    }
+    output.push('\'');
+    output
+}
+
+fn optional_string(value: Option<String>) -> String {
+    value
+        .map(|value| string(&value))
+        .unwrap_or_else(|| "$".to_string())
+}
+
+fn real(value: f64) -> String {
+    let text = format!("{}", value);
+    if text.contains(['.', 'e', 'E']) {
+        text
+    } else {
+        format!("{}.", text)
+    }
+}
+
+fn references(ids: &[usize]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Property value of a single value property
+enum Property {
+    Label(String),
+    Text(String),
+    Integer(i32),
+    Length(f64),
+    Date(String),
+}
+
+impl Property {
+    fn value(&self) -> String {
+        match self {
+            Property::Label(value) => format!("IFCLABEL({})", string(value)),
+            Property::Text(value) => format!("IFCTEXT({})", string(value)),
+            Property::Integer(value) => format!("IFCINTEGER({})", value),
+            Property::Length(value) => format!("IFCLENGTHMEASURE({})", real(*value)),
+            Property::Date(value) => format!("IFCDATE({})", string(value)),
+        }
+    }
+}
+
+fn label<T: crate::infra_file::write::InfraToken>(value: &ParseResult<T>) -> Option<Property> {
+    token(value).map(Property::Label)
+}
+
+fn length(value: Option<f32>) -> Option<Property> {
+    value.map(|value| Property::Length(decimal(value)))
+}
+
+#[derive(Default)]
+struct StepWriter {
+    entities: Vec<String>,
+    guids: usize,
+    seed: u64,
+}
+
+impl StepWriter {
+    fn add(&mut self, entity: String) -> usize {
+        self.entities.push(entity);
+        self.entities.len()
+    }
+
+    // Deterministic IfcGloballyUniqueId, 128 bits in the IFC base64 alphabet
+    fn guid(&mut self) -> String {
+        const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$";
+
+        self.guids += 1;
+        let mut value: u128 = 0;
+        for part in 0..2u8 {
+            let mut hasher = DefaultHasher::new();
+            (self.seed, self.guids, part).hash(&mut hasher);
+            value = (value << 64) | hasher.finish() as u128;
+        }
+
+        // 22 characters, the first one holds the two highest bits
+        let mut guid = String::with_capacity(22);
+        guid.push(ALPHABET[(value >> 126) as usize] as char);
+        for index in (0..21).rev() {
+            guid.push(ALPHABET[((value >> (index * 6)) & 0x3f) as usize] as char);
+        }
+        string(&guid)
+    }
+
+    // Local coordinates rounded to millimetres
+    fn point(&mut self, coordinates: [f64; 3]) -> usize {
+        let coordinates = coordinates.map(|value| (value * 1000.0).round() / 1000.0 + 0.0);
+        self.add(format!(
+            "IFCCARTESIANPOINT(({},{},{}))",
+            real(coordinates[0]),
+            real(coordinates[1]),
+            real(coordinates[2])
+        ))
+    }
+
+    fn placement(&mut self, relative_to: Option<usize>, coordinates: [f64; 3]) -> usize {
+        let point = self.point(coordinates);
+        let axis = self.add(format!("IFCAXIS2PLACEMENT3D(#{},$,$)", point));
+        let relative_to = relative_to
+            .map(|id| format!("#{}", id))
+            .unwrap_or_else(|| "$".to_string());
+        self.add(format!("IFCLOCALPLACEMENT({},#{})", relative_to, axis))
+    }
+
+    fn property_set(
+        &mut self,
+        name: &str,
+        objects: &[usize],
+        properties: Vec<(&str, Option<Property>)>,
+    ) {
+        let properties: Vec<usize> = properties
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (name, value)))
+            .map(|(name, value)| {
+                self.add(format!(
+                    "IFCPROPERTYSINGLEVALUE({},$,{},$)",
+                    string(name),
+                    value.value()
+                ))
+            })
+            .collect();
+        if properties.is_empty() {
+            return;
+        }
+
+        let guid = self.guid();
+        let set = self.add(format!(
+            "IFCPROPERTYSET({},$,{},$,({}))",
+            guid,
+            string(name),
+            references(&properties)
+        ));
+        let guid = self.guid();
+        self.add(format!(
+            "IFCRELDEFINESBYPROPERTIES({},$,$,$,({}),#{})",
+            guid,
+            references(objects),
+            set
+        ));
+    }
+}
+
+fn geodetic_datum(system: &CoordinateSystem) -> &'static str {
+    match system {
+        CoordinateSystem::KKJ0
+        | CoordinateSystem::KKJ1
+        | CoordinateSystem::KKJ2
+        | CoordinateSystem::KKJ3
+        | CoordinateSystem::KKJ4
+        | CoordinateSystem::KKJ5
+        | CoordinateSystem::YKJ => "KKJ",
+        _ => "ETRS89",
+    }
+}
+
+fn ifc(
+    investigations: &[Investigation],
+    target: Option<CoordinateSystem>,
+    transformer: &Transformer,
+) -> Result<String, String> {
+    let coordinate_system = match &target {
+        Some(target) => Some(target.clone()),
+        None => parsed(&common_coordinate_system(investigations)?),
+    };
+    if coordinate_system == Some(CoordinateSystem::WGS84) {
+        return Err("IFC export needs a projected coordinate system".to_string());
+    }
+
+    // Placements are in metres above the datum, so the elevations must agree
+    let mut elevation_systems = investigations
+        .iter()
+        .map(|investigation| &investigation.spatial.elevation_system);
+    let elevation_system: Option<ElevationSystem> = match elevation_systems.next() {
+        Some(first) if elevation_systems.all(|system| system == first) => parsed(first),
+        Some(_) => {
+            return Err(
+                "Investigations are in different elevation systems, harmonise them first"
+                    .to_string(),
+            )
+        }
+        None => None,
+    };
+
+    let positions: Vec<Option<[f64; 2]>> = investigations
+        .iter()
+        .map(|investigation| position(investigation, target.as_ref(), transformer))
+        .collect();
+
+    // Local origin near the data keeps the coordinates small, easting first
+    let origin = positions
+        .iter()
+        .flatten()
+        .next()
+        .map(|[x, y]| [(y / 100.0).floor() * 100.0, (x / 100.0).floor() * 100.0])
+        .unwrap_or([0.0, 0.0]);
+
+    let mut seed_hasher = DefaultHasher::new();
+    for investigation in investigations {
+        investigation.file_info.path.hash(&mut seed_hasher);
+        token(&investigation.coordinates.point_id).hash(&mut seed_hasher);
+    }
+    let mut step = StepWriter {
+        seed: seed_hasher.finish(),
+        ..Default::default()
+    };
+
+    let units: Vec<usize> = [
+        "IFCSIUNIT(*,.LENGTHUNIT.,$,.METRE.)",
+        "IFCSIUNIT(*,.AREAUNIT.,$,.SQUARE_METRE.)",
+        "IFCSIUNIT(*,.VOLUMEUNIT.,$,.CUBIC_METRE.)",
+        "IFCSIUNIT(*,.PLANEANGLEUNIT.,$,.RADIAN.)",
+    ]
+    .iter()
+    .map(|unit| step.add(unit.to_string()))
+    .collect();
+    let unit_assignment = step.add(format!("IFCUNITASSIGNMENT(({}))", references(&units)));
+
+    let world_origin = step.point([0.0, 0.0, 0.0]);
+    let world = step.add(format!("IFCAXIS2PLACEMENT3D(#{},$,$)", world_origin));
+    let context = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONCONTEXT($,'Model',3,1.E-05,#{},$)",
+        world
+    ));
+    let body_context = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONSUBCONTEXT('Body','Model',*,*,*,*,#{},$,.MODEL_VIEW.,$)",
+        context
+    ));
+    let axis_context = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONSUBCONTEXT('Axis','Model',*,*,*,*,#{},$,.GRAPH_VIEW.,$)",
+        context
+    ));
+
+    if let Some(system) = &coordinate_system {
+        if let Some(code) = system.epsg() {
+            let vertical = elevation_system.as_ref().map(|system| match system.epsg() {
+                Some(code) => format!("EPSG:{}", code),
+                None => system.to_string(),
+            });
+            let crs = step.add(format!(
+                "IFCPROJECTEDCRS({},{},{},{},$,$,#{})",
+                string(&format!("EPSG:{}", code)),
+                string(&system.to_string()),
+                string(geodetic_datum(system)),
+                optional_string(vertical),
+                units[0]
+            ));
+            step.add(format!(
+                "IFCMAPCONVERSION(#{},#{},{},{},0.,1.,0.,1.,$,$)",
+                context,
+                crs,
+                real(origin[0]),
+                real(origin[1])
+            ));
+        }
+    }
+
+    let first = investigations.first();
+    let project_name = first
+        .and_then(|investigation| token(&investigation.work.name))
+        .or_else(|| first.and_then(|investigation| token(&investigation.work.id)))
+        .unwrap_or_else(|| "Ground investigations".to_string());
+    let guid = step.guid();
+    let project = step.add(format!(
+        "IFCPROJECT({},$,{},$,$,$,$,(#{}),#{})",
+        guid,
+        string(&project_name),
+        context,
+        unit_assignment
+    ));
+
+    let site_placement = step.placement(None, [0.0, 0.0, 0.0]);
+    let guid = step.guid();
+    let site = step.add(format!(
+        "IFCSITE({},$,'Site',$,$,#{},$,$,.ELEMENT.,$,$,$,$,$)",
+        guid, site_placement
+    ));
+    let guid = step.guid();
+    step.add(format!(
+        "IFCRELAGGREGATES({},$,$,$,#{},(#{}))",
+        guid, project, site
+    ));
+
+    let mut boreholes = Vec::new();
+
+    for (investigation, point) in investigations.iter().zip(&positions) {
+        let elevation = parsed(&investigation.coordinates.start_elevation).unwrap_or(0.0) as f64;
+        let depth = investigation.total_depth.unwrap_or(0.0) as f64;
+
+        let placement = point.map(|[x, y]| {
+            step.placement(
+                Some(site_placement),
+                [y - origin[0], x - origin[1], elevation],
+            )
+        });
+        let representation = if point.is_some() && depth > 0.0 {
+            let top = step.point([0.0, 0.0, 0.0]);
+            let bottom = step.point([0.0, 0.0, -depth]);
+            let line = step.add(format!("IFCPOLYLINE((#{},#{}))", top, bottom));
+            let shape = step.add(format!(
+                "IFCSHAPEREPRESENTATION(#{},'Axis','Curve3D',(#{}))",
+                axis_context, line
+            ));
+            Some(step.add(format!("IFCPRODUCTDEFINITIONSHAPE($,$,(#{}))", shape)))
+        } else {
+            None
+        };
+
+        let name = token(&investigation.coordinates.point_id);
+        let method = token(&investigation.method.token);
+        let guid = step.guid();
+        let borehole = step.add(format!(
+            "IFCBOREHOLE({},$,{},{},{},{},{},{})",
+            guid,
+            optional_string(name.clone()),
+            optional_string(parsed(&investigation.method.token).map(|method| method.to_string())),
+            optional_string(method),
+            placement
+                .map(|id| format!("#{}", id))
+                .unwrap_or_else(|| "$".to_string()),
+            representation
+                .map(|id| format!("#{}", id))
+                .unwrap_or_else(|| "$".to_string()),
+            optional_string(name),
+        ));
+        boreholes.push(borehole);
+
+        step.property_set(
+            "InfraModel_Investigation",
+            &[borehole],
+            vec![
+                ("PointId", label(&investigation.coordinates.point_id)),
+                (
+                    "Date",
+                    parsed(&investigation.coordinates.date)
+                        .map(|date| Property::Date(date.format("%Y-%m-%d").to_string())),
+                ),
+                (
+                    "StartElevation",
+                    length(parsed(&investigation.coordinates.start_elevation)),
+                ),
+                ("TotalDepth", length(investigation.total_depth)),
+                ("SoundedDepth", length(investigation.sounded_depth)),
+                (
+                    "CoordinateSystem",
+                    label(&investigation.spatial.coordinate_system),
+                ),
+                (
+                    "ElevationSystem",
+                    label(&investigation.spatial.elevation_system),
+                ),
+                ("Owner", label(&investigation.organisations.owner_name)),
+                (
+                    "Investigator",
+                    label(&investigation.organisations.investigator_name),
+                ),
+                ("WorkId", label(&investigation.work.id)),
+                ("WorkName", label(&investigation.work.name)),
+            ],
+        );
+
+        let method = &investigation.method;
+        step.property_set(
+            "InfraModel_Method",
+            &[borehole],
+            vec![
+                ("Method", label(&method.token)),
+                (
+                    "MethodName",
+                    parsed(&method.token).map(|method| Property::Text(method.to_string())),
+                ),
+                ("Category", parsed(&method.category).map(Property::Integer)),
+                ("Id", label(&method.id)),
+                ("Standard", label(&method.standard)),
+                ("Sampler", label(&method.sampler)),
+                ("Specifier", label(&method.specifier)),
+            ],
+        );
+
+        step.property_set(
+            "InfraModel_Termination",
+            &[borehole],
+            vec![
+                ("Termination", label(&investigation.termination.token)),
+                (
+                    "TerminationName",
+                    parsed(&investigation.termination.token)
+                        .map(|termination| Property::Text(termination.to_string())),
+                ),
+            ],
+        );
+
+        let record = &investigation.record;
+        step.property_set(
+            "InfraModel_Record",
+            &[borehole],
+            vec![
+                ("Number", parsed(&record.number).map(Property::Integer)),
+                ("Driller", label(&record.driller)),
+                ("Inspector", label(&record.inspector)),
+                ("Processor", label(&record.processor)),
+                ("Digitized", label(&record.digitalized)),
+                ("Condition", label(&record.condition)),
+            ],
+        );
+
+        let strata: Vec<usize> = investigation
+            .soil_layers
+            .iter()
+            .map(|layer| stratum(&mut step, layer, placement, body_context))
+            .collect();
+        if !strata.is_empty() {
+            let guid = step.guid();
+            step.add(format!(
+                "IFCRELAGGREGATES({},$,$,$,#{},({}))",
+                guid,
+                borehole,
+                references(&strata)
+            ));
+        }
+    }
+
+    if !boreholes.is_empty() {
+        let guid = step.guid();
+        step.add(format!(
+            "IFCRELCONTAINEDINSPATIALSTRUCTURE({},$,$,$,({}),#{})",
+            guid,
+            references(&boreholes),
+            site
+        ));
+    }
+
+    let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let mut output = String::new();
+    output.push_str("ISO-10303-21;\nHEADER;\n");
+    output.push_str("FILE_DESCRIPTION(('ViewDefinition [ReferenceView]'),'2;1');\n");
+    output.push_str(&format!(
+        "FILE_NAME('',{},(''),(''),'inframodel','inframodel','');\n",
+        string(&timestamp)
+    ));
+    output.push_str("FILE_SCHEMA(('IFC4X3_ADD2'));\nENDSEC;\nDATA;\n");
+    for (index, entity) in step.entities.iter().enumerate() {
+        output.push_str(&format!("#{}={};\n", index + 1, entity));
+    }
+    output.push_str("ENDSEC;\nEND-ISO-10303-21;\n");
+
+    Ok(output)
+}
+
+// Soil layer as a cylinder from its top depth downwards
+fn stratum(
+    step: &mut StepWriter,
+    layer: &SoilLayer,
+    borehole_placement: Option<usize>,
+    body_context: usize,
+) -> usize {
+    // Millimetres like the points, the f32 difference would carry noise
+    let thickness =
+        ((decimal(layer.bottom_depth) - decimal(layer.top_depth)) * 1000.0).round() / 1000.0;
+
+    let placement = borehole_placement
+        .map(|placement| step.placement(Some(placement), [0.0, 0.0, -(layer.top_depth as f64)]));
+    let representation = if placement.is_some() && thickness > 0.0 {
+        let profile = step.add(format!(
+            "IFCCIRCLEPROFILEDEF(.AREA.,$,$,{})",
+            real(STRATUM_RADIUS)
+        ));
+        let origin = step.point([0.0, 0.0, 0.0]);
+        let position = step.add(format!("IFCAXIS2PLACEMENT3D(#{},$,$)", origin));
+        let direction = step.add("IFCDIRECTION((0.,0.,-1.))".to_string());
+        let solid = step.add(format!(
+            "IFCEXTRUDEDAREASOLID(#{},#{},#{},{})",
+            profile,
+            position,
+            direction,
+            real(thickness)
+        ));
+        let shape = step.add(format!(
+            "IFCSHAPEREPRESENTATION(#{},'Body','SweptSolid',(#{}))",
+            body_context, solid
+        ));
+        Some(step.add(format!("IFCPRODUCTDEFINITIONSHAPE($,$,(#{}))", shape)))
+    } else {
+        None
+    };
+
+    let guid = step.guid();
+    let id = step.add(format!(
+        "IFCGEOTECHNICALSTRATUM({},$,{},{},$,{},{},$,.SOLID.)",
+        guid,
+        string(&layer.soil_type),
+        string(&format!("{:.2}-{:.2}", layer.top_depth, layer.bottom_depth)),
+        placement
+            .map(|id| format!("#{}", id))
+            .unwrap_or_else(|| "$".to_string()),
+        representation
+            .map(|id| format!("#{}", id))
+            .unwrap_or_else(|| "$".to_string()),
+    ));
+
+    step.property_set(
+        "InfraModel_SoilLayer",
+        &[id],
+        vec![
+            ("SoilType", Some(Property::Label(layer.soil_type.clone()))),
+            ("TopDepth", length(Some(layer.top_depth))),
+            ("BottomDepth", length(Some(layer.bottom_depth))),
+            ("Thickness", length(Some(layer.thickness))),
+            ("TopElevation", length(layer.top_elevation)),
+            ("BottomElevation", length(layer.bottom_elevation)),
+            ("Source", Some(Property::Text(layer.source.to_string()))),
+        ],
+    );
+
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::collections::HashMap;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ GK25 N2000
+TT PA 1 P1
+XY 6672069.192 25496669.192 12.50 01012020 P1
+0.4 100 10 Sa
+1.2 100 20 Sa
+1.9 55 30 SaSi
+-1 KI
+TT PA 1 P2
+XY 6672080.0 25496680.0 12.40 01012020 P2
+1.0 100 10 Mr
+-1 KI
+";
+
+    #[test]
+    fn ifc_entities() {
+        let file = InfraFile::parse_str(INFRA);
+
+        let ifc = file.to_ifc(None).unwrap();
+        assert!(ifc.contains("FILE_SCHEMA(('IFC4X3_ADD2'));"));
+
+        let entities: HashMap<usize, &str> = ifc
+            .lines()
+            .filter_map(|line| line.strip_prefix('#')?.strip_suffix(';')?.split_once('='))
+            .map(|(id, entity)| (id.parse().unwrap(), entity))
+            .collect();
+        let named = |name: &str| -> Vec<&str> {
+            let mut found: Vec<(usize, &str)> = entities
+                .iter()
+                .filter(|(_, entity)| entity.starts_with(&format!("{}(", name)))
+                .map(|(id, entity)| (*id, *entity))
+                .collect();
+            found.sort();
+            found.into_iter().map(|(_, entity)| entity).collect()
+        };
+        let reference = |entity: &str, index: usize| -> &str {
+            let (_, arguments) = entity.split_once('(').unwrap();
+            let argument = arguments.split(',').nth(index).unwrap();
+            let id = argument.trim_matches(['#', ')']).parse::<usize>().unwrap();
+            entities[&id]
+        };
+
+        // Map conversion offsets are the local origin, easting first
+        let conversion = named("IFCMAPCONVERSION");
+        assert_eq!(conversion.len(), 1);
+        assert!(conversion[0].ends_with(",25496600.,6672000.,0.,1.,0.,1.,$,$)"));
+        assert!(named("IFCPROJECTEDCRS")[0].starts_with("IFCPROJECTEDCRS('EPSG:3879'"));
+
+        let boreholes = named("IFCBOREHOLE");
+        assert_eq!(boreholes.len(), 2);
+
+        // Borehole placements relative to the origin keep the millimetres
+        let placement = reference(boreholes[0], 5);
+        let axis = reference(placement, 1);
+        assert_eq!(
+            reference(axis, 0),
+            "IFCCARTESIANPOINT((69.192,69.192,12.5))"
+        );
+
+        let strata = named("IFCGEOTECHNICALSTRATUM");
+        assert_eq!(strata.len(), 3);
+        let labels: Vec<&str> = strata
+            .iter()
+            .map(|stratum| stratum.split(',').nth(3).unwrap())
+            .collect();
+        assert_eq!(labels, vec!["'0.00-1.20'", "'1.20-1.90'", "'0.00-1.00'"]);
+
+        let depths: Vec<&str> = named("IFCEXTRUDEDAREASOLID")
+            .iter()
+            .map(|solid| solid.rsplit(',').next().unwrap())
+            .collect();
+        assert_eq!(depths, vec!["1.2)", "0.7)", "1.)"]);
+
+        // Strata hang from their top depth below the borehole
+        let placement = reference(strata[1], 5);
+        let axis = reference(placement, 1);
+        assert_eq!(reference(axis, 0), "IFCCARTESIANPOINT((0.,0.,-1.2))");
+    }
+}
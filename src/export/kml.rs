@@ -0,0 +1,490 @@
+use crate::export::{observation_date, parsed, position, token, write, XmlWriter};
+use crate::{
+    CoordinateSystem, InfraFile, Investigation, InvestigationCollection, MethodToken,
+    ObservationValues, Transformer,
+};
+
+use crate::infra_file::write::InfraToken;
+
+use quick_xml::escape::escape;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+const KML_NAMESPACE: &str = "http://www.opengis.net/kml/2.2";
+const ICON: &str = "http://maps.google.com/mapfiles/kml/shapes/placemark_circle.png";
+
+// Grouping of the placemarks into KML folders
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KmlFolders {
+    #[default]
+    Work,
+    Program,
+}
+
+impl InvestigationCollection {
+    // Placemarks in WGS84, one folder per work (TY) or program (GR)
+    pub fn to_kml(&self, folders: KmlFolders) -> Result<String, String> {
+        self.to_kml_with(folders, &Transformer::default())
+    }
+
+    pub fn to_kml_with(
+        &self,
+        folders: KmlFolders,
+        transformer: &Transformer,
+    ) -> Result<String, String> {
+        kml(&self.investigations, folders, transformer)
+    }
+
+    // Zipped KML with the document as doc.kml
+    pub fn to_kmz(&self, folders: KmlFolders) -> Result<Vec<u8>, String> {
+        kmz(&self.to_kml(folders)?)
+    }
+
+    pub fn write_kml(&self, file_path: &str, folders: KmlFolders) -> Result<(), String> {
+        write(file_path, self.to_kml(folders)?.as_bytes())
+    }
+
+    pub fn write_kmz(&self, file_path: &str, folders: KmlFolders) -> Result<(), String> {
+        write(file_path, &self.to_kmz(folders)?)
+    }
+}
+
+impl InfraFile {
+    pub fn to_kml(&self, folders: KmlFolders) -> Result<String, String> {
+        kml(&self.investigations, folders, &Transformer::default())
+    }
+
+    pub fn to_kmz(&self, folders: KmlFolders) -> Result<Vec<u8>, String> {
+        kmz(&self.to_kml(folders)?)
+    }
+
+    pub fn write_kml(&self, file_path: &str, folders: KmlFolders) -> Result<(), String> {
+        write(file_path, self.to_kml(folders)?.as_bytes())
+    }
+
+    pub fn write_kmz(&self, file_path: &str, folders: KmlFolders) -> Result<(), String> {
+        write(file_path, &self.to_kmz(folders)?)
+    }
+}
+
+fn kmz(kml: &str) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("doc.kml", options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(kml.as_bytes()).map_err(|e| e.to_string())?;
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
+
+// KML colours are aabbggrr, grouped by the kind of investigation
+fn color(method: Option<MethodToken>) -> &'static str {
+    use MethodToken::*;
+
+    match method {
+        Some(PA | PI | PT | TR) => "ff00d7ff",
+        Some(LY | HE | HK | HP) => "ff0000e6",
+        Some(PR | CP | CU) => "ff008cff",
+        Some(SI) => "ff00b400",
+        Some(NO | NE | KE | KR | KO) => "ff2a5a8b",
+        Some(PO | MW | VP | VO | VK | VPK | HV | HU) => "ffe68c00",
+        Some(PS | PM) => "ffb4308c",
+        Some(None) | Option::None => "ffffffff",
+    }
+}
+
+fn style_id(method: Option<MethodToken>) -> String {
+    match method {
+        Some(method) if method != MethodToken::None => {
+            format!("method_{}", method.infra_token())
+        }
+        _ => "method_unknown".to_string(),
+    }
+}
+
+fn folder_name(investigation: &Investigation, folders: KmlFolders) -> Option<String> {
+    match folders {
+        KmlFolders::Work => {
+            let parts: Vec<String> = [
+                token(&investigation.work.id),
+                token(&investigation.work.name),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            (!parts.is_empty()).then(|| parts.join(" "))
+        }
+        KmlFolders::Program => token(&investigation.program.name),
+    }
+}
+
+fn kml(
+    investigations: &[Investigation],
+    folders: KmlFolders,
+    transformer: &Transformer,
+) -> Result<String, String> {
+    let target = CoordinateSystem::WGS84;
+
+    // Investigations without a position or a known transformation to WGS84
+    // are left out
+    let mut grouped: BTreeMap<Option<String>, Vec<(&Investigation, [f64; 2])>> = BTreeMap::new();
+    for investigation in investigations {
+        if let Some(position) = position(investigation, Some(&target), transformer) {
+            grouped
+                .entry(folder_name(investigation, folders))
+                .or_default()
+                .push((investigation, position));
+        }
+    }
+
+    let mut methods: Vec<Option<MethodToken>> = investigations
+        .iter()
+        .map(|investigation| parsed(&investigation.method.token))
+        .collect();
+    methods.sort_by_key(|method| style_id(*method));
+    methods.dedup_by_key(|method| style_id(*method));
+
+    let mut xml = XmlWriter::default();
+    xml.output
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.open("kml", &[("xmlns", KML_NAMESPACE)]);
+    xml.open("Document", &[]);
+    xml.text("name", &[], "InfraModel investigations");
+
+    for method in &methods {
+        xml.open("Style", &[("id", &style_id(*method))]);
+        xml.open("IconStyle", &[]);
+        xml.text("color", &[], color(*method));
+        xml.text("scale", &[], "0.8");
+        xml.open("Icon", &[]);
+        xml.text("href", &[], ICON);
+        xml.close();
+        xml.close();
+        xml.open("LabelStyle", &[]);
+        xml.text("scale", &[], "0.7");
+        xml.close();
+        xml.close();
+    }
+
+    // Named folders in order, investigations without a work or program last
+    let mut groups: Vec<_> = grouped.into_iter().collect();
+    let unnamed = groups.iter().take_while(|(name, _)| name.is_none()).count();
+    groups.rotate_left(unnamed);
+
+    for (name, members) in groups {
+        xml.open("Folder", &[]);
+        let name = name.unwrap_or_else(|| match folders {
+            KmlFolders::Work => "No work".to_string(),
+            KmlFolders::Program => "No program".to_string(),
+        });
+        xml.text("name", &[], &name);
+
+        for (investigation, [latitude, longitude]) in members {
+            let method = parsed(&investigation.method.token);
+            xml.open("Placemark", &[]);
+            if let Some(point_id) = token(&investigation.coordinates.point_id) {
+                xml.text("name", &[], &point_id);
+            }
+            xml.text("description", &[], &balloon(investigation));
+            xml.text("styleUrl", &[], &format!("#{}", style_id(method)));
+            xml.open("Point", &[]);
+            xml.text(
+                "coordinates",
+                &[],
+                &format!("{:.7},{:.7}", longitude, latitude),
+            );
+            xml.close();
+            xml.close();
+        }
+
+        xml.close();
+    }
+
+    xml.close();
+    xml.close();
+
+    Ok(xml.output)
+}
+
+// HTML table shown in the placemark balloon
+fn balloon(investigation: &Investigation) -> String {
+    let mut rows: Vec<(String, String)> = Vec::new();
+
+    if let Some(method) = parsed(&investigation.method.token) {
+        rows.push(("Method".to_string(), method.to_string()));
+    }
+    if let Some(date) = parsed(&investigation.coordinates.date) {
+        rows.push(("Date".to_string(), date.format("%Y-%m-%d").to_string()));
+    }
+    if let Some(elevation) = parsed(&investigation.coordinates.start_elevation) {
+        let system = token(&investigation.spatial.elevation_system)
+            .map(|system| format!(" ({})", system))
+            .unwrap_or_default();
+        rows.push((
+            "Start elevation".to_string(),
+            format!("{:.2}{}", elevation, system),
+        ));
+    }
+    if let Some(depth) = investigation.total_depth {
+        rows.push(("Depth".to_string(), format!("{:.2} m", depth)));
+    }
+    if let Some(termination) = parsed(&investigation.termination.token) {
+        rows.push((
+            "Termination".to_string(),
+            format!(
+                "{} {}",
+                token(&investigation.termination.token).unwrap_or_default(),
+                termination
+            ),
+        ));
+    }
+    for layer in &investigation.soil_layers {
+        rows.push((
+            format!("{:.2}-{:.2} m", layer.top_depth, layer.bottom_depth),
+            layer.soil_type.clone(),
+        ));
+    }
+
+    for water in investigation.water_observations() {
+        let kind = parsed(&water.token)
+            .map(|token| token.to_string())
+            .unwrap_or_else(|| "Water".to_string());
+        let depth = parsed(&water.depth)
+            .map(|depth| format!("{:.2} m", depth))
+            .unwrap_or_default();
+        rows.push((kind, depth));
+    }
+
+    // Standpipe and well readings as water level elevations
+    for observation in &investigation.observations {
+        if !matches!(
+            observation.values,
+            ObservationValues::VP { .. }
+                | ObservationValues::VO { .. }
+                | ObservationValues::VK { .. }
+                | ObservationValues::VPK { .. }
+        ) {
+            continue;
+        }
+        if let Some(elevation) = observation.values.get_parsed_elevation() {
+            let date = observation_date(&observation.values)
+                .map(|date| date.to_string())
+                .unwrap_or_default();
+            rows.push((
+                format!("Water level {}", date).trim_end().to_string(),
+                format!("{:.2}", elevation),
+            ));
+        }
+    }
+
+    if let Some(name) = investigation
+        .file_info
+        .path
+        .as_deref()
+        .and_then(|path| Path::new(path).file_name())
+    {
+        rows.push(("File".to_string(), name.to_string_lossy().to_string()));
+    }
+
+    let mut html = String::from("<table>");
+    for (label, value) in rows {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td></tr>",
+            escape(label.as_str()),
+            escape(value.as_str())
+        ));
+    }
+    html.push_str("</table>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    use std::io::Read;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TY W1 Work_one
+GR G1 01012020 Author
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+1.0 100 10 Sa
+VS 1.4 VO
+2.0 100 20 SaSi
+-1 KI
+TY W2 Work_two
+GR G1 01012020 Author
+TT SI 1 S1
+XY 6672010.0 385010.0 12.40 01012020 S1
+0.5 12.5 Sa
+-1 KA
+TT PA 1 P2
+XY 6672020.0 385020.0 12.30 01012020 P2
+1.0 100 10 Sa
+-1 KI
+TY W1 Work_one
+TT PA 1 P3
+XY -999999 -999999 12.30 01012020 P3
+-1 KI
+";
+
+    struct Placemark {
+        name: String,
+        style: String,
+        description: String,
+        coordinates: String,
+    }
+
+    // Style ids and the placemarks of each folder
+    fn read(kml: &str) -> (Vec<String>, Vec<(String, Vec<Placemark>)>) {
+        let mut reader = Reader::from_str(kml);
+        let mut path: Vec<String> = Vec::new();
+        let mut styles = Vec::new();
+        let mut folders: Vec<(String, Vec<Placemark>)> = Vec::new();
+
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) => {
+                    let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+                    match name.as_str() {
+                        "Style" => {
+                            let id = element.try_get_attribute("id").unwrap().unwrap();
+                            styles.push(id.unescape_value().unwrap().to_string());
+                        }
+                        "Folder" => folders.push((String::new(), Vec::new())),
+                        "Placemark" => folders.last_mut().unwrap().1.push(Placemark {
+                            name: String::new(),
+                            style: String::new(),
+                            description: String::new(),
+                            coordinates: String::new(),
+                        }),
+                        _ => {}
+                    }
+                    path.push(name);
+                }
+                Event::End(_) => {
+                    path.pop();
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().unwrap().to_string();
+                    let parent = path.get(path.len().wrapping_sub(2)).map(String::as_str);
+                    let Some(folder) = folders.last_mut() else {
+                        continue;
+                    };
+                    match (parent, path.last().map(String::as_str)) {
+                        (Some("Folder"), Some("name")) => folder.0 = text,
+                        (Some("Placemark"), Some(field)) => {
+                            let placemark = folder.1.last_mut().unwrap();
+                            match field {
+                                "name" => placemark.name = text,
+                                "styleUrl" => placemark.style = text,
+                                "description" => placemark.description = text,
+                                _ => {}
+                            }
+                        }
+                        (Some("Point"), Some("coordinates")) => {
+                            folder.1.last_mut().unwrap().coordinates = text
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        (styles, folders)
+    }
+
+    fn names(folders: &[(String, Vec<Placemark>)]) -> Vec<(&str, Vec<&str>)> {
+        folders
+            .iter()
+            .map(|(name, placemarks)| {
+                (
+                    name.as_str(),
+                    placemarks.iter().map(|p| p.name.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kml_placemarks() {
+        let file = InfraFile::parse_str(INFRA);
+        let (styles, folders) = read(&file.to_kml(KmlFolders::Work).unwrap());
+
+        // One style per method, each placemark refers to its own
+        assert_eq!(styles, vec!["method_PA", "method_SI"]);
+
+        // Named folders first, P3 has no known position
+        assert_eq!(
+            names(&folders),
+            vec![
+                ("W1 Work_one", vec!["P1"]),
+                ("W2 Work_two", vec!["S1"]),
+                ("No work", vec!["P2"]),
+            ]
+        );
+        let p1 = &folders[0].1[0];
+        assert_eq!(p1.style, "#method_PA");
+        assert_eq!(folders[1].1[0].style, "#method_SI");
+
+        // Longitude first
+        let (latitude, longitude) = Transformer::default()
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::WGS84,
+                6672000.0,
+                385000.0,
+            )
+            .unwrap();
+        assert!(longitude < latitude);
+        assert_eq!(p1.coordinates, format!("{:.7},{:.7}", longitude, latitude));
+
+        assert_eq!(
+            p1.description,
+            "<table>\
+             <tr><td>Method</td><td>Weight sounding test</td></tr>\
+             <tr><td>Date</td><td>2020-01-01</td></tr>\
+             <tr><td>Start elevation</td><td>12.50 (N2000)</td></tr>\
+             <tr><td>Depth</td><td>2.00 m</td></tr>\
+             <tr><td>Termination</td><td>KI Estimated rock or boulder</td></tr>\
+             <tr><td>0.00-1.00 m</td><td>Sa</td></tr>\
+             <tr><td>1.00-2.00 m</td><td>SaSi</td></tr>\
+             <tr><td>Perched water</td><td>1.40 m</td></tr>\
+             </table>"
+        );
+
+        let (_, folders) = read(&file.to_kml(KmlFolders::Program).unwrap());
+        assert_eq!(
+            names(&folders),
+            vec![("G1", vec!["P1", "S1"]), ("No program", vec!["P2"])]
+        );
+    }
+
+    #[test]
+    fn kmz_archive() {
+        let file = InfraFile::parse_str(INFRA);
+        let kmz = file.to_kmz(KmlFolders::Work).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(kmz)).unwrap();
+        assert_eq!(archive.len(), 1);
+        let mut kml = String::new();
+        archive
+            .by_name("doc.kml")
+            .unwrap()
+            .read_to_string(&mut kml)
+            .unwrap();
+        assert_eq!(kml, file.to_kml(KmlFolders::Work).unwrap());
+    }
+}
@@ -1,11 +1,17 @@
+pub(crate) mod coordinate_analysis;
 pub(crate) mod file_info;
 pub(crate) mod format;
 pub(crate) mod parse;
 pub(crate) mod spatial;
+pub(crate) mod write;
 
-use crate::{FileInfo, Format, HasInvestigations, Investigation, InvestigationAggregator, Spatial};
+use crate::{
+    ElevationSystem, FileInfo, Format, HasInvestigations, Investigation, InvestigationAggregator,
+    ParseResult, Spatial, Transformer,
+};
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfraFile {
     pub file_info: FileInfo,
     pub format: Format,
@@ -17,6 +23,26 @@ impl InfraFile {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn convert_elevations(&mut self, target: ElevationSystem) -> Result<(), String> {
+        self.convert_elevations_with(target, &Transformer::default())
+    }
+
+    pub fn convert_elevations_with(
+        &mut self,
+        target: ElevationSystem,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        let mut investigations = self.investigations.clone();
+
+        for investigation in &mut investigations {
+            investigation.convert_elevations_with(target.clone(), transformer)?;
+        }
+
+        self.investigations = investigations;
+        self.spatial.elevation_system = ParseResult::Parsed(target);
+        Ok(())
+    }
 }
 
 impl HasInvestigations for InfraFile {
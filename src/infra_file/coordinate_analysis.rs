@@ -0,0 +1,278 @@
+use crate::{CoordinateSystem, InfraFile, ParseResult};
+
+use std::cmp::Reverse;
+
+// Northing range covering Finland in all projected systems
+const NORTHING_RANGE: (f64, f64) = (6_500_000.0, 7_900_000.0);
+
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoordinateAnalysis {
+    pub declared: ParseResult<CoordinateSystem>,
+    // Most likely system based on the coordinate magnitudes
+    pub detected: Option<CoordinateSystem>,
+    // X and Y are written in the wrong order (easting first)
+    pub swapped: bool,
+    // Coordinates fit the declared system
+    pub declared_plausible: bool,
+    // Number of investigations with usable coordinates
+    pub samples: usize,
+}
+
+impl CoordinateAnalysis {
+    pub fn needs_correction(&self) -> bool {
+        self.swapped || (!self.declared_plausible && self.detected.is_some())
+    }
+}
+
+impl CoordinateSystem {
+    pub fn detect(x: f64, y: f64) -> Option<CoordinateSystem> {
+        if (59.0..71.0).contains(&x) && (19.0..32.0).contains(&y) {
+            return Some(CoordinateSystem::WGS84);
+        }
+
+        if !(NORTHING_RANGE.0..NORTHING_RANGE.1).contains(&x) || y < 0.0 {
+            return None;
+        }
+
+        let zone = (y / 1_000_000.0).floor() as u32;
+        let system = match zone {
+            0 => CoordinateSystem::TM35,
+            1 => CoordinateSystem::KKJ1,
+            2 => CoordinateSystem::KKJ2,
+            3 => CoordinateSystem::YKJ,
+            4 => CoordinateSystem::KKJ4,
+            5 => CoordinateSystem::KKJ5,
+            19 => CoordinateSystem::GK19,
+            20 => CoordinateSystem::GK20,
+            21 => CoordinateSystem::GK21,
+            22 => CoordinateSystem::GK22,
+            23 => CoordinateSystem::GK23,
+            24 => CoordinateSystem::GK24,
+            25 => CoordinateSystem::GK25,
+            26 => CoordinateSystem::GK26,
+            27 => CoordinateSystem::GK27,
+            28 => CoordinateSystem::GK28,
+            29 => CoordinateSystem::GK29,
+            30 => CoordinateSystem::GK30,
+            31 => CoordinateSystem::GK31,
+            _ => return None,
+        };
+
+        Some(system)
+    }
+
+    pub fn is_plausible(&self, x: f64, y: f64) -> bool {
+        let projected = |easting: (f64, f64)| {
+            (NORTHING_RANGE.0..NORTHING_RANGE.1).contains(&x) && (easting.0..easting.1).contains(&y)
+        };
+        let zone = |prefix: f64| projected((prefix * 1_000_000.0, (prefix + 1.0) * 1_000_000.0));
+
+        match self {
+            CoordinateSystem::WGS84 => (59.0..71.0).contains(&x) && (19.0..32.0).contains(&y),
+            CoordinateSystem::TM34
+            | CoordinateSystem::TM35
+            | CoordinateSystem::TM36
+            | CoordinateSystem::KKJ0 => zone(0.0),
+            CoordinateSystem::KKJ1 => zone(1.0),
+            CoordinateSystem::KKJ2 => zone(2.0),
+            CoordinateSystem::KKJ3 | CoordinateSystem::YKJ => zone(3.0),
+            CoordinateSystem::KKJ4 => zone(4.0),
+            CoordinateSystem::KKJ5 => zone(5.0),
+            CoordinateSystem::GK19 => zone(19.0),
+            CoordinateSystem::GK20 => zone(20.0),
+            CoordinateSystem::GK21 => zone(21.0),
+            CoordinateSystem::GK22 => zone(22.0),
+            CoordinateSystem::GK23 => zone(23.0),
+            CoordinateSystem::GK24 => zone(24.0),
+            CoordinateSystem::GK25 => zone(25.0),
+            CoordinateSystem::GK26 => zone(26.0),
+            CoordinateSystem::GK27 => zone(27.0),
+            CoordinateSystem::GK28 => zone(28.0),
+            CoordinateSystem::GK29 => zone(29.0),
+            CoordinateSystem::GK30 => zone(30.0),
+            CoordinateSystem::GK31 => zone(31.0),
+            // Local systems have no known extent
+            CoordinateSystem::HKI | CoordinateSystem::VANTAA | CoordinateSystem::ESPOO => true,
+            CoordinateSystem::Unknown => false,
+        }
+    }
+}
+
+impl InfraFile {
+    pub fn analyse_coordinates(&self) -> CoordinateAnalysis {
+        let points: Vec<(f64, f64)> = self
+            .investigations
+            .iter()
+            .filter_map(|inv| inv.coordinates.known_xy())
+            .collect();
+
+        let mut analysis = CoordinateAnalysis {
+            declared: self.spatial.coordinate_system.clone(),
+            samples: points.len(),
+            ..Default::default()
+        };
+
+        if points.is_empty() {
+            return analysis;
+        }
+
+        // Ties go to the declared system, then to the system seen first
+        let declared = match &self.spatial.coordinate_system {
+            ParseResult::Parsed(system) => Some(system),
+            _ => None,
+        };
+        let majority = |points: &[(f64, f64)]| -> Option<(CoordinateSystem, usize)> {
+            let mut votes: Vec<(CoordinateSystem, usize)> = Vec::new();
+            for (x, y) in points {
+                if let Some(system) = CoordinateSystem::detect(*x, *y) {
+                    match votes.iter_mut().find(|(voted, _)| *voted == system) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((system, 1)),
+                    }
+                }
+            }
+            votes
+                .into_iter()
+                .enumerate()
+                .max_by_key(|(index, (system, count))| {
+                    (*count, declared == Some(system), Reverse(*index))
+                })
+                .map(|(_, vote)| vote)
+        };
+
+        let swapped_points: Vec<(f64, f64)> = points.iter().map(|(x, y)| (*y, *x)).collect();
+        let plausible_count = |system: &CoordinateSystem, points: &[(f64, f64)]| {
+            points
+                .iter()
+                .filter(|(x, y)| system.is_plausible(*x, *y))
+                .count()
+        };
+
+        let (detected, swapped) = match (majority(&points), majority(&swapped_points)) {
+            (Some((system, count)), Some((_, swapped_count))) if count >= swapped_count => {
+                (Some(system), false)
+            }
+            (_, Some((system, _))) => (Some(system), true),
+            (Some((system, _)), None) => (Some(system), false),
+            (None, None) => (None, false),
+        };
+
+        analysis.declared_plausible = match &self.spatial.coordinate_system {
+            ParseResult::Parsed(system) => {
+                let oriented = if swapped { &swapped_points } else { &points };
+                plausible_count(system, oriented) * 2 > points.len()
+            }
+            _ => false,
+        };
+        analysis.detected = detected;
+        analysis.swapped = swapped;
+
+        analysis
+    }
+
+    pub fn apply_coordinate_analysis(&mut self, analysis: &CoordinateAnalysis) {
+        if analysis.swapped {
+            for investigation in &mut self.investigations {
+                let coordinates = &mut investigation.coordinates;
+                std::mem::swap(&mut coordinates.x, &mut coordinates.y);
+            }
+        }
+
+        if !analysis.declared_plausible {
+            if let Some(system) = &analysis.detected {
+                self.spatial.coordinate_system = ParseResult::Parsed(system.clone());
+            }
+        }
+
+        for investigation in &mut self.investigations {
+            investigation.spatial = self.spatial.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn file(system: &str, points: &[(f64, f64)]) -> InfraFile {
+        let spatial = Spatial {
+            coordinate_system: ParseResult::parse(system),
+            elevation_system: ParseResult::None,
+        };
+        let investigations = points
+            .iter()
+            .map(|(x, y)| Investigation {
+                spatial: spatial.clone(),
+                coordinates: Coordinates {
+                    x: ParseResult::Parsed(*x),
+                    y: ParseResult::Parsed(*y),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+
+        InfraFile {
+            spatial,
+            investigations,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detection() {
+        assert_eq!(
+            CoordinateSystem::detect(6672000.0, 385000.0),
+            Some(CoordinateSystem::TM35)
+        );
+        assert_eq!(
+            CoordinateSystem::detect(6672000.0, 25496669.0),
+            Some(CoordinateSystem::GK25)
+        );
+        assert_eq!(
+            CoordinateSystem::detect(60.17, 24.94),
+            Some(CoordinateSystem::WGS84)
+        );
+        assert_eq!(CoordinateSystem::detect(385000.0, 6672000.0), None);
+
+        // Easting written first
+        let analysis =
+            file("TM35", &[(385000.0, 6672000.0), (385100.0, 6672100.0)]).analyse_coordinates();
+        assert!(analysis.swapped && analysis.declared_plausible);
+        assert_eq!(analysis.detected, Some(CoordinateSystem::TM35));
+        assert_eq!(analysis.samples, 2);
+
+        let mut wrong = file("KKJ2", &[(6672000.0, 385000.0), (6672100.0, 385100.0)]);
+        let analysis = wrong.analyse_coordinates();
+        assert!(!analysis.swapped && !analysis.declared_plausible);
+        assert!(analysis.needs_correction());
+        wrong.apply_coordinate_analysis(&analysis);
+        assert_eq!(
+            wrong.investigations[0].spatial.coordinate_system,
+            ParseResult::Parsed(CoordinateSystem::TM35)
+        );
+    }
+
+    #[test]
+    fn detection_ties() {
+        let points = [
+            (6672000.0, 25496000.0),
+            (6672000.0, 385000.0),
+            (6672100.0, 385100.0),
+            (6672100.0, 25496100.0),
+        ];
+
+        // Equal votes go to the declared system, otherwise to the first seen
+        for _ in 0..10 {
+            assert_eq!(
+                file("TM35", &points).analyse_coordinates().detected,
+                Some(CoordinateSystem::TM35)
+            );
+            assert_eq!(
+                file("-", &points).analyse_coordinates().detected,
+                Some(CoordinateSystem::GK25)
+            );
+        }
+    }
+}
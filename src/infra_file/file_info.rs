@@ -1,4 +1,5 @@
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileInfo {
     pub path: Option<String>,
     pub encoding: Option<String>,
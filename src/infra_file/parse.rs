@@ -1,7 +1,7 @@
 use crate::{
     ClassificationName, CoordinateSystem, Digitized, ElevationSystem, FileInfo, InfraFile,
     InitialBoreToken, Investigation, LabResult, MethodToken, Observation, ObservationValues,
-    ParseResult, Sampler, TerminationToken, TryParse,
+    ParseResult, Sampler, TerminationToken, TryParse, WaterObserved, WaterObservedToken,
 };
 
 use chardetng::EncodingDetector;
@@ -24,13 +24,25 @@ impl InfraFile {
         detector.feed(&buffer, true);
         let encoding = detector.guess(None, true);
         let (decoded, _, _) = encoding.decode(&buffer);
-        let lines: Vec<&str> = decoded.lines().collect();
 
-        let mut infra = InfraFile {
-            file_info: FileInfo {
+        Ok(Self::parse_content(
+            &decoded,
+            FileInfo {
                 path: Some(file_path.to_string()),
                 encoding: Some(encoding.name().to_string()),
             },
+        ))
+    }
+
+    pub fn parse_str(content: &str) -> InfraFile {
+        Self::parse_content(content, FileInfo::default())
+    }
+
+    fn parse_content(content: &str, file_info: FileInfo) -> InfraFile {
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut infra = InfraFile {
+            file_info,
             ..Default::default()
         };
 
@@ -67,6 +79,7 @@ impl InfraFile {
                     "TX" => Self::parse_tx(&mut inv, rest),
                     "HT" => Self::parse_ht(&mut inv, rest),
                     "EM" => Self::parse_em(&mut inv, rest),
+                    "VS" => Self::parse_vs(&mut inv, rest),
                     "LB" => Self::parse_lb(&mut inv, rest),
                     "RK" => Self::parse_rk(&mut inv, rest),
                     // TODO: Implement KK token parsing correctly
@@ -116,12 +129,13 @@ impl InfraFile {
         }
 
         Self::compute_properties(&mut infra);
-        Ok(infra)
+        infra
     }
 
     fn compute_properties(infra: &mut InfraFile) {
         for investigation in &mut infra.investigations {
             investigation.file_info = infra.file_info.clone();
+            investigation.format = infra.format.clone();
             investigation.spatial = infra.spatial.clone();
             investigation.compute_properties();
         }
@@ -188,8 +202,8 @@ impl InfraFile {
     }
 
     fn parse_xy(inv: &mut Investigation, params: &[&str]) {
-        inv.coordinates.x = Self::parse_value::<f32>(params, 0);
-        inv.coordinates.y = Self::parse_value::<f32>(params, 1);
+        inv.coordinates.x = Self::parse_value::<f64>(params, 0);
+        inv.coordinates.y = Self::parse_value::<f64>(params, 1);
         inv.coordinates.start_elevation = Self::parse_value::<f32>(params, 2);
         inv.coordinates.date = Self::parse_value::<NaiveDate>(params, 3);
         inv.coordinates.point_id = Self::parse_value::<String>(params, 4);
@@ -302,6 +316,23 @@ impl InfraFile {
         }
     }
 
+    fn parse_vs(inv: &mut Investigation, params: &[&str]) {
+        if let Some(last_obs) = inv.observations.last_mut() {
+            let mut depth = Self::parse_value::<f32>(params, 0);
+
+            if !matches!(depth, ParseResult::Parsed(_)) {
+                if let Some(obs_depth) = last_obs.values.get_parsed_depth() {
+                    depth = ParseResult::Parsed(obs_depth);
+                }
+            }
+
+            last_obs.water_observed = ParseResult::Parsed(WaterObserved {
+                depth,
+                token: Self::parse_value::<WaterObservedToken>(params, 1),
+            });
+        }
+    }
+
     fn parse_lb(inv: &mut Investigation, params: &[&str]) {
         // TODO: Implement common lab types
         let lab_other = LabResult::Other {
@@ -758,3 +789,56 @@ impl InfraFile {
         inv.observations.push(obs);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+1.0 100 10 Sa
+VS 1.4 VO
+2.0 100 20 Sa
+VS - VP
+3.0 100 30 Sa
+VS x VA
+-1 KI
+";
+
+    #[test]
+    fn water_observations() {
+        let file = InfraFile::parse_str(INFRA);
+
+        let investigation = &file.investigations[0];
+        let water: Vec<(ParseResult<f32>, ParseResult<WaterObservedToken>)> = investigation
+            .water_observations()
+            .into_iter()
+            .map(|water| (water.depth.clone(), water.token.clone()))
+            .collect();
+
+        // Missing and non-numeric depths fall back to the observation depth
+        assert_eq!(
+            water,
+            vec![
+                (
+                    ParseResult::Parsed(1.4),
+                    ParseResult::Parsed(WaterObservedToken::VO)
+                ),
+                (
+                    ParseResult::Parsed(2.0),
+                    ParseResult::Parsed(WaterObservedToken::VP)
+                ),
+                (
+                    ParseResult::Parsed(3.0),
+                    ParseResult::Parsed(WaterObservedToken::VA)
+                ),
+            ]
+        );
+
+        let first = investigation.first_water_observed().unwrap();
+        assert_eq!(first.depth, ParseResult::Parsed(1.4));
+        assert_eq!(first.token, ParseResult::Parsed(WaterObservedToken::VO));
+    }
+}
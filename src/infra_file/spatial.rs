@@ -3,12 +3,14 @@ use crate::{ParseResult, TryParse};
 use std::fmt;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spatial {
     pub coordinate_system: ParseResult<CoordinateSystem>,
     pub elevation_system: ParseResult<ElevationSystem>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CoordinateSystem {
     #[default]
     Unknown,
@@ -42,6 +44,7 @@ pub enum CoordinateSystem {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElevationSystem {
     #[default]
     Unknown,
@@ -52,8 +55,123 @@ pub enum ElevationSystem {
     LN,
 }
 
+impl Spatial {
+    pub fn epsg(&self) -> Option<String> {
+        let horizontal = match &self.coordinate_system {
+            ParseResult::Parsed(system) => system.epsg()?,
+            _ => return None,
+        };
+
+        match &self.elevation_system {
+            ParseResult::Parsed(system) => match system.epsg() {
+                Some(vertical) => Some(format!("EPSG:{}+{}", horizontal, vertical)),
+                None => Some(format!("EPSG:{}", horizontal)),
+            },
+            _ => Some(format!("EPSG:{}", horizontal)),
+        }
+    }
+}
+
+impl CoordinateSystem {
+    pub fn epsg(&self) -> Option<u32> {
+        match self {
+            CoordinateSystem::WGS84 => Some(4326),
+            CoordinateSystem::KKJ0 => Some(3386),
+            CoordinateSystem::KKJ1 => Some(2391),
+            CoordinateSystem::KKJ2 => Some(2392),
+            CoordinateSystem::KKJ3 | CoordinateSystem::YKJ => Some(2393),
+            CoordinateSystem::KKJ4 => Some(2394),
+            CoordinateSystem::KKJ5 => Some(3387),
+            CoordinateSystem::GK19 => Some(3873),
+            CoordinateSystem::GK20 => Some(3874),
+            CoordinateSystem::GK21 => Some(3875),
+            CoordinateSystem::GK22 => Some(3876),
+            CoordinateSystem::GK23 => Some(3877),
+            CoordinateSystem::GK24 => Some(3878),
+            CoordinateSystem::GK25 => Some(3879),
+            CoordinateSystem::GK26 => Some(3880),
+            CoordinateSystem::GK27 => Some(3881),
+            CoordinateSystem::GK28 => Some(3882),
+            CoordinateSystem::GK29 => Some(3883),
+            CoordinateSystem::GK30 => Some(3884),
+            CoordinateSystem::GK31 => Some(3885),
+            CoordinateSystem::TM34 => Some(25834),
+            CoordinateSystem::TM35 => Some(3067),
+            CoordinateSystem::TM36 => Some(25836),
+            CoordinateSystem::HKI
+            | CoordinateSystem::VANTAA
+            | CoordinateSystem::ESPOO
+            | CoordinateSystem::Unknown => None,
+        }
+    }
+
+    pub fn from_epsg(code: u32) -> Option<CoordinateSystem> {
+        match code {
+            4326 | 4258 => Some(CoordinateSystem::WGS84),
+            3386 => Some(CoordinateSystem::KKJ0),
+            2391 => Some(CoordinateSystem::KKJ1),
+            2392 => Some(CoordinateSystem::KKJ2),
+            2393 => Some(CoordinateSystem::YKJ),
+            2394 => Some(CoordinateSystem::KKJ4),
+            3387 => Some(CoordinateSystem::KKJ5),
+            // 3126-3138 (ETRS-GKnnFIN) have a plain 500 km false easting while
+            // InfraModel GK coordinates carry the zone number, so they are not
+            // accepted as GK zones
+            3873 => Some(CoordinateSystem::GK19),
+            3874 => Some(CoordinateSystem::GK20),
+            3875 => Some(CoordinateSystem::GK21),
+            3876 => Some(CoordinateSystem::GK22),
+            3877 => Some(CoordinateSystem::GK23),
+            3878 => Some(CoordinateSystem::GK24),
+            3879 => Some(CoordinateSystem::GK25),
+            3880 => Some(CoordinateSystem::GK26),
+            3881 => Some(CoordinateSystem::GK27),
+            3882 => Some(CoordinateSystem::GK28),
+            3883 => Some(CoordinateSystem::GK29),
+            3884 => Some(CoordinateSystem::GK30),
+            3885 => Some(CoordinateSystem::GK31),
+            25834 => Some(CoordinateSystem::TM34),
+            3067 | 25835 => Some(CoordinateSystem::TM35),
+            25836 => Some(CoordinateSystem::TM36),
+            _ => None,
+        }
+    }
+}
+
+impl ElevationSystem {
+    pub fn epsg(&self) -> Option<u32> {
+        match self {
+            ElevationSystem::N2000 => Some(3900),
+            ElevationSystem::N60 => Some(5717),
+            ElevationSystem::N43 => Some(8675),
+            _ => None,
+        }
+    }
+
+    pub fn from_epsg(code: u32) -> Option<ElevationSystem> {
+        match code {
+            3900 => Some(ElevationSystem::N2000),
+            5717 => Some(ElevationSystem::N60),
+            8675 => Some(ElevationSystem::N43),
+            _ => None,
+        }
+    }
+}
+
+fn parse_epsg(input: &str) -> Option<u32> {
+    input
+        .trim()
+        .to_uppercase()
+        .strip_prefix("EPSG:")
+        .and_then(|code| code.parse::<u32>().ok())
+}
+
 impl TryParse for CoordinateSystem {
     fn try_parse(input: &str) -> Result<Self, String> {
+        if let Some(code) = parse_epsg(input) {
+            return CoordinateSystem::from_epsg(code).ok_or_else(|| input.to_string());
+        }
+
         match input.trim().to_uppercase().as_str() {
             "WGS84" | "WGS" => Ok(CoordinateSystem::WGS84),
             "HKI" => Ok(CoordinateSystem::HKI),
@@ -79,9 +197,11 @@ impl TryParse for CoordinateSystem {
             "GK29" | "ETRSGK29" | "ETRS-GK29" => Ok(CoordinateSystem::GK29),
             "GK30" | "ETRSGK30" | "ETRS-GK30" => Ok(CoordinateSystem::GK30),
             "GK31" | "ETRSGK31" | "ETRS-GK31" => Ok(CoordinateSystem::GK31),
+            "TM34" | "ETRSTM34" | "ETRS-TM34" => Ok(CoordinateSystem::TM34),
             "TM35" | "ETRSTM35" | "ETRS-TM35" | "TM35FIN" | "ETRSTM35FIN" | "ETRS-TM35FIN" => {
                 Ok(CoordinateSystem::TM35)
             }
+            "TM36" | "ETRSTM36" | "ETRS-TM36" => Ok(CoordinateSystem::TM36),
             _ => Err(input.to_string()),
         }
     }
@@ -89,6 +209,10 @@ impl TryParse for CoordinateSystem {
 
 impl TryParse for ElevationSystem {
     fn try_parse(input: &str) -> Result<Self, String> {
+        if let Some(code) = parse_epsg(input) {
+            return ElevationSystem::from_epsg(code).ok_or_else(|| input.to_string());
+        }
+
         match input.trim().to_uppercase().as_str() {
             "N2000" => Ok(ElevationSystem::N2000),
             "N60" => Ok(ElevationSystem::N60),
@@ -0,0 +1,657 @@
+use crate::{
+    ClassificationName, CoordinateSystem, Digitized, ElevationSystem, InfraFile, InitialBoreToken,
+    Investigation, LabResult, MethodToken, Observation, ObservationValues, ParseResult, Sampler,
+    TerminationToken, WaterObservedToken,
+};
+
+use chrono::NaiveDate;
+use encoding_rs::{Encoding, UTF_8};
+
+use std::fs;
+
+// Value as written in an InfraModel file
+pub(crate) trait InfraToken {
+    fn infra_token(&self) -> String;
+}
+
+impl InfraToken for String {
+    fn infra_token(&self) -> String {
+        self.clone()
+    }
+}
+
+impl InfraToken for f32 {
+    fn infra_token(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl InfraToken for f64 {
+    fn infra_token(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl InfraToken for i32 {
+    fn infra_token(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl InfraToken for NaiveDate {
+    fn infra_token(&self) -> String {
+        self.format("%d%m%Y").to_string()
+    }
+}
+
+impl InfraToken for CoordinateSystem {
+    fn infra_token(&self) -> String {
+        match self {
+            CoordinateSystem::Unknown => "-".to_string(),
+            CoordinateSystem::WGS84
+            | CoordinateSystem::HKI
+            | CoordinateSystem::VANTAA
+            | CoordinateSystem::ESPOO
+            | CoordinateSystem::KKJ0
+            | CoordinateSystem::KKJ1
+            | CoordinateSystem::KKJ2
+            | CoordinateSystem::KKJ3
+            | CoordinateSystem::KKJ4
+            | CoordinateSystem::KKJ5
+            | CoordinateSystem::YKJ => self.to_string(),
+            // "ETRS-TM35" etc. without the prefix
+            _ => self.to_string().trim_start_matches("ETRS-").to_string(),
+        }
+    }
+}
+
+impl InfraToken for ElevationSystem {
+    fn infra_token(&self) -> String {
+        match self {
+            ElevationSystem::Unknown => "-".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl InfraToken for ClassificationName {
+    fn infra_token(&self) -> String {
+        match self {
+            ClassificationName::GEO => "GEO",
+            ClassificationName::ISO => "ISO",
+        }
+        .to_string()
+    }
+}
+
+impl InfraToken for Digitized {
+    fn infra_token(&self) -> String {
+        match self {
+            Digitized::Yes => "D",
+            Digitized::No => "-",
+        }
+        .to_string()
+    }
+}
+
+impl InfraToken for MethodToken {
+    fn infra_token(&self) -> String {
+        match self {
+            MethodToken::None => "-".to_string(),
+            _ => format!("{:?}", self),
+        }
+    }
+}
+
+impl InfraToken for Sampler {
+    fn infra_token(&self) -> String {
+        match self {
+            Sampler::K => "K",
+            Sampler::L => "L",
+            Sampler::PMK => "PMK",
+            Sampler::R => "R",
+            Sampler::ST50 => "ST50",
+            Sampler::ST60 => "ST60",
+            Sampler::Unknown => "-",
+        }
+        .to_string()
+    }
+}
+
+impl InfraToken for InitialBoreToken {
+    fn infra_token(&self) -> String {
+        match self {
+            InitialBoreToken::SI => "SI",
+            InitialBoreToken::LK => "LK",
+            InitialBoreToken::AP => "AP",
+            InitialBoreToken::LY => "LY",
+            InitialBoreToken::VA => "VA",
+            InitialBoreToken::JA => "JA",
+            InitialBoreToken::Unknown => "-",
+        }
+        .to_string()
+    }
+}
+
+impl InfraToken for TerminationToken {
+    fn infra_token(&self) -> String {
+        match self {
+            TerminationToken::TM => "TM",
+            TerminationToken::KI => "KI",
+            TerminationToken::KL => "KL",
+            TerminationToken::KA => "KA",
+            TerminationToken::KK => "KK",
+            TerminationToken::MS => "MS",
+            TerminationToken::KN => "KN",
+            TerminationToken::JA => "JA",
+            TerminationToken::Unknown => "-",
+        }
+        .to_string()
+    }
+}
+
+impl InfraToken for WaterObservedToken {
+    fn infra_token(&self) -> String {
+        match self {
+            WaterObservedToken::VP => "VP",
+            WaterObservedToken::VO => "VO",
+            WaterObservedToken::VA => "VA",
+            WaterObservedToken::Unknown => "-",
+        }
+        .to_string()
+    }
+}
+
+// Unparsed values are written back as they were read
+fn field<T: InfraToken>(value: &ParseResult<T>) -> String {
+    match value {
+        ParseResult::Parsed(value) => value.infra_token(),
+        ParseResult::Fallback(original) => original.clone(),
+        ParseResult::None => "-".to_string(),
+    }
+}
+
+// Token line with trailing empty fields dropped, None when all are empty
+fn row(token: &str, mut fields: Vec<String>) -> Option<String> {
+    while fields
+        .last()
+        .is_some_and(|value| value == "-" || value.is_empty())
+    {
+        fields.pop();
+    }
+
+    if fields.is_empty() {
+        None
+    } else if token.is_empty() {
+        Some(fields.join(" "))
+    } else {
+        Some(format!("{} {}", token, fields.join(" ")))
+    }
+}
+
+fn texts(lines: &mut Vec<String>, token: &str, values: &[ParseResult<String>]) {
+    lines.extend(
+        values
+            .iter()
+            .filter_map(|value| row(token, vec![field(value)])),
+    );
+}
+
+impl InfraFile {
+    pub fn to_infra_string(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        lines.extend(row(
+            "FO",
+            vec![
+                field(&self.format.version),
+                field(&self.format.used_software),
+                field(&self.format.software_version),
+            ],
+        ));
+        lines.extend(row(
+            "KJ",
+            vec![
+                field(&self.spatial.coordinate_system),
+                field(&self.spatial.elevation_system),
+            ],
+        ));
+
+        for investigation in &self.investigations {
+            investigation.write_lines(&mut lines);
+        }
+
+        let mut content = lines.join("\r\n");
+        content.push_str("\r\n");
+        content
+    }
+
+    // Written in the encoding the file was read with, UTF-8 otherwise
+    pub fn write_file(&self, file_path: &str) -> Result<(), String> {
+        let encoding = self
+            .file_info
+            .encoding
+            .as_ref()
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8);
+
+        let content = self.to_infra_string();
+        let (bytes, _, _) = encoding.encode(&content);
+
+        fs::write(file_path, bytes)
+            .map_err(|e| format!("Failed to write file '{}': {}", file_path, e))
+    }
+}
+
+impl Investigation {
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        let standpipe = &self.standpipe;
+
+        let header = [
+            row("OM", vec![field(&self.organisations.owner_name)]),
+            row("ML", vec![field(&self.classification.name)]),
+            row("OR", vec![field(&self.organisations.investigator_name)]),
+            row("TY", vec![field(&self.work.id), field(&self.work.name)]),
+            row(
+                "PK",
+                vec![
+                    field(&self.record.number),
+                    field(&self.record.driller),
+                    field(&self.record.inspector),
+                    field(&self.record.processor),
+                    field(&self.record.digitalized),
+                    field(&self.record.condition),
+                ],
+            ),
+            row(
+                "TT",
+                vec![
+                    field(&self.method.token),
+                    field(&self.method.category),
+                    field(&self.method.id),
+                    field(&self.method.standard),
+                    field(&self.method.sampler),
+                    field(&self.method.specifier),
+                ],
+            ),
+            row(
+                "LA",
+                vec![
+                    field(&self.equipment.number),
+                    field(&self.equipment.description),
+                    field(&self.equipment.cone_size),
+                ],
+            ),
+            row(
+                "XY",
+                vec![
+                    field(&self.coordinates.x),
+                    field(&self.coordinates.y),
+                    field(&self.coordinates.start_elevation),
+                    field(&self.coordinates.date),
+                    field(&self.coordinates.point_id),
+                ],
+            ),
+            row(
+                "LN",
+                vec![
+                    field(&self.line.name),
+                    field(&self.line.stake),
+                    field(&self.line.distance),
+                ],
+            ),
+            row(
+                "GR",
+                vec![
+                    field(&self.program.name),
+                    field(&self.program.date),
+                    field(&self.program.author),
+                ],
+            ),
+        ];
+        lines.extend(header.into_iter().flatten());
+
+        texts(lines, "GL", &self.program.guide);
+
+        let details = [
+            row(
+                "AT",
+                vec![
+                    field(&self.depthless_rock_sample.attribute),
+                    field(&self.depthless_rock_sample.value),
+                ],
+            ),
+            row(
+                "AL",
+                vec![
+                    field(&self.initial_borehole.depth),
+                    field(&self.initial_borehole.method),
+                    field(&self.initial_borehole.soil_type),
+                ],
+            ),
+            row(
+                "ZP",
+                vec![
+                    field(&standpipe.top_elevation),
+                    field(&standpipe.ground_elevation),
+                    field(&standpipe.protection_top_elevation),
+                    field(&standpipe.cover_elevation),
+                    field(&standpipe.sieve_bottom_elevation),
+                ],
+            ),
+            row(
+                "TP",
+                vec![
+                    field(&standpipe.upper_structure),
+                    field(&standpipe.sieve_length),
+                    field(&standpipe.sieve_type),
+                    field(&standpipe.diameter),
+                    field(&standpipe.material),
+                ],
+            ),
+            row(
+                "LP",
+                vec![
+                    field(&standpipe.measure_point),
+                    field(&standpipe.details),
+                    field(&standpipe.locked),
+                    field(&standpipe.lock_owner),
+                    field(&standpipe.installer),
+                ],
+            ),
+        ];
+        lines.extend(details.into_iter().flatten());
+
+        texts(lines, "HM", &self.notes);
+        texts(lines, "TX", &self.free_text);
+        texts(lines, "HT", &self.hidden_text);
+
+        for observation in &self.observations {
+            observation.write_lines(lines);
+        }
+
+        lines.push(row("-1", vec![field(&self.termination.token)]).unwrap_or("-1".to_string()));
+    }
+}
+
+impl Observation {
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        lines.extend(self.values.row());
+
+        if let ParseResult::Parsed(water) = &self.water_observed {
+            lines.extend(row("VS", vec![field(&water.depth), field(&water.token)]));
+        }
+
+        texts(lines, "HM", &self.notes);
+        texts(lines, "TX", &self.free_text);
+        texts(lines, "HT", &self.hidden_text);
+        texts(lines, "EM", &self.unofficial_soil_type);
+
+        if let ObservationValues::NO { lab_values, .. } | ObservationValues::NE { lab_values, .. } =
+            &self.values
+        {
+            for lab_value in lab_values {
+                let ParseResult::Parsed(lab_value) = lab_value else {
+                    continue;
+                };
+
+                lines.extend(match lab_value {
+                    LabResult::GrainSize {
+                        grain_mm,
+                        pass_percent,
+                    } => row("RK", vec![field(grain_mm), field(pass_percent)]),
+                    LabResult::WaterContent { water_content } => row(
+                        "LB",
+                        vec!["w".to_string(), field(water_content), "%".to_string()],
+                    ),
+                    LabResult::Other {
+                        attribute,
+                        result,
+                        unit,
+                    } => row("LB", vec![field(attribute), field(result), field(unit)]),
+                    LabResult::None => None,
+                });
+            }
+        }
+    }
+}
+
+impl ObservationValues {
+    // Data row in the column order of the method
+    fn row(&self) -> Option<String> {
+        let fields = match self {
+            ObservationValues::None => return None,
+            ObservationValues::PA {
+                depth,
+                load,
+                hits,
+                half_turns,
+                soil_type,
+            } => {
+                // Hits are recorded as a negative load
+                let load = match (load, hits) {
+                    (ParseResult::None, ParseResult::Parsed(hits)) => (-hits).to_string(),
+                    _ => field(load),
+                };
+                vec![field(depth), load, field(half_turns), field(soil_type)]
+            }
+            ObservationValues::PI { depth, soil_type }
+            | ObservationValues::PT { depth, soil_type }
+            | ObservationValues::TR { depth, soil_type } => vec![field(depth), field(soil_type)],
+            ObservationValues::LY {
+                depth,
+                load,
+                hits,
+                soil_type,
+            } => vec![field(depth), field(load), field(hits), field(soil_type)],
+            ObservationValues::SI {
+                depth,
+                shear_str,
+                disturb_shear_str,
+                sensitivity,
+                residual_str,
+            } => vec![
+                field(depth),
+                field(shear_str),
+                field(disturb_shear_str),
+                field(sensitivity),
+                field(residual_str),
+            ],
+            ObservationValues::HE {
+                depth,
+                hits,
+                soil_type,
+            } => vec![field(depth), field(hits), field(soil_type)],
+            ObservationValues::HK {
+                depth,
+                hits,
+                torque,
+                soil_type,
+            } => vec![field(depth), field(hits), field(torque), field(soil_type)],
+            ObservationValues::PR {
+                depth,
+                total_resistance,
+                sleeve_friction,
+                soil_type,
+            } => vec![
+                field(depth),
+                field(total_resistance),
+                field(sleeve_friction),
+                field(soil_type),
+            ],
+            ObservationValues::CP {
+                depth,
+                total_resistance,
+                sleeve_friction,
+                tip_resistance,
+                soil_type,
+            } => vec![
+                field(depth),
+                field(total_resistance),
+                field(sleeve_friction),
+                field(tip_resistance),
+                field(soil_type),
+            ],
+            ObservationValues::CU {
+                depth,
+                total_resistance,
+                sleeve_friction,
+                tip_resistance,
+                pore_water_pressure,
+                soil_type,
+            } => vec![
+                field(depth),
+                field(total_resistance),
+                field(sleeve_friction),
+                field(tip_resistance),
+                field(pore_water_pressure),
+                field(soil_type),
+            ],
+            ObservationValues::HP {
+                depth,
+                hits,
+                pressure,
+                torque,
+                mode,
+                soil_type,
+            } => {
+                let value = match mode {
+                    ParseResult::Parsed(mode) if mode == "H" => field(hits),
+                    _ => field(pressure),
+                };
+                vec![
+                    field(depth),
+                    value,
+                    field(torque),
+                    field(mode),
+                    field(soil_type),
+                ]
+            }
+            ObservationValues::PO {
+                depth,
+                time,
+                soil_type,
+            } => vec![field(depth), field(time), field(soil_type)],
+            ObservationValues::MW {
+                depth,
+                advance_rate,
+                compressive_force,
+                flushing_pressure,
+                water_consumption,
+                torque,
+                rotation_speed,
+                hits,
+                soil_type,
+            } => vec![
+                field(depth),
+                field(advance_rate),
+                field(compressive_force),
+                field(flushing_pressure),
+                field(water_consumption),
+                field(torque),
+                field(rotation_speed),
+                field(hits),
+                field(soil_type),
+            ],
+            ObservationValues::VP {
+                surface_elev,
+                date,
+                pipe_top_elev,
+                pipe_bot_elev,
+                sieve_len,
+                measurer,
+            }
+            | ObservationValues::VO {
+                surface_elev,
+                date,
+                pipe_top_elev,
+                pipe_bot_elev,
+                sieve_len,
+                measurer,
+            }
+            | ObservationValues::HU {
+                surface_elev,
+                date,
+                pipe_top_elev,
+                pipe_bot_elev,
+                sieve_len,
+                measurer,
+            } => vec![
+                field(surface_elev),
+                field(date),
+                field(pipe_top_elev),
+                field(pipe_bot_elev),
+                field(sieve_len),
+                field(measurer),
+            ],
+            ObservationValues::VK {
+                surface_elev,
+                date,
+                water_type,
+            } => vec![field(surface_elev), field(date), field(water_type)],
+            ObservationValues::VPK { surface_elev, date } => {
+                vec![field(surface_elev), field(date)]
+            }
+            ObservationValues::HV {
+                depth,
+                pressure,
+                date,
+                measurer,
+            } => vec![field(depth), field(pressure), field(date), field(measurer)],
+            ObservationValues::PS {
+                depth,
+                modulus,
+                fail_pressure,
+            } => vec![field(depth), field(modulus), field(fail_pressure)],
+            ObservationValues::PM {
+                elev,
+                date,
+                measurer,
+            } => vec![field(elev), field(date), field(measurer)],
+            ObservationValues::KO {
+                depth,
+                soil_type,
+                stones,
+                boulders,
+                max_width,
+                min_width,
+            } => vec![
+                field(depth),
+                field(soil_type),
+                field(stones),
+                field(boulders),
+                field(max_width),
+                field(min_width),
+            ],
+            ObservationValues::KE {
+                start_depth,
+                end_depth,
+            }
+            | ObservationValues::KR {
+                start_depth,
+                end_depth,
+            } => vec![field(start_depth), field(end_depth)],
+            ObservationValues::NO {
+                start_depth,
+                sample_id,
+                end_depth,
+                soil_type,
+                ..
+            }
+            | ObservationValues::NE {
+                start_depth,
+                sample_id,
+                end_depth,
+                soil_type,
+                ..
+            } => vec![
+                field(start_depth),
+                field(sample_id),
+                field(end_depth),
+                field(soil_type),
+            ],
+        };
+
+        row("", fields)
+    }
+}
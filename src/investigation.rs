@@ -8,20 +8,24 @@ pub(crate) mod method;
 pub(crate) mod organisations;
 pub(crate) mod program;
 pub(crate) mod record;
+pub(crate) mod soil_layer;
 pub(crate) mod standpipe;
 pub(crate) mod termination;
 pub(crate) mod work;
 
 use crate::{
-    Classification, Coordinates, DepthlessRockSample, Equipment, FileInfo, InitialBorehole, Line,
-    Method, Observation, ObservationValues, Organisations, ParseResult, Program, Record, Spatial,
-    Standpipe, Termination, Work,
+    Classification, CoordinateSystem, Coordinates, DepthlessRockSample, ElevationSystem, Equipment,
+    FileInfo, Format, InitialBoreToken, InitialBorehole, Line, Method, Observation,
+    ObservationValues, Organisations, ParseResult, Program, Record, SoilLayer, SoilLayerSource,
+    Spatial, Standpipe, Termination, TerminationToken, Transformer, WaterObserved, Work,
 };
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Investigation {
     // File-level properties
     pub file_info: FileInfo,
+    pub format: Format,
     pub spatial: Spatial,
 
     // Parsed properties
@@ -45,13 +49,19 @@ pub struct Investigation {
 
     // Computed and additional properties
     pub total_depth: Option<f32>,
+    pub sounded_depth: Option<f32>,
     pub soil_layers: Vec<SoilLayer>,
+    // Spatial reference the investigation was recorded in, set when converted
+    pub original_spatial: Option<Spatial>,
 }
 
 #[derive(Clone, PartialEq, Debug, Default)]
-pub struct SoilLayer {
-    pub soil_type: String,
-    pub thickness: f32,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResistanceStatistics {
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
 }
 
 impl Investigation {
@@ -95,8 +105,208 @@ impl Investigation {
         }
     }
 
+    pub fn continues_as_another(&self) -> bool {
+        matches!(
+            self.termination.token,
+            ParseResult::Parsed(TerminationToken::JA)
+        )
+    }
+
+    pub fn continues_previous(&self) -> bool {
+        matches!(
+            self.initial_borehole.method,
+            ParseResult::Parsed(InitialBoreToken::JA)
+        )
+    }
+
+    pub fn combine(parts: &[&Investigation]) -> Option<Investigation> {
+        let (first, rest) = parts.split_first()?;
+        let mut combined = (*first).clone();
+
+        for part in rest {
+            let previous_depth = combined.total_depth.unwrap_or(0.0);
+
+            combined.observations.extend(
+                part.observations
+                    .iter()
+                    .filter(|observation| match observation.values.get_parsed_depth() {
+                        Some(depth) => depth > previous_depth,
+                        None => true,
+                    })
+                    .cloned(),
+            );
+            combined.notes.extend(part.notes.iter().cloned());
+            combined.free_text.extend(part.free_text.iter().cloned());
+            combined
+                .hidden_text
+                .extend(part.hidden_text.iter().cloned());
+            combined.termination = part.termination.clone();
+            combined.compute_properties();
+        }
+
+        Some(combined)
+    }
+
+    pub fn convert_elevations(&mut self, target: ElevationSystem) -> Result<(), String> {
+        self.convert_elevations_with(target, &Transformer::default())
+    }
+
+    pub fn convert_elevations_with(
+        &mut self,
+        target: ElevationSystem,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        let source = match &self.spatial.elevation_system {
+            ParseResult::Parsed(system) => system,
+            _ => return Err("Source elevation system is unknown".to_string()),
+        };
+
+        let coordinate_system = match &self.spatial.coordinate_system {
+            ParseResult::Parsed(system) => system,
+            _ => return Err("Coordinate system is unknown".to_string()),
+        };
+
+        let (x, y) = match (&self.coordinates.x, &self.coordinates.y) {
+            (ParseResult::Parsed(x), ParseResult::Parsed(y)) => (*x, *y),
+            _ => return Err("Coordinates are missing".to_string()),
+        };
+
+        let correction =
+            transformer.elevation_correction(coordinate_system, x, y, source, &target)? as f32;
+
+        let mut elevations = self.standpipe.elevations_mut();
+        elevations.push(&mut self.coordinates.start_elevation);
+        for observation in &mut self.observations {
+            elevations.extend(observation.values.elevations_mut());
+        }
+
+        for elevation in elevations {
+            if let ParseResult::Parsed(value) = elevation {
+                *value += correction;
+            }
+        }
+
+        self.spatial.elevation_system = ParseResult::Parsed(target);
+        self.calculate_soil_layer_thicknesses();
+
+        Ok(())
+    }
+
+    pub fn convert_coordinates(&mut self, target: CoordinateSystem) -> Result<(), String> {
+        self.convert_coordinates_with(target, &Transformer::default())
+    }
+
+    pub fn convert_coordinates_with(
+        &mut self,
+        target: CoordinateSystem,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        let source = match &self.spatial.coordinate_system {
+            ParseResult::Parsed(system) => system,
+            _ => return Err("Source coordinate system is unknown".to_string()),
+        };
+
+        // Without coordinates only the system is relabelled
+        if let (ParseResult::Parsed(x), ParseResult::Parsed(y)) =
+            (&self.coordinates.x, &self.coordinates.y)
+        {
+            let (x, y) = transformer.transform(source, &target, *x, *y)?;
+            self.coordinates.x = ParseResult::Parsed(x);
+            self.coordinates.y = ParseResult::Parsed(y);
+        }
+
+        self.spatial.coordinate_system = ParseResult::Parsed(target);
+        Ok(())
+    }
+
+    // Converts elevations and coordinates to the parsed systems of `target`.
+    // Either both succeed or the investigation is left unchanged.
+    pub fn harmonise_with(
+        &mut self,
+        target: &Spatial,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        let mut converted = self.clone();
+
+        if let ParseResult::Parsed(system) = &target.elevation_system {
+            if converted.spatial.elevation_system != target.elevation_system {
+                converted.convert_elevations_with(system.clone(), transformer)?;
+            }
+        }
+
+        if let ParseResult::Parsed(system) = &target.coordinate_system {
+            if converted.spatial.coordinate_system != target.coordinate_system {
+                converted.convert_coordinates_with(system.clone(), transformer)?;
+            }
+        }
+
+        if converted.spatial != self.spatial && converted.original_spatial.is_none() {
+            converted.original_spatial = Some(self.spatial.clone());
+        }
+
+        *self = converted;
+        Ok(())
+    }
+
+    pub fn water_observations(&self) -> Vec<&WaterObserved> {
+        self.observations
+            .iter()
+            .filter_map(|observation| match &observation.water_observed {
+                ParseResult::Parsed(water) => Some(water),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn first_water_observed(&self) -> Option<&WaterObserved> {
+        self.water_observations()
+            .into_iter()
+            .filter(|water| water.depth.is_some())
+            .min_by(|a, b| {
+                a.depth
+                    .partial_cmp(&b.depth)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    pub fn initial_bore_depth(&self) -> f32 {
+        match self.initial_borehole.depth {
+            ParseResult::Parsed(depth) if depth > 0.0 => depth,
+            _ => 0.0,
+        }
+    }
+
+    pub fn resistance_statistics(&self) -> Option<ResistanceStatistics> {
+        let initial_depth = self.initial_bore_depth();
+
+        let values: Vec<f32> = self
+            .observations
+            .iter()
+            .filter(|observation| match observation.values.get_parsed_depth() {
+                Some(depth) => depth > initial_depth,
+                None => false,
+            })
+            .filter_map(|observation| observation.values.get_parsed_resistance())
+            .collect();
+
+        if values.is_empty() {
+            return None;
+        }
+
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+
+        Some(ResistanceStatistics {
+            count: values.len(),
+            min,
+            max,
+            mean,
+        })
+    }
+
     fn calculate_total_depth(&mut self) {
-        self.total_depth = self.observations.last().and_then(|last_observation| {
+        let observed_depth = self.observations.last().and_then(|last_observation| {
             let depth_result = match &last_observation.values {
                 ObservationValues::PA { depth, .. }
                 | ObservationValues::PI { depth, .. }
@@ -123,13 +333,45 @@ impl Investigation {
                 _ => None,
             }
         });
+
+        let initial_depth = self.initial_bore_depth();
+
+        self.total_depth = match observed_depth {
+            Some(depth) => Some(depth.max(initial_depth)),
+            None if initial_depth > 0.0 => Some(initial_depth),
+            None => None,
+        };
+
+        self.sounded_depth = self
+            .total_depth
+            .map(|depth| (depth - initial_depth).max(0.0));
     }
 
     pub fn calculate_soil_layer_thicknesses(&mut self) {
-        let mut merged_layers: Vec<SoilLayer> = Vec::new();
-        let mut previous_depth = 0.0;
+        let mut layers: Vec<SoilLayer> = Vec::new();
+        let mut previous_depth = self.initial_bore_depth();
+
+        if previous_depth > 0.0 {
+            let soil_type = match &self.initial_borehole.soil_type {
+                ParseResult::Parsed(soil) | ParseResult::Fallback(soil) => soil.clone(),
+                ParseResult::None => String::new(),
+            };
+
+            let method = match &self.initial_borehole.method {
+                ParseResult::Parsed(token) => token.clone(),
+                _ => InitialBoreToken::Unknown,
+            };
 
-        for observation in &self.observations {
+            layers.push(SoilLayer {
+                soil_type,
+                top_depth: 0.0,
+                bottom_depth: previous_depth,
+                source: SoilLayerSource::InitialBorehole(method),
+                ..Default::default()
+            });
+        }
+
+        for (index, observation) in self.observations.iter().enumerate() {
             let current_depth = match observation.values.get_parsed_depth() {
                 Some(d) => d,
                 None => continue,
@@ -140,30 +382,237 @@ impl Investigation {
                 None => continue,
             };
 
-            let thickness = current_depth - previous_depth;
-
-            if thickness < 0.0 {
+            if current_depth <= previous_depth {
                 continue;
             }
 
-            let new_layer = SoilLayer {
-                soil_type: soil_type.clone(),
-                thickness,
+            layers.push(SoilLayer {
+                soil_type,
+                top_depth: previous_depth,
+                bottom_depth: current_depth,
+                source: SoilLayerSource::Sounding,
+                observations: vec![index],
+                ..Default::default()
+            });
+
+            previous_depth = current_depth;
+        }
+
+        // Sample soil types are classified from the sample itself, so they
+        // take precedence over the layers interpreted from the sounding.
+        for (index, observation) in self.observations.iter().enumerate() {
+            let (top_depth, bottom_depth) = match observation.values.get_parsed_depth_interval() {
+                Some(interval) => interval,
+                None => continue,
+            };
+
+            let soil_type = match observation.values.get_parsed_soil_type() {
+                Some(s) => s.clone(),
+                None => continue,
             };
 
-            if let Some(last_layer) = merged_layers.last_mut() {
-                if last_layer.soil_type == new_layer.soil_type {
-                    last_layer.thickness += new_layer.thickness;
-                } else {
-                    merged_layers.push(new_layer);
+            // The initial borehole (AL) layer is kept whole, samples only
+            // replace the layers below it
+            let top_depth = top_depth.max(self.initial_bore_depth());
+            if bottom_depth <= top_depth {
+                continue;
+            }
+
+            layers = Self::cut_soil_layers(layers, top_depth, bottom_depth);
+            layers.push(SoilLayer {
+                soil_type,
+                top_depth,
+                bottom_depth,
+                source: SoilLayerSource::Sample,
+                observations: vec![index],
+                ..Default::default()
+            });
+        }
+
+        layers.sort_by(|a, b| {
+            a.top_depth
+                .partial_cmp(&b.top_depth)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut merged_layers: Vec<SoilLayer> = Vec::new();
+
+        for layer in layers {
+            match merged_layers.last_mut() {
+                Some(last_layer) if last_layer.can_merge(&layer) => {
+                    last_layer.bottom_depth = layer.bottom_depth;
+                    last_layer.observations.extend(layer.observations);
                 }
-            } else {
-                merged_layers.push(new_layer);
+                _ => merged_layers.push(layer),
             }
+        }
 
-            previous_depth = current_depth;
+        let start_elevation = match self.coordinates.start_elevation {
+            ParseResult::Parsed(elevation) => Some(elevation),
+            _ => None,
+        };
+
+        for layer in &mut merged_layers {
+            layer.thickness = layer.bottom_depth - layer.top_depth;
+            layer.top_elevation = start_elevation.map(|z| z - layer.top_depth);
+            layer.bottom_elevation = start_elevation.map(|z| z - layer.bottom_depth);
         }
 
         self.soil_layers = merged_layers;
     }
+
+    fn cut_soil_layers(
+        layers: Vec<SoilLayer>,
+        top_depth: f32,
+        bottom_depth: f32,
+    ) -> Vec<SoilLayer> {
+        let mut result = Vec::with_capacity(layers.len() + 1);
+
+        for layer in layers {
+            if layer.bottom_depth <= top_depth
+                || layer.top_depth >= bottom_depth
+                || matches!(layer.source, SoilLayerSource::InitialBorehole(_))
+            {
+                result.push(layer);
+                continue;
+            }
+
+            if layer.top_depth < top_depth {
+                result.push(SoilLayer {
+                    bottom_depth: top_depth,
+                    ..layer.clone()
+                });
+            }
+
+            if layer.bottom_depth > bottom_depth {
+                result.push(SoilLayer {
+                    top_depth: bottom_depth,
+                    ..layer
+                });
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+AL 1.0 LK Mr
+0.5 100 3 Mr
+1.0 100 5 Sa
+2.0 100 10 Sa
+3.0 100 20 Sa
+-1 KI
+TT PA 1 P2
+XY 6672010.0 385000.0 12.50 01012020 P2
+AL 2.0 LK Mr
+-1 KI
+TT PA 1 P3
+XY 6672020.0 385000.0 12.50 01012020 P3
+1.0 100 4 Sa
+-1 KI
+";
+
+    const HAMMERING: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT HP 1 H1
+XY 6672000.0 385000.0 12.50 01012020 H1
+0.2 5 10 H Sa
+0.4 1500 20 P Sa
+0.6 7 10 H Sa
+-1 KI
+";
+
+    const SAMPLES: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT NO 1 N1
+XY 6672000.0 385000.0 12.50 01012020 N1
+AL 1.0 LK Mr
+0.5 S1 1.5 Sa
+2.0 S2 3.0 Ct
+-1 MS
+";
+
+    #[test]
+    fn initial_borehole_depths() {
+        let file = InfraFile::parse_str(INFRA);
+        let [bored, only_bored, sounded] = &file.investigations[..] else {
+            panic!("expected three investigations");
+        };
+
+        assert_eq!(bored.initial_bore_depth(), 1.0);
+        assert_eq!(bored.total_depth, Some(3.0));
+        assert_eq!(bored.sounded_depth, Some(2.0));
+
+        // Observations within the initial borehole are left out
+        let statistics = bored.resistance_statistics().unwrap();
+        assert_eq!(statistics.count, 2);
+        assert_eq!((statistics.min, statistics.max), (10.0, 20.0));
+        assert_eq!(statistics.mean, 15.0);
+
+        assert_eq!(only_bored.initial_bore_depth(), 2.0);
+        assert_eq!(only_bored.total_depth, Some(2.0));
+        assert_eq!(only_bored.sounded_depth, Some(0.0));
+        assert!(only_bored.resistance_statistics().is_none());
+
+        assert_eq!(sounded.initial_bore_depth(), 0.0);
+        assert_eq!(sounded.total_depth, Some(1.0));
+        assert_eq!(sounded.sounded_depth, Some(1.0));
+        assert_eq!(sounded.resistance_statistics().unwrap().count, 1);
+    }
+
+    #[test]
+    fn hammering_resistance() {
+        let file = InfraFile::parse_str(HAMMERING);
+
+        // The pressure row has no hits and is not mixed into the statistics
+        let statistics = file.investigations[0].resistance_statistics().unwrap();
+        assert_eq!(statistics.count, 2);
+        assert_eq!((statistics.min, statistics.max), (5.0, 7.0));
+        assert_eq!(statistics.mean, 6.0);
+    }
+
+    #[test]
+    fn sample_soil_layers() {
+        let file = InfraFile::parse_str(SAMPLES);
+        let layers: Vec<_> = file.investigations[0]
+            .soil_layers
+            .iter()
+            .map(|layer| {
+                (
+                    layer.soil_type.as_str(),
+                    layer.top_depth,
+                    layer.bottom_depth,
+                    layer.thickness,
+                    layer.top_elevation,
+                    layer.bottom_elevation,
+                )
+            })
+            .collect();
+
+        // The sample starting inside the initial borehole is cut to its bottom
+        assert_eq!(
+            layers,
+            vec![
+                ("Mr", 0.0, 1.0, 1.0, Some(12.5), Some(11.5)),
+                ("Sa", 1.0, 1.5, 0.5, Some(11.5), Some(11.0)),
+                ("Ct", 2.0, 3.0, 1.0, Some(10.5), Some(9.5)),
+            ]
+        );
+        assert_eq!(
+            file.investigations[0].soil_layers[0].source,
+            SoilLayerSource::InitialBorehole(InitialBoreToken::LK)
+        );
+        assert_eq!(
+            file.investigations[0].soil_layers[1].source,
+            SoilLayerSource::Sample
+        );
+    }
 }
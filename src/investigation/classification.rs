@@ -2,11 +2,13 @@ use crate::{ParseResult, TryParse};
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Classification {
     pub name: ParseResult<ClassificationName>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClassificationName {
     #[default]
     GEO,
@@ -1,12 +1,62 @@
-use crate::ParseResult;
+use crate::{CoordinateSystem, ParseResult, Spatial, Transformer};
 use chrono::NaiveDate;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinates {
     // TODO: Implement "-999999" as unknown for x and y coordinates
-    pub x: ParseResult<f32>,
-    pub y: ParseResult<f32>,
+    // Kept in f64, f32 only resolves 0.5-2 m at national grid magnitudes
+    pub x: ParseResult<f64>,
+    pub y: ParseResult<f64>,
     pub start_elevation: ParseResult<f32>,
     pub date: ParseResult<NaiveDate>,
     pub point_id: ParseResult<String>,
 }
+
+impl Coordinates {
+    // Parsed x and y, None when either is missing or -999999, which marks an
+    // unknown coordinate
+    pub(crate) fn known_xy(&self) -> Option<(f64, f64)> {
+        match (&self.x, &self.y) {
+            (ParseResult::Parsed(x), ParseResult::Parsed(y))
+                if *x > -999_999.0 && *y > -999_999.0 =>
+            {
+                Some((*x, *y))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn transform_to(
+        &self,
+        spatial: &Spatial,
+        target: CoordinateSystem,
+    ) -> Result<Coordinates, String> {
+        self.transform_with(spatial, target, &Transformer::default())
+    }
+
+    pub fn transform_with(
+        &self,
+        spatial: &Spatial,
+        target: CoordinateSystem,
+        transformer: &Transformer,
+    ) -> Result<Coordinates, String> {
+        let source = match &spatial.coordinate_system {
+            ParseResult::Parsed(system) => system,
+            _ => return Err("Source coordinate system is unknown".to_string()),
+        };
+
+        let (x, y) = match (&self.x, &self.y) {
+            (ParseResult::Parsed(x), ParseResult::Parsed(y)) => (*x, *y),
+            _ => return Err("Coordinates are missing".to_string()),
+        };
+
+        let (x, y) = transformer.transform(source, &target, x, y)?;
+
+        Ok(Coordinates {
+            x: ParseResult::Parsed(x),
+            y: ParseResult::Parsed(y),
+            ..self.clone()
+        })
+    }
+}
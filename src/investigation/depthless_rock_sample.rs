@@ -1,6 +1,7 @@
 use crate::ParseResult;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DepthlessRockSample {
     // TODO: Implement known attributes (attachment 3)
     pub attribute: ParseResult<String>,
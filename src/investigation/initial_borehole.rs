@@ -2,6 +2,7 @@ use crate::{ParseResult, TryParse};
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitialBorehole {
     pub depth: ParseResult<f32>,
     pub method: ParseResult<InitialBoreToken>,
@@ -9,6 +10,7 @@ pub struct InitialBorehole {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InitialBoreToken {
     #[default]
     Unknown,
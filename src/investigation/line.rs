@@ -1,6 +1,7 @@
 use crate::ParseResult;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     pub name: ParseResult<String>,
     pub stake: ParseResult<f32>,
@@ -3,6 +3,7 @@ use crate::{ParseResult, TryParse};
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Method {
     pub token: ParseResult<MethodToken>,
     pub category: ParseResult<i32>,
@@ -13,6 +14,7 @@ pub struct Method {
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MethodToken {
     #[default]
     None,
@@ -46,6 +48,7 @@ pub enum MethodToken {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sampler {
     #[default]
     Unknown,
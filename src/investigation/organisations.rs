@@ -1,6 +1,7 @@
 use crate::ParseResult;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Organisations {
     pub owner_name: ParseResult<String>,
     pub investigator_name: ParseResult<String>,
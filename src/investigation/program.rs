@@ -2,6 +2,7 @@ use crate::ParseResult;
 use chrono::NaiveDate;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub name: ParseResult<String>,
     pub date: ParseResult<NaiveDate>,
@@ -3,6 +3,7 @@ use crate::{ParseResult, TryParse};
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record {
     pub number: ParseResult<i32>,
     pub driller: ParseResult<String>,
@@ -13,6 +14,7 @@ pub struct Record {
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Digitized {
     #[default]
     No,
@@ -0,0 +1,59 @@
+use crate::InitialBoreToken;
+
+use std::fmt;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoilLayer {
+    pub soil_type: String,
+    pub top_depth: f32,
+    pub bottom_depth: f32,
+    pub thickness: f32,
+    pub top_elevation: Option<f32>,
+    pub bottom_elevation: Option<f32>,
+    pub source: SoilLayerSource,
+    // Indices into `Investigation::observations` the layer was derived from
+    pub observations: Vec<usize>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SoilLayerSource {
+    #[default]
+    Sounding,
+    Sample,
+    InitialBorehole(InitialBoreToken),
+}
+
+impl SoilLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn can_merge(&self, other: &SoilLayer) -> bool {
+        self.soil_type == other.soil_type
+            && self.source == other.source
+            && !matches!(self.source, SoilLayerSource::InitialBorehole(_))
+            && (self.bottom_depth - other.top_depth).abs() < 1e-4
+    }
+}
+
+impl fmt::Display for SoilLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} - {:.2} m: {} ({})",
+            self.top_depth, self.bottom_depth, self.soil_type, self.source
+        )
+    }
+}
+
+impl fmt::Display for SoilLayerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoilLayerSource::Sounding => write!(f, "Sounding"),
+            SoilLayerSource::Sample => write!(f, "Sample"),
+            SoilLayerSource::InitialBorehole(token) => write!(f, "Initial borehole: {}", token),
+        }
+    }
+}
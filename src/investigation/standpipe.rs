@@ -1,6 +1,7 @@
 use crate::ParseResult;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Standpipe {
     // ZP token
     pub top_elevation: ParseResult<f32>,
@@ -21,3 +22,15 @@ pub struct Standpipe {
     pub lock_owner: ParseResult<String>,
     pub installer: ParseResult<String>,
 }
+
+impl Standpipe {
+    pub(crate) fn elevations_mut(&mut self) -> Vec<&mut ParseResult<f32>> {
+        vec![
+            &mut self.top_elevation,
+            &mut self.ground_elevation,
+            &mut self.protection_top_elevation,
+            &mut self.cover_elevation,
+            &mut self.sieve_bottom_elevation,
+        ]
+    }
+}
@@ -1,10 +1,15 @@
-use crate::{Investigation, MethodToken, ParseResult};
+pub(crate) mod density_grid;
+pub(crate) mod hull;
+
+use crate::{DensityGrid, Investigation, MethodToken, ParseResult};
 use std::collections::HashMap;
 
 pub trait HasInvestigations {
     fn investigations(&self) -> &Vec<Investigation>;
 }
 
+// Geometric methods use the coordinates as they are, so the investigations
+// should share one coordinate system (see `InvestigationCollection::harmonise`).
 pub trait InvestigationAggregator: HasInvestigations {
     fn count_investigations(&self) -> HashMap<MethodToken, usize> {
         let mut acc = HashMap::new();
@@ -15,4 +20,55 @@ pub trait InvestigationAggregator: HasInvestigations {
         }
         acc
     }
+
+    fn points(&self) -> Vec<(f64, f64)> {
+        self.investigations()
+            .iter()
+            .filter_map(|investigation| investigation.coordinates.known_xy())
+            .collect()
+    }
+
+    // (min x, min y, max x, max y)
+    fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+        self.points().iter().fold(None, |acc, (x, y)| match acc {
+            None => Some((*x, *y, *x, *y)),
+            Some((a, b, c, d)) => Some((a.min(*x), b.min(*y), c.max(*x), d.max(*y))),
+        })
+    }
+
+    fn convex_hull(&self) -> Vec<(f64, f64)> {
+        hull::convex_hull(&self.points())
+    }
+
+    // Hull whose edges are at most `max_edge` long where the points allow it
+    fn concave_hull(&self, max_edge: f64) -> Vec<(f64, f64)> {
+        hull::concave_hull(&self.points(), max_edge)
+    }
+
+    fn convex_hull_area(&self) -> f64 {
+        hull::polygon_area(&self.convex_hull())
+    }
+
+    fn concave_hull_area(&self, max_edge: f64) -> f64 {
+        hull::polygon_area(&self.concave_hull(max_edge))
+    }
+
+    // None without points, with an invalid cell size or when the grid would
+    // have more than `DensityGrid::MAX_CELLS` cells
+    fn density_grid(&self, cell_size: f64) -> Option<DensityGrid> {
+        let points: Vec<(f64, f64, Option<MethodToken>)> = self
+            .investigations()
+            .iter()
+            .filter_map(|investigation| {
+                let method = match investigation.method.token {
+                    ParseResult::Parsed(token) => Some(token),
+                    _ => None,
+                };
+                let (x, y) = investigation.coordinates.known_xy()?;
+                Some((x, y, method))
+            })
+            .collect();
+
+        DensityGrid::new(&points, cell_size)
+    }
 }
@@ -0,0 +1,187 @@
+use crate::MethodToken;
+
+use std::collections::HashMap;
+use std::fs;
+
+// Investigation counts on a regular grid. Rows run along x (northing) from
+// south to north, columns along y (easting) from west to east.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DensityGrid {
+    // South-west corner of the grid
+    pub origin_x: f64,
+    pub origin_y: f64,
+    pub cell_size: f64,
+    pub rows: usize,
+    pub columns: usize,
+    // Row-major counts, row 0 being the southernmost
+    pub counts: Vec<usize>,
+    pub method_counts: HashMap<MethodToken, Vec<usize>>,
+}
+
+impl DensityGrid {
+    // 10 million cells, 80 MB of counts per method
+    pub const MAX_CELLS: usize = 10_000_000;
+
+    pub(crate) fn new(points: &[(f64, f64, Option<MethodToken>)], cell_size: f64) -> Option<Self> {
+        if cell_size <= 0.0 || !cell_size.is_finite() {
+            return None;
+        }
+
+        let points: Vec<&(f64, f64, Option<MethodToken>)> = points
+            .iter()
+            .filter(|(x, y, _)| x.is_finite() && y.is_finite())
+            .collect();
+
+        let (min_x, min_y, max_x, max_y) =
+            points.iter().fold(None, |acc, (x, y, _)| match acc {
+                None => Some((*x, *y, *x, *y)),
+                Some((a, b, c, d)) => Some((a.min(*x), b.min(*y), c.max(*x), d.max(*y))),
+            })?;
+
+        // Cell edges are aligned to multiples of the cell size
+        let origin_x = (min_x / cell_size).floor() * cell_size;
+        let origin_y = (min_y / cell_size).floor() * cell_size;
+        let rows = ((max_x - origin_x) / cell_size).floor() + 1.0;
+        let columns = ((max_y - origin_y) / cell_size).floor() + 1.0;
+
+        if rows * columns > Self::MAX_CELLS as f64 {
+            return None;
+        }
+        let (rows, columns) = (rows as usize, columns as usize);
+
+        let mut grid = DensityGrid {
+            origin_x,
+            origin_y,
+            cell_size,
+            rows,
+            columns,
+            counts: vec![0; rows * columns],
+            method_counts: HashMap::new(),
+        };
+
+        for (x, y, method) in points {
+            let row = (((x - origin_x) / cell_size).floor() as usize).min(rows - 1);
+            let column = (((y - origin_y) / cell_size).floor() as usize).min(columns - 1);
+            let index = row * columns + column;
+
+            grid.counts[index] += 1;
+            if let Some(method) = method {
+                grid.method_counts
+                    .entry(*method)
+                    .or_insert_with(|| vec![0; rows * columns])[index] += 1;
+            }
+        }
+
+        Some(grid)
+    }
+
+    pub fn count(&self, row: usize, column: usize) -> usize {
+        self.counts[row * self.columns + column]
+    }
+
+    // Number of cells with at least one investigation
+    pub fn covered_cells(&self) -> usize {
+        self.counts.iter().filter(|count| **count > 0).count()
+    }
+
+    // ESRI ASCII raster of all investigations or of one method. The raster
+    // x axis is easting, so `xllcorner` is the easting of the origin.
+    pub fn to_ascii_raster(&self, method: Option<&MethodToken>) -> String {
+        let empty = vec![0; self.counts.len()];
+        let counts = match method {
+            Some(method) => self.method_counts.get(method).unwrap_or(&empty),
+            None => &self.counts,
+        };
+
+        let mut raster = format!(
+            "ncols {}\nnrows {}\nxllcorner {}\nyllcorner {}\ncellsize {}\nNODATA_value -9999\n",
+            self.columns, self.rows, self.origin_y, self.origin_x, self.cell_size
+        );
+
+        for row in counts.chunks(self.columns).rev() {
+            let values: Vec<String> = row.iter().map(|count| count.to_string()).collect();
+            raster.push_str(&values.join(" "));
+            raster.push('\n');
+        }
+
+        raster
+    }
+
+    pub fn write_ascii_raster(
+        &self,
+        file_path: &str,
+        method: Option<&MethodToken>,
+    ) -> Result<(), String> {
+        fs::write(file_path, self.to_ascii_raster(method))
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn density_grid() {
+        let points = [
+            (6672000.5, 385000.5, Some(MethodToken::PA)),
+            (6672000.7, 385000.2, Some(MethodToken::PA)),
+            (6672002.5, 385001.5, Some(MethodToken::SI)),
+            (6672002.9, 385001.1, None),
+        ];
+        let grid = DensityGrid::new(&points, 1.0).unwrap();
+
+        assert_eq!((grid.origin_x, grid.origin_y), (6672000.0, 385000.0));
+        assert_eq!((grid.rows, grid.columns), (3, 2));
+        assert_eq!(grid.counts, vec![2, 0, 0, 0, 0, 2]);
+        assert_eq!(grid.count(2, 1), 2);
+        assert_eq!(grid.covered_cells(), 2);
+        assert_eq!(grid.method_counts[&MethodToken::SI], vec![0, 0, 0, 0, 0, 1]);
+
+        // Northernmost row first
+        assert_eq!(
+            grid.to_ascii_raster(Some(&MethodToken::PA)),
+            "ncols 2\nnrows 3\nxllcorner 385000\nyllcorner 6672000\ncellsize 1\n\
+             NODATA_value -9999\n0 0\n0 0\n2 0\n"
+        );
+
+        assert_eq!(DensityGrid::new(&points, 0.0), None);
+        assert_eq!(DensityGrid::new(&[], 1.0), None);
+
+        // 1000 km x 1000 km in 1 m cells
+        let spread = [(6672000.0, 385000.0, None), (7672000.0, 1385000.0, None)];
+        assert_eq!(DensityGrid::new(&spread, 1.0), None);
+        assert_eq!(
+            DensityGrid::new(&spread, 1000.0).unwrap().counts.len(),
+            1001 * 1001
+        );
+    }
+
+    #[test]
+    fn unknown_coordinates() {
+        let collection = InvestigationCollection::parse_str(
+            "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.5 385000.5 12.50 01012020 P1
+-1 KI
+TT PA 1 P2
+XY -999999 -999999 12.50 01012020 P2
+-1 KI
+TT PA 1 P3
+XY 6672x 385000.5 12.50 01012020 P3
+-1 KI
+TT PA 1 P4
+-1 KI
+",
+        );
+
+        // Only P1 has a known position
+        assert_eq!(collection.points(), vec![(6672000.5, 385000.5)]);
+        let grid = collection.density_grid(1.0).unwrap();
+        assert_eq!((grid.origin_x, grid.origin_y), (6672000.0, 385000.0));
+        assert_eq!((grid.rows, grid.columns), (1, 1));
+        assert_eq!(grid.counts, vec![1]);
+    }
+}
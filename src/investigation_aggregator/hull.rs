@@ -0,0 +1,308 @@
+// Plane geometry on (x, y) points. Polygons are returned counter-clockwise
+// in the (easting, northing) plane without repeating the first point.
+
+use rstar::primitives::{GeomWithData, Line};
+use rstar::{RTree, AABB};
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+fn cross(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    // Positive when o -> a -> b turns counter-clockwise with x as northing
+    (a.1 - o.1) * (b.0 - o.0) - (a.0 - o.0) * (b.1 - o.1)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+pub(crate) fn convex_hull(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = points.to_vec();
+    points.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.total_cmp(&b.0)));
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Andrew's monotone chain, lower then upper half
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(points.len() * 2);
+    let halves = [points.clone(), points.iter().rev().copied().collect()];
+
+    for half in halves {
+        let start = hull.len();
+        for point in half {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+
+    hull
+}
+
+// Concave hull by digging into the convex hull: edges longer than
+// `max_edge` are replaced by two edges through the closest inner point as
+// long as the polygon stays simple and no point is left outside. Edges are
+// dug longest first, the inner points and the hull edges are kept in R-trees
+// so that each dig only looks at its neighbourhood.
+pub(crate) fn concave_hull(points: &[(f64, f64)], max_edge: f64) -> Vec<(f64, f64)> {
+    let convex = convex_hull(points);
+    if convex.len() < 3 {
+        return convex;
+    }
+
+    let mut points: Vec<(f64, f64)> = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    let index_of = |point: &(f64, f64)| {
+        points
+            .binary_search_by(|p| p.0.total_cmp(&point.0).then(p.1.total_cmp(&point.1)))
+            .unwrap()
+    };
+
+    let mut inner: RTree<IndexedPoint> = RTree::bulk_load(
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| IndexedPoint::new([p.0, p.1], i))
+            .collect(),
+    );
+
+    // Hull as a ring of point indices. Points on the convex hull edges start
+    // in the ring, otherwise edges along straight rows of points could not be
+    // dug past them.
+    let mut hull: Vec<usize> = Vec::new();
+    for (i, &a) in convex.iter().enumerate() {
+        let b = convex[(i + 1) % convex.len()];
+        let mut on_edge: Vec<usize> = inner
+            .locate_in_envelope(&bounds(&[a, b]))
+            .map(|point| point.data)
+            .filter(|&j| points[j] != a && points[j] != b && cross(a, b, points[j]) == 0.0)
+            .collect();
+        on_edge.sort_by(|x, y| distance(a, points[*x]).total_cmp(&distance(a, points[*y])));
+
+        hull.push(index_of(&a));
+        hull.extend(on_edge);
+    }
+
+    let mut next: Vec<Option<usize>> = vec![None; points.len()];
+    for (i, &from) in hull.iter().enumerate() {
+        next[from] = Some(hull[(i + 1) % hull.len()]);
+        inner.remove(&IndexedPoint::new([points[from].0, points[from].1], from));
+    }
+
+    let mut edges: RTree<IndexedEdge> = RTree::bulk_load(
+        hull.iter()
+            .map(|&from| edge(&points, from, next[from].unwrap()))
+            .collect(),
+    );
+
+    let mut open: BinaryHeap<OpenEdge> = hull
+        .iter()
+        .map(|&from| OpenEdge::new(&points, from, next[from].unwrap()))
+        .filter(|open| open.length > max_edge)
+        .collect();
+
+    while let Some(OpenEdge { from, to, length }) = open.pop() {
+        let (a, b) = (points[from], points[to]);
+
+        // Only points closer than the edge length to both ends qualify
+        let envelope = AABB::from_corners(
+            [a.0.max(b.0) - length, a.1.max(b.1) - length],
+            [a.0.min(b.0) + length, a.1.min(b.1) + length],
+        );
+        let mut candidates: Vec<(f64, usize)> = inner
+            .locate_in_envelope(&envelope)
+            .map(|point| (points[point.data], point.data))
+            .filter(|(p, _)| distance(a, *p) < length && distance(*p, b) < length)
+            .filter(|(p, _)| cross(a, b, *p) > 0.0)
+            .map(|(p, i)| (segment_distance(a, b, p), i))
+            .collect();
+        candidates.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)));
+
+        let candidate = candidates.into_iter().map(|(_, i)| i).find(|&i| {
+            let p = points[i];
+            !crosses_boundary(&edges, (from, to), a, p)
+                && !crosses_boundary(&edges, (from, to), p, b)
+                && !inner
+                    .locate_in_envelope(&bounds(&[a, p, b]))
+                    .any(|q| q.data != i && in_triangle(a, p, b, points[q.data]))
+        });
+
+        if let Some(i) = candidate {
+            inner.remove(&IndexedPoint::new([points[i].0, points[i].1], i));
+            edges.remove(&edge(&points, from, to));
+            next[from] = Some(i);
+            next[i] = Some(to);
+            for (from, to) in [(from, i), (i, to)] {
+                edges.insert(edge(&points, from, to));
+                let open_edge = OpenEdge::new(&points, from, to);
+                if open_edge.length > max_edge {
+                    open.push(open_edge);
+                }
+            }
+        }
+    }
+
+    // Walk the ring from the first vertex of the convex hull
+    let mut polygon = vec![points[hull[0]]];
+    let mut current = next[hull[0]].unwrap();
+    while current != hull[0] {
+        polygon.push(points[current]);
+        current = next[current].unwrap();
+    }
+    polygon
+}
+
+type IndexedPoint = GeomWithData<[f64; 2], usize>;
+type IndexedEdge = GeomWithData<Line<[f64; 2]>, (usize, usize)>;
+
+fn edge(points: &[(f64, f64)], from: usize, to: usize) -> IndexedEdge {
+    let (a, b) = (points[from], points[to]);
+    IndexedEdge::new(Line::new([a.0, a.1], [b.0, b.1]), (from, to))
+}
+
+fn bounds(points: &[(f64, f64)]) -> AABB<[f64; 2]> {
+    AABB::from_points(points.iter().map(|p| [p.0, p.1]).collect::<Vec<_>>().iter())
+}
+
+// Hull edge waiting to be dug, the longest one first
+struct OpenEdge {
+    from: usize,
+    to: usize,
+    length: f64,
+}
+
+impl OpenEdge {
+    fn new(points: &[(f64, f64)], from: usize, to: usize) -> Self {
+        OpenEdge {
+            from,
+            to,
+            length: distance(points[from], points[to]),
+        }
+    }
+}
+
+impl PartialEq for OpenEdge {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenEdge {}
+
+impl PartialOrd for OpenEdge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenEdge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.length
+            .total_cmp(&other.length)
+            .then((other.from, other.to).cmp(&(self.from, self.to)))
+    }
+}
+
+pub(crate) fn polygon_area(polygon: &[(f64, f64)]) -> f64 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            a.1 * b.0 - b.1 * a.0
+        })
+        .sum::<f64>()
+        .abs()
+        / 2.0
+}
+
+fn segment_distance(a: (f64, f64), b: (f64, f64), p: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared < f64::EPSILON {
+        return distance(a, p);
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0);
+    distance(p, (a.0 + t * dx, a.1 + t * dy))
+}
+
+fn in_triangle(a: (f64, f64), b: (f64, f64), c: (f64, f64), p: (f64, f64)) -> bool {
+    let (d1, d2, d3) = (cross(a, b, p), cross(b, c, p), cross(c, a, p));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
+
+// Whether segment p-q properly crosses any hull edge other than `skip`
+fn crosses_boundary(
+    edges: &RTree<IndexedEdge>,
+    skip: (usize, usize),
+    p: (f64, f64),
+    q: (f64, f64),
+) -> bool {
+    edges
+        .locate_in_envelope_intersecting(&bounds(&[p, q]))
+        .filter(|edge| edge.data != skip)
+        .any(|edge| {
+            let (a, b) = (
+                (edge.geom().from[0], edge.geom().from[1]),
+                (edge.geom().to[0], edge.geom().to[1]),
+            );
+            if a == p || a == q || b == p || b == q {
+                return false;
+            }
+            let (d1, d2) = (cross(p, q, a), cross(p, q, b));
+            let (d3, d4) = (cross(a, b, p), cross(a, b, q));
+            d1 * d2 < 0.0 && d3 * d4 < 0.0
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 10 x 10 square with one point just inside the edge at y = 10 and a
+    // duplicate corner
+    const POINTS: [(f64, f64); 6] = [
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 10.0),
+        (0.0, 10.0),
+        (5.0, 9.0),
+        (10.0, 10.0),
+    ];
+
+    #[test]
+    fn hulls() {
+        let convex = convex_hull(&POINTS);
+        assert_eq!(convex.len(), 4);
+        assert_eq!(polygon_area(&convex), 100.0);
+
+        // Only the edge at y = 10 is close enough to the inner point to dig
+        let concave = concave_hull(&POINTS, 6.0);
+        assert_eq!(concave.len(), 5);
+        assert!(concave.contains(&(5.0, 9.0)));
+        assert_eq!(polygon_area(&concave), 95.0);
+
+        assert_eq!(concave_hull(&POINTS, 20.0), convex);
+        assert_eq!(concave_hull(&POINTS[..2], 6.0).len(), 2);
+    }
+
+    #[test]
+    fn concave_hull_of_many_points() {
+        // Grid with a notch cut from one side
+        let points: Vec<(f64, f64)> = (0..100)
+            .flat_map(|x| (0..100).map(move |y| (x as f64, y as f64)))
+            .filter(|(x, y)| !(*x > 30.0 && *x < 70.0 && *y > 80.0))
+            .collect();
+
+        let concave = concave_hull(&points, 1.2);
+        assert_eq!(polygon_area(&concave), 99.0 * 99.0 - 40.0 * 19.0);
+    }
+}
@@ -1,3 +1,11 @@
+pub(crate) mod clip;
+pub(crate) mod continuation;
+pub(crate) mod cross_section;
+pub(crate) mod harmonise;
+pub(crate) mod line_profile;
+pub(crate) mod spatial_index;
+pub(crate) mod stationing;
+
 use crate::{HasInvestigations, InfraFile, Investigation, InvestigationAggregator};
 
 use rayon::prelude::*;
@@ -6,6 +14,7 @@ use walkdir::WalkDir;
 use std::fs;
 
 #[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InvestigationCollection {
     pub investigations: Vec<Investigation>,
 }
@@ -73,6 +82,12 @@ impl InvestigationCollection {
             investigations: infra.investigations,
         })
     }
+
+    pub fn parse_str(content: &str) -> InvestigationCollection {
+        InvestigationCollection {
+            investigations: InfraFile::parse_str(content).investigations,
+        }
+    }
 }
 
 impl HasInvestigations for InvestigationCollection {
@@ -0,0 +1,437 @@
+use crate::{
+    CoordinateSystem, Format, InfraFile, InvestigationCollection, ParseResult, Transformer,
+};
+
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// Polygon with optional holes. Vertices are (x, y) in the InfraModel
+// convention, x being the northing (or latitude). WKT and GeoJSON list
+// easting (or longitude) first, so their axes are swapped when parsed.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    pub exterior: Vec<(f64, f64)>,
+    pub holes: Vec<Vec<(f64, f64)>>,
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<(f64, f64)>, holes: Vec<Vec<(f64, f64)>>) -> Self {
+        Polygon { exterior, holes }
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        ring_contains(&self.exterior, x, y)
+            && !self.holes.iter().any(|hole| ring_contains(hole, x, y))
+    }
+
+    // POLYGON or MULTIPOLYGON
+    pub fn from_wkt(wkt: &str) -> Result<Vec<Polygon>, String> {
+        let wkt = wkt.trim();
+        let open = wkt
+            .find('(')
+            .ok_or_else(|| "Invalid WKT: missing coordinates".to_string())?;
+        let kind = wkt[..open].trim().to_uppercase();
+        let kind = kind.trim_end_matches(" Z").trim_end_matches(" M").trim();
+        let body = parse_wkt_lists(&wkt[open..])?;
+
+        match (kind, body) {
+            ("POLYGON", WktList::List(rings)) => Ok(vec![Self::from_wkt_rings(rings)?]),
+            ("MULTIPOLYGON", WktList::List(polygons)) => polygons
+                .into_iter()
+                .map(|polygon| match polygon {
+                    WktList::List(rings) => Self::from_wkt_rings(rings),
+                    WktList::Point(_) => Err("Invalid WKT multipolygon".to_string()),
+                })
+                .collect(),
+            (kind, _) => Err(format!("Unsupported WKT geometry: {}", kind)),
+        }
+    }
+
+    // Polygon or MultiPolygon geometry, or a Feature or FeatureCollection of them
+    pub fn from_geojson(geojson: &str) -> Result<Vec<Polygon>, String> {
+        let value: Value =
+            serde_json::from_str(geojson).map_err(|e| format!("Invalid GeoJSON: {}", e))?;
+        let mut polygons = Vec::new();
+        Self::collect_geojson(&value, &mut polygons)?;
+        Ok(polygons)
+    }
+
+    fn collect_geojson(value: &Value, polygons: &mut Vec<Polygon>) -> Result<(), String> {
+        let kind = value["type"].as_str().unwrap_or_default();
+
+        match kind {
+            "FeatureCollection" => {
+                for feature in value["features"].as_array().into_iter().flatten() {
+                    Self::collect_geojson(feature, polygons)?;
+                }
+            }
+            "Feature" if !value["geometry"].is_null() => {
+                Self::collect_geojson(&value["geometry"], polygons)?;
+            }
+            "GeometryCollection" => {
+                for geometry in value["geometries"].as_array().into_iter().flatten() {
+                    Self::collect_geojson(geometry, polygons)?;
+                }
+            }
+            "Polygon" => polygons.push(Self::from_geojson_rings(&value["coordinates"])?),
+            "MultiPolygon" => {
+                for rings in value["coordinates"].as_array().into_iter().flatten() {
+                    polygons.push(Self::from_geojson_rings(rings)?);
+                }
+            }
+            // Other geometries do not bound an area
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn from_geojson_rings(value: &Value) -> Result<Polygon, String> {
+        let rings = value
+            .as_array()
+            .ok_or_else(|| "Invalid GeoJSON polygon".to_string())?
+            .iter()
+            .map(|ring| {
+                ring.as_array()
+                    .ok_or_else(|| "Invalid GeoJSON ring".to_string())?
+                    .iter()
+                    .map(
+                        |position| match (position[0].as_f64(), position[1].as_f64()) {
+                            (Some(east), Some(north)) => Ok((north, east)),
+                            _ => Err("Invalid GeoJSON position".to_string()),
+                        },
+                    )
+                    .collect::<Result<Vec<_>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Self::from_rings(rings)
+    }
+
+    fn from_wkt_rings(rings: Vec<WktList>) -> Result<Polygon, String> {
+        let rings = rings
+            .into_iter()
+            .map(|ring| match ring {
+                WktList::List(points) => points
+                    .into_iter()
+                    .map(|point| match point {
+                        WktList::Point((east, north)) => Ok((north, east)),
+                        WktList::List(_) => Err("Invalid WKT ring".to_string()),
+                    })
+                    .collect::<Result<Vec<_>, String>>(),
+                WktList::Point(_) => Err("Invalid WKT polygon".to_string()),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Self::from_rings(rings)
+    }
+
+    fn from_rings(mut rings: Vec<Vec<(f64, f64)>>) -> Result<Polygon, String> {
+        if rings.is_empty() {
+            return Err("Polygon has no rings".to_string());
+        }
+
+        for ring in &mut rings {
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            if ring.len() < 3 {
+                return Err("Polygon ring needs at least three points".to_string());
+            }
+        }
+
+        let exterior = rings.remove(0);
+        Ok(Polygon {
+            exterior,
+            holes: rings,
+        })
+    }
+}
+
+enum WktList {
+    Point((f64, f64)),
+    List(Vec<WktList>),
+}
+
+// Nested parenthesised coordinate lists, e.g. "((1 2, 3 4, 5 6))"
+fn parse_wkt_lists(input: &str) -> Result<WktList, String> {
+    let mut stack: Vec<Vec<WktList>> = Vec::new();
+    let mut token = String::new();
+    let mut result = None;
+
+    let flush = |token: &mut String, stack: &mut Vec<Vec<WktList>>| -> Result<(), String> {
+        let values: Vec<&str> = token.split_whitespace().collect();
+        if !values.is_empty() {
+            let parse = |value: &str| {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid WKT number: {}", value))
+            };
+            if values.len() < 2 {
+                return Err(format!("Invalid WKT point: {}", token.trim()));
+            }
+            let point = (parse(values[0])?, parse(values[1])?);
+            stack
+                .last_mut()
+                .ok_or_else(|| "Invalid WKT".to_string())?
+                .push(WktList::Point(point));
+        }
+        token.clear();
+        Ok(())
+    };
+
+    for c in input.chars() {
+        match c {
+            '(' => stack.push(Vec::new()),
+            ',' => flush(&mut token, &mut stack)?,
+            ')' => {
+                flush(&mut token, &mut stack)?;
+                let list = stack.pop().ok_or_else(|| "Unbalanced WKT".to_string())?;
+                match stack.last_mut() {
+                    Some(parent) => parent.push(WktList::List(list)),
+                    None => result = Some(WktList::List(list)),
+                }
+            }
+            _ if result.is_some() && !c.is_whitespace() => {
+                return Err("Unexpected content after WKT geometry".to_string())
+            }
+            _ => token.push(c),
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err("Unbalanced WKT".to_string());
+    }
+
+    result.ok_or_else(|| "Invalid WKT".to_string())
+}
+
+// Even-odd rule
+fn ring_contains(ring: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    let mut j = ring.len().wrapping_sub(1);
+
+    for i in 0..ring.len() {
+        let (xi, yi) = ring[i];
+        let (xj, yj) = ring[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+impl InvestigationCollection {
+    // Investigations inside any of the polygons, which are given in
+    // `coordinate_system`. Investigations whose position cannot be resolved
+    // in that system are left out.
+    pub fn clip(
+        &self,
+        polygons: &[Polygon],
+        coordinate_system: CoordinateSystem,
+    ) -> InvestigationCollection {
+        self.clip_with(polygons, coordinate_system, &Transformer::default())
+    }
+
+    pub fn clip_with(
+        &self,
+        polygons: &[Polygon],
+        coordinate_system: CoordinateSystem,
+        transformer: &Transformer,
+    ) -> InvestigationCollection {
+        let investigations = self
+            .investigations
+            .iter()
+            .filter(|investigation| {
+                Self::indexed_position(investigation, &coordinate_system, transformer)
+                    .is_some_and(|[x, y]| polygons.iter().any(|polygon| polygon.contains(x, y)))
+            })
+            .cloned()
+            .collect();
+
+        InvestigationCollection { investigations }
+    }
+
+    // Writes the investigations into `folder_path` as InfraModel files, one
+    // per source file and spatial reference. The source files keep their
+    // paths relative to the folder they have in common. Returns the written
+    // file paths.
+    pub fn write_files(&self, folder_path: &str) -> Result<Vec<String>, String> {
+        let sources: Vec<&Path> = self
+            .investigations
+            .iter()
+            .filter_map(|investigation| investigation.file_info.path.as_deref())
+            .map(Path::new)
+            .collect();
+        let root = common_folder(&sources);
+
+        let mut files: BTreeMap<(PathBuf, String), InfraFile> = BTreeMap::new();
+
+        for investigation in &self.investigations {
+            let relative = investigation
+                .file_info
+                .path
+                .as_deref()
+                .map(Path::new)
+                .and_then(|path| path.strip_prefix(&root).ok())
+                .filter(|path| path.file_name().is_some())
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("investigations.tek"));
+
+            // Files without a FO version are written as the current version
+            let version = match &investigation.format.version {
+                ParseResult::None => ParseResult::Parsed("2.5".to_string()),
+                version => version.clone(),
+            };
+
+            let file = files
+                .entry((relative, investigation.spatial.to_string()))
+                .or_insert_with(|| InfraFile {
+                    file_info: investigation.file_info.clone(),
+                    format: Format {
+                        version,
+                        used_software: ParseResult::Parsed(env!("CARGO_PKG_NAME").to_string()),
+                        software_version: ParseResult::Parsed(
+                            env!("CARGO_PKG_VERSION").to_string(),
+                        ),
+                    },
+                    spatial: investigation.spatial.clone(),
+                    investigations: Vec::new(),
+                });
+            file.investigations.push(investigation.clone());
+        }
+
+        let mut names: BTreeMap<PathBuf, usize> = BTreeMap::new();
+        let mut written = Vec::new();
+
+        for ((relative, _), file) in files {
+            let count = names.entry(relative.clone()).or_insert(0);
+            *count += 1;
+
+            // Same source in several spatial references gets numbered files
+            let name = relative.file_name().unwrap_or_default().to_string_lossy();
+            let name = match (*count, name.rsplit_once('.')) {
+                (1, _) => name.to_string(),
+                (n, Some((stem, extension))) => format!("{}_{}.{}", stem, n, extension),
+                (n, None) => format!("{}_{}", name, n),
+            };
+            let path = Path::new(folder_path).join(relative.with_file_name(name));
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    format!("Failed to create folder '{}': {}", parent.display(), e)
+                })?;
+            }
+
+            let path = path.to_string_lossy().to_string();
+            file.write_file(&path)?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+// Deepest folder containing all of the files
+fn common_folder(files: &[&Path]) -> PathBuf {
+    let mut folders = files
+        .iter()
+        .map(|file| file.parent().unwrap_or(Path::new("")));
+    let Some(first) = folders.next() else {
+        return PathBuf::new();
+    };
+
+    let mut common: Vec<Component> = first.components().collect();
+    for folder in folders {
+        let shared = common
+            .iter()
+            .zip(folder.components())
+            .take_while(|(a, b)| *a == b)
+            .count();
+        common.truncate(shared);
+    }
+
+    common.iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.4 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.123 385000.456 12.34 01012020 P1
+LN L1 125.5 -2.25
+1.0 100 10 Sa
+2.0 55 20 SaSi
+-1 KI
+TT SI 1 P2
+XY 6672950.001 385999.999 11.5 02012020 P2
+0.5 12.5 Sa
+-1 KA
+";
+
+    #[test]
+    fn write_clipped_files() {
+        let folder = temp_path("write_files");
+        let output = temp_path("write_files_output");
+
+        // Same file name in two folders
+        for area in ["north", "south"] {
+            std::fs::create_dir_all(folder.join(area)).unwrap();
+            std::fs::write(folder.join(area).join("points.tek"), INFRA).unwrap();
+        }
+        let collection = InvestigationCollection::parse_folder(folder.to_str().unwrap()).unwrap();
+
+        let polygons = Polygon::from_wkt(
+            "POLYGON((384900 6671900, 386100 6671900, 386100 6673100, 384900 6673100, 384900 6671900))",
+        )
+        .unwrap();
+        let clipped = collection.clip(&polygons, CoordinateSystem::TM35);
+        assert_eq!(clipped.investigations.len(), 4);
+
+        let mut written = clipped.write_files(output.to_str().unwrap()).unwrap();
+        written.sort();
+        let expected: Vec<String> = ["north", "south"]
+            .iter()
+            .map(|area| {
+                output
+                    .join(area)
+                    .join("points.tek")
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(written, expected);
+
+        // Parse -> write -> parse keeps the investigations as they were
+        let original =
+            InfraFile::parse_file(folder.join("north").join("points.tek").to_str().unwrap())
+                .unwrap();
+        for path in &written {
+            let infra = InfraFile::parse_file(path).unwrap();
+            assert_eq!(infra.format.version, ParseResult::Parsed("2.4".to_string()));
+            assert_eq!(infra.investigations.len(), original.investigations.len());
+
+            for (mut rewritten, original) in infra
+                .investigations
+                .into_iter()
+                .zip(original.investigations.iter())
+            {
+                rewritten.file_info = original.file_info.clone();
+                rewritten.format = original.format.clone();
+                assert_eq!(&rewritten, original);
+            }
+        }
+
+        std::fs::remove_dir_all(&folder).ok();
+        std::fs::remove_dir_all(&output).ok();
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{Investigation, InvestigationCollection, ParseResult};
+
+// Maximum horizontal distance (m) between the investigations of a continued sounding
+const POINT_TOLERANCE: f64 = 0.5;
+// Maximum difference (m) between the end depth and the continuing initial bore depth
+const DEPTH_TOLERANCE: f32 = 0.2;
+
+impl InvestigationCollection {
+    pub fn continuation_chains(&self) -> Vec<Vec<usize>> {
+        let mut used = vec![false; self.investigations.len()];
+        let mut chains = Vec::new();
+
+        for (start, investigation) in self.investigations.iter().enumerate() {
+            if used[start]
+                || investigation.continues_previous()
+                || !investigation.continues_as_another()
+            {
+                continue;
+            }
+
+            let mut chain = vec![start];
+            used[start] = true;
+            let mut current = start;
+
+            while self.investigations[current].continues_as_another() {
+                match self.find_continuation(current, &used) {
+                    Some(next) => {
+                        used[next] = true;
+                        chain.push(next);
+                        current = next;
+                    }
+                    None => break,
+                }
+            }
+
+            if chain.len() > 1 {
+                chains.push(chain);
+            }
+        }
+
+        chains
+    }
+
+    pub fn combine_continuations(&self) -> InvestigationCollection {
+        let chains = self.continuation_chains();
+        let mut chained = vec![false; self.investigations.len()];
+        let mut investigations = Vec::with_capacity(self.investigations.len());
+
+        for chain in &chains {
+            for &index in chain {
+                chained[index] = true;
+            }
+        }
+
+        for (index, investigation) in self.investigations.iter().enumerate() {
+            if let Some(chain) = chains.iter().find(|chain| chain[0] == index) {
+                let parts: Vec<&Investigation> =
+                    chain.iter().map(|&i| &self.investigations[i]).collect();
+                if let Some(combined) = Investigation::combine(&parts) {
+                    investigations.push(combined);
+                }
+            } else if !chained[index] {
+                investigations.push(investigation.clone());
+            }
+        }
+
+        InvestigationCollection { investigations }
+    }
+
+    fn find_continuation(&self, current: usize, used: &[bool]) -> Option<usize> {
+        let previous = &self.investigations[current];
+        let end_depth = previous.total_depth.unwrap_or(0.0);
+
+        self.investigations
+            .iter()
+            .enumerate()
+            .filter(|(index, candidate)| {
+                !used[*index]
+                    && candidate.continues_previous()
+                    && Self::same_point(previous, candidate)
+            })
+            .filter_map(
+                |(index, candidate)| match candidate.initial_borehole.depth {
+                    ParseResult::Parsed(depth) => {
+                        let difference = (depth - end_depth).abs();
+                        (difference <= DEPTH_TOLERANCE).then_some((index, difference))
+                    }
+                    _ => Some((index, DEPTH_TOLERANCE)),
+                },
+            )
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(index, _)| index)
+    }
+
+    fn same_point(a: &Investigation, b: &Investigation) -> bool {
+        if a.spatial != b.spatial {
+            return false;
+        }
+
+        match (
+            &a.coordinates.x,
+            &a.coordinates.y,
+            &b.coordinates.x,
+            &b.coordinates.y,
+        ) {
+            (
+                ParseResult::Parsed(ax),
+                ParseResult::Parsed(ay),
+                ParseResult::Parsed(bx),
+                ParseResult::Parsed(by),
+            ) => ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt() <= POINT_TOLERANCE,
+            _ => match (&a.coordinates.point_id, &b.coordinates.point_id) {
+                (ParseResult::Parsed(a_id), ParseResult::Parsed(b_id)) => a_id == b_id,
+                _ => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+1.0 100 10 Sa
+3.0 100 30 Sa
+-1 JA
+TT PA 1 P2
+XY 6672100.0 385000.0 12.50 01012020 P2
+AL 3.0 JA
+3.5 -10 50 Mr
+-1 KI
+TT PA 1 P1B
+XY 6672000.2 385000.1 12.50 01012020 P1B
+AL 3.1 JA
+3.0 -5 40 Mr
+3.5 -10 50 Mr
+4.0 -20 60 Mr
+-1 KI
+";
+
+    #[test]
+    fn continuations() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+
+        // P2 continues a sounding 100 m away and is left alone
+        assert_eq!(collection.continuation_chains(), vec![vec![0, 2]]);
+
+        let combined = collection.combine_continuations();
+        assert_eq!(combined.investigations.len(), 2);
+
+        let sounding = &combined.investigations[0];
+        assert_eq!(
+            sounding.coordinates.point_id,
+            ParseResult::Parsed("P1".to_string())
+        );
+        assert_eq!(sounding.total_depth, Some(4.0));
+        assert_eq!(
+            sounding.termination.token,
+            ParseResult::Parsed(TerminationToken::KI)
+        );
+
+        // Rows of the continuation above the previous end depth are dropped
+        let depths: Vec<f32> = sounding
+            .observations
+            .iter()
+            .filter_map(|observation| observation.values.get_parsed_depth())
+            .collect();
+        assert_eq!(depths, vec![1.0, 3.0, 3.5, 4.0]);
+
+        assert_eq!(
+            combined.investigations[1].coordinates.point_id,
+            ParseResult::Parsed("P2".to_string())
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use crate::{
+    CoordinateSystem, ElevationSystem, Investigation, InvestigationCollection, Observation,
+    ParseResult, SoilLayer, Transformer,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CrossSection<'a> {
+    pub coordinate_system: CoordinateSystem,
+    // Polyline vertices as (x, y), x being the northing
+    pub alignment: Vec<(f64, f64)>,
+    pub corridor_width: f64,
+    // Elevations are converted to this system when given, otherwise they are
+    // as recorded
+    pub elevation_system: Option<ElevationSystem>,
+    // Sorted by chainage
+    pub investigations: Vec<ProfileInvestigation<'a>>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProfileInvestigation<'a> {
+    pub investigation: &'a Investigation,
+    pub chainage: f64,
+    // Perpendicular distance from the alignment, positive to the right
+    pub offset: f64,
+    // None when the elevation could not be converted to the target system
+    pub start_elevation: Option<f64>,
+    pub observations: Vec<ProfileObservation<'a>>,
+    pub soil_layers: &'a [SoilLayer],
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProfileObservation<'a> {
+    pub observation: &'a Observation,
+    // Top of the interval for sample and core observations
+    pub depth: Option<f64>,
+    pub depth_interval: Option<(f64, f64)>,
+    pub elevation: Option<f64>,
+}
+
+impl InvestigationCollection {
+    pub fn cross_section(
+        &self,
+        alignment: &[(f64, f64)],
+        coordinate_system: CoordinateSystem,
+        elevation_system: Option<ElevationSystem>,
+        corridor_width: f64,
+    ) -> Result<CrossSection<'_>, String> {
+        self.cross_section_with(
+            alignment,
+            coordinate_system,
+            elevation_system,
+            corridor_width,
+            &Transformer::default(),
+        )
+    }
+
+    pub fn cross_section_with(
+        &self,
+        alignment: &[(f64, f64)],
+        coordinate_system: CoordinateSystem,
+        elevation_system: Option<ElevationSystem>,
+        corridor_width: f64,
+        transformer: &Transformer,
+    ) -> Result<CrossSection<'_>, String> {
+        if alignment.len() < 2 {
+            return Err("Alignment needs at least two vertices".to_string());
+        }
+
+        let mut investigations = Vec::new();
+
+        for investigation in &self.investigations {
+            let position =
+                match Self::indexed_position(investigation, &coordinate_system, transformer) {
+                    Some([x, y]) => (x, y),
+                    None => continue,
+                };
+
+            let (chainage, offset) = match station_offset(alignment, position) {
+                Some(result) => result,
+                None => continue,
+            };
+
+            if offset.abs() > corridor_width / 2.0 {
+                continue;
+            }
+
+            let correction = match &elevation_system {
+                Some(target) => Self::elevation_correction(investigation, target, transformer),
+                None => Some(0.0),
+            };
+
+            investigations.push(ProfileInvestigation::new(
+                investigation,
+                chainage,
+                offset,
+                correction,
+            ));
+        }
+
+        investigations.sort_by(|a, b| {
+            a.chainage
+                .partial_cmp(&b.chainage)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(CrossSection {
+            coordinate_system,
+            alignment: alignment.to_vec(),
+            corridor_width,
+            elevation_system,
+            investigations,
+        })
+    }
+
+    fn elevation_correction(
+        investigation: &Investigation,
+        target: &ElevationSystem,
+        transformer: &Transformer,
+    ) -> Option<f64> {
+        let (x, y) = investigation.coordinates.known_xy()?;
+
+        match (
+            &investigation.spatial.coordinate_system,
+            &investigation.spatial.elevation_system,
+        ) {
+            (ParseResult::Parsed(coordinate_system), ParseResult::Parsed(source)) => transformer
+                .elevation_correction(coordinate_system, x, y, source, target)
+                .ok(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ProfileInvestigation<'a> {
+    // `elevation_correction` is added to the recorded elevations, None leaves
+    // them unknown
+    pub(crate) fn new(
+        investigation: &'a Investigation,
+        chainage: f64,
+        offset: f64,
+        elevation_correction: Option<f64>,
+    ) -> Self {
+        let start_elevation = match investigation.coordinates.start_elevation {
+            ParseResult::Parsed(elevation) => {
+                elevation_correction.map(|correction| elevation as f64 + correction)
+            }
+            _ => None,
+        };
+
+        let observations = investigation
+            .observations
+            .iter()
+            .map(|observation| {
+                let depth_interval = observation
+                    .values
+                    .get_parsed_depth_interval()
+                    .map(|(top, bottom)| (top as f64, bottom as f64));
+                let depth = match observation.values.get_parsed_depth() {
+                    Some(depth) => Some(depth as f64),
+                    None => depth_interval.map(|(top, _)| top),
+                };
+                let elevation = match observation.values.get_parsed_elevation() {
+                    Some(elevation) => {
+                        elevation_correction.map(|correction| elevation as f64 + correction)
+                    }
+                    None => depth.and_then(|d| start_elevation.map(|z| z - d)),
+                };
+
+                ProfileObservation {
+                    observation,
+                    depth,
+                    depth_interval,
+                    elevation,
+                }
+            })
+            .collect();
+
+        ProfileInvestigation {
+            investigation,
+            chainage,
+            offset,
+            start_elevation,
+            observations,
+            soil_layers: &investigation.soil_layers,
+        }
+    }
+}
+
+// Chainage along the polyline and signed offset (positive to the right) of
+// the closest point, or None when the point projects beyond either end.
+pub(crate) fn station_offset(alignment: &[(f64, f64)], point: (f64, f64)) -> Option<(f64, f64)> {
+    // Chainage, offset, distance and whether the projection falls beyond the ends
+    let mut best: Option<(f64, f64, f64, bool)> = None;
+    let mut start_chainage = 0.0;
+    let last_segment = alignment.len().saturating_sub(2);
+
+    for (i, segment) in alignment.windows(2).enumerate() {
+        let (a, b) = (segment[0], segment[1]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let length = (dx * dx + dy * dy).sqrt();
+
+        if length < f64::EPSILON {
+            continue;
+        }
+
+        let (vx, vy) = (point.0 - a.0, point.1 - a.1);
+        let t = (vx * dx + vy * dy) / (length * length);
+
+        let beyond_ends = (i == 0 && t < 0.0) || (i == last_segment && t > 1.0);
+        let t = t.clamp(0.0, 1.0);
+        let (px, py) = (a.0 + t * dx, a.1 + t * dy);
+        let distance = ((point.0 - px).powi(2) + (point.1 - py).powi(2)).sqrt();
+        let side = if dx * vy - dy * vx >= 0.0 { 1.0 } else { -1.0 };
+
+        if best.is_none_or(|(_, _, d, _)| distance < d) {
+            best = Some((
+                start_chainage + t * length,
+                side * distance,
+                distance,
+                beyond_ends,
+            ));
+        }
+
+        start_chainage += length;
+    }
+
+    match best {
+        Some((chainage, offset, _, false)) => Some((chainage, offset)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::investigation_collection::cross_section::station_offset;
+    use crate::*;
+
+    // East 100 m, then north 100 m
+    const ALIGNMENT: [(f64, f64); 3] = [
+        (6672000.0, 385000.0),
+        (6672000.0, 385100.0),
+        (6672100.0, 385100.0),
+    ];
+
+    const N2000: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 A
+XY 6671990.0 385050.0 12.00 01012020 A
+-1 KI
+TT PA 1 B
+XY 6672050.0 385095.0 12.00 01012020 B
+-1 KI
+TT PA 1 C
+XY 6672030.0 385130.0 12.00 01012020 C
+-1 KI
+TT PA 1 D
+XY 6672000.0 384990.0 12.00 01012020 D
+-1 KI
+TT PA 1 E
+XY 6672110.0 385100.0 12.00 01012020 E
+-1 KI
+TT PA 1 H
+XY 6671990.0 385110.0 12.00 01012020 H
+-1 KI
+";
+
+    const N60: &str = "FO 2.5 Test 1.0
+KJ TM35 N60
+TT PA 1 F
+XY 6671995.0 385020.0 10.00 01012020 F
+1.0 100 10 Sa
+-1 KI
+";
+
+    const UNKNOWN_HEIGHT: &str = "FO 2.5 Test 1.0
+KJ TM35
+TT PA 1 G
+XY 6672000.0 385010.0 10.00 01012020 G
+-1 KI
+";
+
+    fn collection() -> InvestigationCollection {
+        let mut collection = InvestigationCollection::parse_str(N2000);
+        for content in [N60, UNKNOWN_HEIGHT] {
+            collection
+                .investigations
+                .extend(InvestigationCollection::parse_str(content).investigations);
+        }
+        collection
+    }
+
+    fn id(investigation: &Investigation) -> &str {
+        match &investigation.coordinates.point_id {
+            ParseResult::Parsed(id) => id,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn station_offsets() {
+        // Right of the first segment, left of the second
+        assert_eq!(
+            station_offset(&ALIGNMENT, (6671990.0, 385050.0)),
+            Some((50.0, 10.0))
+        );
+        assert_eq!(
+            station_offset(&ALIGNMENT, (6672010.0, 385050.0)),
+            Some((50.0, -10.0))
+        );
+        assert_eq!(
+            station_offset(&ALIGNMENT, (6672050.0, 385095.0)),
+            Some((150.0, -5.0))
+        );
+        assert_eq!(
+            station_offset(&ALIGNMENT, (6672050.0, 385105.0)),
+            Some((150.0, 5.0))
+        );
+
+        // Outside the corner the closest point is the vertex
+        let (chainage, offset) = station_offset(&ALIGNMENT, (6671990.0, 385110.0)).unwrap();
+        assert_eq!(chainage, 100.0);
+        assert!((offset - 200f64.sqrt()).abs() < 1e-9);
+
+        // The ends are inclusive, anything beyond them is left out
+        assert_eq!(station_offset(&ALIGNMENT, ALIGNMENT[0]), Some((0.0, 0.0)));
+        assert_eq!(station_offset(&ALIGNMENT, ALIGNMENT[2]), Some((200.0, 0.0)));
+        assert_eq!(station_offset(&ALIGNMENT, (6672000.0, 384990.0)), None);
+        assert_eq!(station_offset(&ALIGNMENT, (6672110.0, 385100.0)), None);
+
+        // Zero length segments are skipped
+        let repeated = [ALIGNMENT[0], ALIGNMENT[1], ALIGNMENT[1], ALIGNMENT[2]];
+        assert_eq!(
+            station_offset(&repeated, (6672050.0, 385095.0)),
+            Some((150.0, -5.0))
+        );
+    }
+
+    #[test]
+    fn cross_section() {
+        let collection = collection();
+        let section = collection
+            .cross_section(&ALIGNMENT, CoordinateSystem::TM35, None, 40.0)
+            .unwrap();
+
+        // C is 30 m off the alignment, D is before the start and E past the end
+        let investigations: Vec<(&str, f64, f64)> = section
+            .investigations
+            .iter()
+            .map(|profile| (id(profile.investigation), profile.chainage, profile.offset))
+            .collect();
+        assert_eq!(investigations.len(), 5);
+        assert_eq!(
+            investigations
+                .iter()
+                .map(|(id, chainage, _)| (*id, *chainage))
+                .collect::<Vec<_>>(),
+            vec![
+                ("G", 10.0),
+                ("F", 20.0),
+                ("A", 50.0),
+                ("H", 100.0),
+                ("B", 150.0)
+            ]
+        );
+        assert_eq!(investigations[1].2, 5.0);
+        assert_eq!(investigations[4].2, -5.0);
+
+        // Without a target the elevations are as recorded
+        assert_eq!(section.investigations[0].start_elevation, Some(10.0));
+        assert_eq!(section.investigations[1].start_elevation, Some(10.0));
+
+        // A narrower corridor drops A and H
+        let section = collection
+            .cross_section(&ALIGNMENT, CoordinateSystem::TM35, None, 19.0)
+            .unwrap();
+        assert_eq!(
+            section
+                .investigations
+                .iter()
+                .map(|profile| id(profile.investigation))
+                .collect::<Vec<_>>(),
+            vec!["G", "F", "B"]
+        );
+
+        assert!(collection
+            .cross_section(&ALIGNMENT[..1], CoordinateSystem::TM35, None, 40.0)
+            .is_err());
+    }
+
+    #[test]
+    fn cross_section_elevations() {
+        let collection = collection();
+
+        // Constant 0.3 m N60 -> N2000 correction over Finland
+        let transformer = Transformer {
+            n60_to_n2000: Some(HeightGrid {
+                lat_min: 59.0,
+                lon_min: 19.0,
+                lat_step: 12.0,
+                lon_step: 13.0,
+                rows: 2,
+                cols: 2,
+                values: vec![0.3; 4],
+            }),
+            ..Default::default()
+        };
+        let section = collection
+            .cross_section_with(
+                &ALIGNMENT,
+                CoordinateSystem::TM35,
+                Some(ElevationSystem::N2000),
+                40.0,
+                &transformer,
+            )
+            .unwrap();
+        assert_eq!(section.elevation_system, Some(ElevationSystem::N2000));
+
+        let [g, f, a, ..] = &section.investigations[..] else {
+            panic!("expected five investigations");
+        };
+
+        // G has no elevation system to convert from
+        assert_eq!(id(g.investigation), "G");
+        assert_eq!(g.start_elevation, None);
+
+        assert_eq!(id(f.investigation), "F");
+        assert!((f.start_elevation.unwrap() - 10.3).abs() < 1e-9);
+        assert_eq!(f.observations[0].depth, Some(1.0));
+        assert!((f.observations[0].elevation.unwrap() - 9.3).abs() < 1e-9);
+
+        assert_eq!(a.start_elevation, Some(12.0));
+
+        // Without the grid N60 cannot be converted
+        let section = collection
+            .cross_section(
+                &ALIGNMENT,
+                CoordinateSystem::TM35,
+                Some(ElevationSystem::N2000),
+                40.0,
+            )
+            .unwrap();
+        assert_eq!(section.investigations[1].start_elevation, None);
+        assert_eq!(section.investigations[1].observations[0].elevation, None);
+        assert_eq!(section.investigations[2].start_elevation, Some(12.0));
+    }
+}
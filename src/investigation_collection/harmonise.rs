@@ -0,0 +1,109 @@
+use crate::{InvestigationCollection, Spatial, Transformer};
+
+impl InvestigationCollection {
+    // Converts every investigation to the parsed systems of `target`. Fails
+    // without changing anything if any investigation cannot be converted.
+    pub fn harmonise(&mut self, target: Spatial) -> Result<(), String> {
+        self.harmonise_with(target, &Transformer::default())
+    }
+
+    pub fn harmonise_with(
+        &mut self,
+        target: Spatial,
+        transformer: &Transformer,
+    ) -> Result<(), String> {
+        let mut harmonised = self.clone();
+        let failures = harmonised.harmonise_partial(target, transformer);
+
+        if let Some((index, message)) = failures.first() {
+            return Err(format!(
+                "{} investigation(s) could not be harmonised, first at index {}: {}",
+                failures.len(),
+                index,
+                message
+            ));
+        }
+
+        *self = harmonised;
+        Ok(())
+    }
+
+    // Converts what can be converted and leaves the rest unchanged. Returns
+    // the index and reason of every investigation that was left unchanged.
+    pub fn harmonise_partial(
+        &mut self,
+        target: Spatial,
+        transformer: &Transformer,
+    ) -> Vec<(usize, String)> {
+        self.investigations
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, investigation)| {
+                investigation
+                    .harmonise_with(&target, transformer)
+                    .err()
+                    .map(|message| (index, message))
+            })
+            .collect()
+    }
+
+    // True when all investigations share the same coordinate and elevation system
+    pub fn is_harmonised(&self) -> bool {
+        self.investigations
+            .windows(2)
+            .all(|pair| pair[0].spatial == pair[1].spatial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.123 385000.456 12.50 01012020 P1
+1.0 100 10 Sa
+-1 KI
+";
+
+    #[test]
+    fn harmonised_coordinates_precision() {
+        let mut collection = InvestigationCollection::parse_str(INFRA);
+
+        let gk25 = Spatial {
+            coordinate_system: ParseResult::Parsed(CoordinateSystem::GK25),
+            elevation_system: ParseResult::Parsed(ElevationSystem::N2000),
+        };
+        collection.harmonise(gk25).unwrap();
+
+        // The harmonised values are the transformed values, not rounded to f32
+        let (x, y) = Transformer::default()
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::GK25,
+                6672000.123,
+                385000.456,
+            )
+            .unwrap();
+        let coordinates = &collection.investigations[0].coordinates;
+        assert_eq!(coordinates.x, ParseResult::Parsed(x));
+        assert_eq!(coordinates.y, ParseResult::Parsed(y));
+        assert!(y > 25_000_000.0);
+
+        let tm35 = Spatial {
+            coordinate_system: ParseResult::Parsed(CoordinateSystem::TM35),
+            elevation_system: ParseResult::Parsed(ElevationSystem::N2000),
+        };
+        collection.harmonise(tm35).unwrap();
+
+        let coordinates = &collection.investigations[0].coordinates;
+        match (&coordinates.x, &coordinates.y) {
+            (ParseResult::Parsed(x), ParseResult::Parsed(y)) => {
+                assert!((x - 6672000.123).abs() < 0.001);
+                assert!((y - 385000.456).abs() < 0.001);
+            }
+            _ => panic!("coordinates were lost"),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{Investigation, InvestigationCollection, ParseResult, ProfileInvestigation};
+
+use std::collections::BTreeMap;
+
+// Allowed excess (m) of the coordinate distance over the stake based bound
+const STAKE_TOLERANCE: f64 = 1.0;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StakeInconsistency<'a> {
+    pub line: String,
+    pub first: &'a Investigation,
+    pub second: &'a Investigation,
+    // Longest distance the stakes and offsets allow
+    pub stake_distance: f64,
+    // Distance between the coordinates
+    pub coordinate_distance: f64,
+}
+
+impl InvestigationCollection {
+    // Investigations grouped by LN line name, ordered by stake and offset.
+    // Investigations without a stake are placed last.
+    pub fn lines(&self) -> BTreeMap<String, Vec<&Investigation>> {
+        let mut lines: BTreeMap<String, Vec<&Investigation>> = BTreeMap::new();
+
+        for investigation in &self.investigations {
+            if let ParseResult::Parsed(name) = &investigation.line.name {
+                lines.entry(name.clone()).or_default().push(investigation);
+            }
+        }
+
+        for investigations in lines.values_mut() {
+            investigations.sort_by(|a, b| {
+                let key = |inv: &Investigation| {
+                    (
+                        Self::stake(inv).unwrap_or(f64::INFINITY),
+                        Self::stake_offset(inv),
+                    )
+                };
+                key(a)
+                    .partial_cmp(&key(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+
+        lines
+    }
+
+    // Longitudinal profile of one line using the stake as chainage and the
+    // LN distance as offset. No alignment geometry is needed.
+    pub fn line_profile(
+        &self,
+        line: &str,
+        corridor_width: Option<f64>,
+    ) -> Vec<ProfileInvestigation<'_>> {
+        self.lines()
+            .remove(line)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|investigation| {
+                let stake = Self::stake(investigation)?;
+                let offset = Self::stake_offset(investigation);
+
+                match corridor_width {
+                    Some(width) if offset.abs() > width / 2.0 => None,
+                    _ => Some(ProfileInvestigation::new(
+                        investigation,
+                        stake,
+                        offset,
+                        Some(0.0),
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    // Consecutive investigations whose coordinate distance cannot be explained
+    // by their stakes and offsets. Without the alignment geometry the only safe
+    // bound is the stake difference plus both offsets: the chord between the
+    // stakes is never longer than the stake difference, but offsets on the
+    // outside of a curve spread further apart than the stakes.
+    pub fn stake_inconsistencies(&self) -> Vec<StakeInconsistency<'_>> {
+        let mut inconsistencies = Vec::new();
+
+        for (line, investigations) in self.lines() {
+            let located: Vec<(&Investigation, f64, f64, f64, f64)> = investigations
+                .into_iter()
+                .filter_map(|inv| {
+                    let stake = Self::stake(inv)?;
+                    match (&inv.coordinates.x, &inv.coordinates.y) {
+                        (ParseResult::Parsed(x), ParseResult::Parsed(y)) => {
+                            Some((inv, stake, Self::stake_offset(inv), *x, *y))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            for pair in located.windows(2) {
+                let (first, stake_a, offset_a, xa, ya) = pair[0];
+                let (second, stake_b, offset_b, xb, yb) = pair[1];
+
+                let stake_distance = (stake_b - stake_a).abs() + offset_a.abs() + offset_b.abs();
+                let coordinate_distance = ((xb - xa).powi(2) + (yb - ya).powi(2)).sqrt();
+
+                if coordinate_distance > stake_distance + STAKE_TOLERANCE {
+                    inconsistencies.push(StakeInconsistency {
+                        line: line.clone(),
+                        first,
+                        second,
+                        stake_distance,
+                        coordinate_distance,
+                    });
+                }
+            }
+        }
+
+        inconsistencies
+    }
+
+    fn stake(investigation: &Investigation) -> Option<f64> {
+        match investigation.line.stake {
+            ParseResult::Parsed(stake) => Some(stake as f64),
+            _ => None,
+        }
+    }
+
+    fn stake_offset(investigation: &Investigation) -> f64 {
+        match investigation.line.distance {
+            ParseResult::Parsed(distance) => distance as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    // Centre line is a 50 m radius arc around (6672000, 385000), P1 and P2 are
+    // 10 m outside it and P3 is placed far from its stake
+    const INFRA: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672060.000 385000.000 12.50 01012020 P1
+LN L1 0.0 10.0
+-1 KI
+TT PA 1 P2
+XY 6672052.655 385028.766 12.50 01012020 P2
+LN L1 25.0 10.0
+-1 KI
+TT PA 1 P3
+XY 6672000.000 385200.000 12.50 01012020 P3
+LN L1 40.0 0.0
+-1 KI
+";
+
+    #[test]
+    fn stake_inconsistencies() {
+        let collection = InvestigationCollection::parse_str(INFRA);
+
+        let profile = collection.line_profile("L1", None);
+        assert_eq!(profile.len(), 3);
+
+        // P1 -> P2 is ~29.7 m apart with stakes 25 m apart, which the curve explains
+        let inconsistencies = collection.stake_inconsistencies();
+        assert_eq!(inconsistencies.len(), 1);
+
+        let inconsistency = &inconsistencies[0];
+        assert_eq!(inconsistency.line, "L1");
+        assert_eq!(
+            inconsistency.second.coordinates.point_id,
+            ParseResult::Parsed("P3".to_string())
+        );
+        assert_eq!(inconsistency.stake_distance, 25.0);
+        assert!(inconsistency.coordinate_distance > 170.0);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{CoordinateSystem, Investigation, InvestigationCollection, ParseResult, Transformer};
+
+use rstar::{primitives::GeomWithData, RTree, AABB};
+
+type IndexedPoint = GeomWithData<[f64; 2], usize>;
+
+// R-tree over the investigation points of a collection. All points are
+// converted to one coordinate system so that investigations recorded in
+// different systems can be queried together. Coordinates are (x, y) in the
+// InfraModel convention, x being the northing.
+pub struct SpatialIndex<'a> {
+    pub coordinate_system: CoordinateSystem,
+    // Investigations without coordinates or with a system that could not be
+    // transformed to `coordinate_system`
+    pub skipped: Vec<usize>,
+    collection: &'a InvestigationCollection,
+    tree: RTree<IndexedPoint>,
+}
+
+impl InvestigationCollection {
+    pub fn spatial_index(&self, coordinate_system: CoordinateSystem) -> SpatialIndex<'_> {
+        self.spatial_index_with(coordinate_system, &Transformer::default())
+    }
+
+    pub fn spatial_index_with(
+        &self,
+        coordinate_system: CoordinateSystem,
+        transformer: &Transformer,
+    ) -> SpatialIndex<'_> {
+        let mut points = Vec::with_capacity(self.investigations.len());
+        let mut skipped = Vec::new();
+
+        for (index, investigation) in self.investigations.iter().enumerate() {
+            match Self::indexed_position(investigation, &coordinate_system, transformer) {
+                Some(position) => points.push(IndexedPoint::new(position, index)),
+                None => skipped.push(index),
+            }
+        }
+
+        SpatialIndex {
+            coordinate_system,
+            skipped,
+            collection: self,
+            tree: RTree::bulk_load(points),
+        }
+    }
+
+    pub(crate) fn indexed_position(
+        investigation: &Investigation,
+        coordinate_system: &CoordinateSystem,
+        transformer: &Transformer,
+    ) -> Option<[f64; 2]> {
+        let (x, y) = investigation.coordinates.known_xy()?;
+
+        match &investigation.spatial.coordinate_system {
+            ParseResult::Parsed(system) if system == coordinate_system => Some([x, y]),
+            ParseResult::Parsed(system) => transformer
+                .transform(system, coordinate_system, x, y)
+                .ok()
+                .map(|(x, y)| [x, y]),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> SpatialIndex<'a> {
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+
+    pub fn nearest(&self, x: f64, y: f64, k: usize) -> Vec<(&'a Investigation, f64)> {
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&[x, y])
+            .take(k)
+            .map(|(point, distance_2)| (self.investigation(point), distance_2.sqrt()))
+            .collect()
+    }
+
+    // Sorted by distance
+    pub fn within_radius(&self, x: f64, y: f64, radius: f64) -> Vec<(&'a Investigation, f64)> {
+        if radius < 0.0 {
+            return Vec::new();
+        }
+
+        let mut result: Vec<(&'a Investigation, f64)> = self
+            .tree
+            .locate_within_distance([x, y], radius * radius)
+            .map(|point| {
+                let [px, py] = *point.geom();
+                (
+                    self.investigation(point),
+                    ((px - x).powi(2) + (py - y).powi(2)).sqrt(),
+                )
+            })
+            .collect();
+
+        result.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        result
+    }
+
+    pub fn within_bounds(&self, min: (f64, f64), max: (f64, f64)) -> Vec<&'a Investigation> {
+        let envelope = AABB::from_corners([min.0, min.1], [max.0, max.1]);
+
+        self.tree
+            .locate_in_envelope(&envelope)
+            .map(|point| self.investigation(point))
+            .collect()
+    }
+
+    // Position of an investigation in the index coordinate system
+    pub fn position(&self, investigation: &Investigation) -> Option<(f64, f64)> {
+        self.tree
+            .iter()
+            .find(|point| std::ptr::eq(self.investigation(point), investigation))
+            .map(|point| (point.geom()[0], point.geom()[1]))
+    }
+
+    fn investigation(&self, point: &IndexedPoint) -> &'a Investigation {
+        &self.collection.investigations[point.data]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TM35: &str = "FO 2.5 Test 1.0
+KJ TM35 N2000
+TT PA 1 P1
+XY 6672000.0 385000.0 12.50 01012020 P1
+-1 KI
+TT PA 1 P2
+XY 6672030.0 385040.0 12.50 01012020 P2
+-1 KI
+TT PA 1 P3
+XY 6672100.0 385000.0 12.50 01012020 P3
+-1 KI
+TT PA 1 U1
+XY -999999 -999999 12.50 01012020 U1
+-1 KI
+TT PA 1 U2
+-1 KI
+";
+
+    const HKI: &str = "FO 2.5 Test 1.0
+KJ HKI N2000
+TT PA 1 H1
+XY 6672000.0 25496600.0 12.50 01012020 H1
+-1 KI
+";
+
+    // P1-P3 and U1-U2 in TM35, G1 in GK25 20 m east of P1 and H1 in a
+    // municipal system without a definition
+    fn collection() -> InvestigationCollection {
+        let (x, y) = Transformer::default()
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::GK25,
+                6672000.0,
+                385020.0,
+            )
+            .unwrap();
+        let gk25 = format!(
+            "FO 2.5 Test 1.0
+KJ GK25 N2000
+TT PA 1 G1
+XY {:.4} {:.4} 12.50 01012020 G1
+-1 KI
+",
+            x, y
+        );
+
+        let mut collection = InvestigationCollection::parse_str(TM35);
+        for content in [gk25.as_str(), HKI] {
+            collection
+                .investigations
+                .extend(InvestigationCollection::parse_str(content).investigations);
+        }
+        collection
+    }
+
+    fn ids<'a>(investigations: impl IntoIterator<Item = &'a Investigation>) -> Vec<&'a str> {
+        investigations
+            .into_iter()
+            .map(|investigation| match &investigation.coordinates.point_id {
+                ParseResult::Parsed(id) => id.as_str(),
+                _ => "",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nearest() {
+        let collection = collection();
+        let index = collection.spatial_index(CoordinateSystem::TM35);
+
+        // The unknown -999999 point, the point without XY and the undefined
+        // municipal system are left out
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.skipped, vec![3, 4, 6]);
+
+        let nearest = index.nearest(6672000.0, 385000.0, 3);
+        assert_eq!(
+            ids(nearest.iter().map(|(investigation, _)| *investigation)),
+            vec!["P1", "G1", "P2"]
+        );
+        assert_eq!(nearest[0].1, 0.0);
+        // G1 is reprojected from GK25, the fixture is rounded to 0.1 mm
+        assert!((nearest[1].1 - 20.0).abs() < 1e-3);
+        assert_eq!(nearest[2].1, 50.0);
+
+        assert_eq!(index.nearest(6672000.0, 385000.0, 10).len(), 4);
+        assert!(index.nearest(6672000.0, 385000.0, 0).is_empty());
+    }
+
+    #[test]
+    fn within_radius_and_bounds() {
+        let collection = collection();
+        let index = collection.spatial_index(CoordinateSystem::TM35);
+
+        // The radius is inclusive
+        let within = index.within_radius(6672000.0, 385000.0, 50.0);
+        assert_eq!(
+            ids(within.iter().map(|(investigation, _)| *investigation)),
+            vec!["P1", "G1", "P2"]
+        );
+        assert_eq!(within[2].1, 50.0);
+        assert_eq!(
+            ids(index
+                .within_radius(6672000.0, 385000.0, 0.0)
+                .into_iter()
+                .map(|(investigation, _)| investigation)),
+            vec!["P1"]
+        );
+        assert!(index.within_radius(6672000.0, 385000.0, -50.0).is_empty());
+        assert!(index.within_radius(0.0, 0.0, 50.0).is_empty());
+
+        // The bounds are inclusive and the corners may be given in any order
+        let mut within = ids(index.within_bounds((6672000.0, 385000.0), (6672030.0, 385040.0)));
+        within.sort();
+        assert_eq!(within, vec!["G1", "P1", "P2"]);
+        let mut swapped = ids(index.within_bounds((6672030.0, 385040.0), (6672000.0, 385000.0)));
+        swapped.sort();
+        assert_eq!(swapped, within);
+        assert_eq!(
+            ids(index.within_bounds((6672100.0, 385000.0), (6672100.0, 385000.0))),
+            vec!["P3"]
+        );
+        assert!(index
+            .within_bounds((6672001.0, 385001.0), (6672029.0, 385019.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn position() {
+        let collection = collection();
+
+        let index = collection.spatial_index(CoordinateSystem::GK25);
+        let (x, y) = index.position(&collection.investigations[1]).unwrap();
+        let (expected_x, expected_y) = Transformer::default()
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::GK25,
+                6672030.0,
+                385040.0,
+            )
+            .unwrap();
+        assert!((x - expected_x).abs() < 1e-6 && (y - expected_y).abs() < 1e-6);
+
+        // Skipped investigations and ones outside the collection have none
+        assert_eq!(index.position(&collection.investigations[3]), None);
+        assert_eq!(index.position(&collection.investigations[0].clone()), None);
+
+        // With the municipal system defined H1 is indexed as well
+        let transformer = Transformer::default()
+            .with_local_system(
+                CoordinateSystem::HKI,
+                LocalSystem::new(CoordinateSystem::GK25, 0.0, 0.0, 1.0, 0.0),
+            )
+            .unwrap();
+        let index = collection.spatial_index_with(CoordinateSystem::GK25, &transformer);
+        assert_eq!(index.skipped, vec![3, 4]);
+        assert_eq!(
+            index.position(&collection.investigations[6]),
+            Some((6672000.0, 25496600.0))
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Alignment, CoordinateSystem, InvestigationCollection, ParseResult, Transformer};
+
+use crate::alignment::StationingAlignment;
+
+impl InvestigationCollection {
+    // Station and offset of every investigation along the alignment, in the
+    // order of `investigations`. None when the investigation has no usable
+    // coordinates or lies beyond the ends of the alignment.
+    pub fn stations(
+        &self,
+        alignment: &Alignment,
+        coordinate_system: CoordinateSystem,
+    ) -> Vec<Option<(f64, f64)>> {
+        self.stations_with(alignment, coordinate_system, &Transformer::default())
+    }
+
+    pub fn stations_with(
+        &self,
+        alignment: &Alignment,
+        coordinate_system: CoordinateSystem,
+        transformer: &Transformer,
+    ) -> Vec<Option<(f64, f64)>> {
+        let alignment = StationingAlignment::new(alignment);
+
+        self.investigations
+            .iter()
+            .map(|investigation| {
+                Self::indexed_position(investigation, &coordinate_system, transformer)
+                    .and_then(|[x, y]| alignment.station_offset(x, y))
+            })
+            .collect()
+    }
+
+    // Fills LN line name, stake and distance from the alignment. Existing
+    // line information is kept unless `overwrite` is set. Returns the number
+    // of investigations updated.
+    pub fn assign_stations(
+        &mut self,
+        alignment: &Alignment,
+        coordinate_system: CoordinateSystem,
+        overwrite: bool,
+    ) -> usize {
+        let stations = self.stations(alignment, coordinate_system);
+        let mut updated = 0;
+
+        for (investigation, station) in self.investigations.iter_mut().zip(stations) {
+            let Some((stake, distance)) = station else {
+                continue;
+            };
+
+            if !overwrite && matches!(investigation.line.stake, ParseResult::Parsed(_)) {
+                continue;
+            }
+
+            investigation.line.name = ParseResult::Parsed(alignment.name.clone());
+            investigation.line.stake = ParseResult::Parsed(stake as f32);
+            investigation.line.distance = ParseResult::Parsed(distance as f32);
+            updated += 1;
+        }
+
+        updated
+    }
+}
@@ -1,11 +1,19 @@
+mod alignment;
+mod export;
 mod infra_file;
 mod investigation;
 mod investigation_aggregator;
 mod investigation_collection;
 mod observation;
 mod parse_result;
+mod transformation;
+
+pub use alignment::{Alignment, AlignmentElement};
+
+pub use export::kml::KmlFolders;
 
 pub use infra_file::{
+    coordinate_analysis::CoordinateAnalysis,
     file_info::FileInfo,
     format::Format,
     spatial::{CoordinateSystem, ElevationSystem, Spatial},
@@ -23,20 +31,57 @@ pub use investigation::{
     organisations::Organisations,
     program::Program,
     record::{Digitized, Record},
+    soil_layer::{SoilLayer, SoilLayerSource},
     standpipe::Standpipe,
     termination::{Termination, TerminationToken},
     work::Work,
-    Investigation,
+    Investigation, ResistanceStatistics,
 };
 
-pub use investigation_collection::InvestigationCollection;
+pub use investigation_collection::{
+    clip::Polygon,
+    cross_section::{CrossSection, ProfileInvestigation, ProfileObservation},
+    line_profile::StakeInconsistency,
+    spatial_index::SpatialIndex,
+    InvestigationCollection,
+};
 
-pub use investigation_aggregator::{HasInvestigations, InvestigationAggregator};
+pub use investigation_aggregator::{
+    density_grid::DensityGrid, HasInvestigations, InvestigationAggregator,
+};
 
-pub use observation::{lab_results::LabResult, observation_values::ObservationValues, Observation};
+pub use observation::{
+    lab_results::LabResult,
+    observation_values::ObservationValues,
+    water_observed::{WaterObserved, WaterObservedToken},
+    Observation,
+};
 
 pub use parse_result::{ParseResult, TryParse};
 
+pub use transformation::{
+    height_grid::HeightGrid,
+    local_system::LocalSystem,
+    triangle_network::{CommonPoint, TriangleNetwork},
+    Transformer,
+};
+
+// Path in the temporary directory that is unique to this test run, so that
+// concurrent tests and test runs never share files
+#[cfg(test)]
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    std::env::temp_dir().join(format!(
+        "inframodel_{}_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        name
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -65,4 +110,53 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_representation() {
+        let parsed: ParseResult<f32> = ParseResult::Parsed(1.5);
+        let fallback: ParseResult<f32> = ParseResult::Fallback("1,5x".to_string());
+        let none: ParseResult<f32> = ParseResult::None;
+
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            r#"{"status":"parsed","value":1.5}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&fallback).unwrap(),
+            r#"{"status":"fallback","value":"1,5x"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&none).unwrap(),
+            r#"{"status":"none"}"#
+        );
+
+        let values = ObservationValues::PI {
+            depth: ParseResult::Parsed(2.0),
+            soil_type: ParseResult::None,
+        };
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            json,
+            r#"{"method":"PI","depth":{"status":"parsed","value":2.0},"soil_type":{"status":"none"}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ObservationValues>(&json).unwrap(),
+            values
+        );
+
+        let investigation = Investigation {
+            method: Method {
+                token: ParseResult::Parsed(MethodToken::PA),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&investigation).unwrap();
+        assert!(json.contains(r#""token":{"status":"parsed","value":"PA"}"#));
+        assert_eq!(
+            serde_json::from_str::<Investigation>(&json).unwrap(),
+            investigation
+        );
+    }
 }
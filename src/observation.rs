@@ -1,16 +1,18 @@
 pub(crate) mod lab_results;
 pub(crate) mod observation_values;
+pub(crate) mod water_observed;
 
-use crate::{ObservationValues, ParseResult};
+use crate::{ObservationValues, ParseResult, WaterObserved};
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Observation {
     pub values: ObservationValues,
     pub notes: Vec<ParseResult<String>>,
     pub free_text: Vec<ParseResult<String>>,
     pub hidden_text: Vec<ParseResult<String>>,
     pub unofficial_soil_type: Vec<ParseResult<String>>,
-    pub water_observed: ParseResult<String>,
+    pub water_observed: ParseResult<WaterObserved>,
 }
 
 impl Observation {
@@ -3,6 +3,8 @@ use crate::{LabResult, ParseResult};
 use chrono::NaiveDate;
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method"))]
 pub enum ObservationValues {
     // TODO: Implement accepted soil type enum
     // TODO: Ever further, if it's not on that enum, then that should be unofficial soil type..
@@ -205,6 +207,108 @@ impl ObservationValues {
         }
     }
 
+    pub(crate) fn get_parsed_elevation(&self) -> Option<f32> {
+        let elevation = match self {
+            ObservationValues::VP { surface_elev, .. }
+            | ObservationValues::VO { surface_elev, .. }
+            | ObservationValues::VK { surface_elev, .. }
+            | ObservationValues::VPK { surface_elev, .. }
+            | ObservationValues::HU { surface_elev, .. } => surface_elev,
+            ObservationValues::PM { elev, .. } => elev,
+            _ => return None,
+        };
+
+        if let ParseResult::Parsed(value) = elevation {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn elevations_mut(&mut self) -> Vec<&mut ParseResult<f32>> {
+        match self {
+            ObservationValues::VP {
+                surface_elev,
+                pipe_top_elev,
+                pipe_bot_elev,
+                ..
+            }
+            | ObservationValues::VO {
+                surface_elev,
+                pipe_top_elev,
+                pipe_bot_elev,
+                ..
+            }
+            | ObservationValues::HU {
+                surface_elev,
+                pipe_top_elev,
+                pipe_bot_elev,
+                ..
+            } => vec![surface_elev, pipe_top_elev, pipe_bot_elev],
+            ObservationValues::VK { surface_elev, .. }
+            | ObservationValues::VPK { surface_elev, .. } => vec![surface_elev],
+            ObservationValues::PM { elev, .. } => vec![elev],
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn get_parsed_depth_interval(&self) -> Option<(f32, f32)> {
+        match self {
+            ObservationValues::KE {
+                start_depth,
+                end_depth,
+            }
+            | ObservationValues::KR {
+                start_depth,
+                end_depth,
+            }
+            | ObservationValues::NO {
+                start_depth,
+                end_depth,
+                ..
+            }
+            | ObservationValues::NE {
+                start_depth,
+                end_depth,
+                ..
+            } => match (start_depth, end_depth) {
+                (ParseResult::Parsed(start), ParseResult::Parsed(end)) => Some((*start, *end)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_parsed_resistance(&self) -> Option<f32> {
+        let resistance = match self {
+            ObservationValues::PA { half_turns, .. } => half_turns.clone().map(|v| v as f32),
+            ObservationValues::LY { hits, .. }
+            | ObservationValues::HE { hits, .. }
+            | ObservationValues::HK { hits, .. } => hits.clone().map(|v| v as f32),
+            ObservationValues::PR {
+                total_resistance, ..
+            }
+            | ObservationValues::CP {
+                total_resistance, ..
+            }
+            | ObservationValues::CU {
+                total_resistance, ..
+            } => total_resistance.clone(),
+            // Only the hammering rows of HP, pressure rows have no hits and
+            // are left out so a sounding never mixes hits and pressure
+            ObservationValues::HP { hits, .. } => hits.clone().map(|v| v as f32),
+            // Seconds per 0.2 m, the only quantity a PO row has
+            ObservationValues::PO { time, .. } => time.clone().map(|v| v as f32),
+            _ => ParseResult::None,
+        };
+
+        if let ParseResult::Parsed(value) = resistance {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn get_parsed_soil_type(&self) -> Option<&String> {
         match self {
             ObservationValues::PA { soil_type, .. }
@@ -220,7 +324,9 @@ impl ObservationValues {
             | ObservationValues::HP { soil_type, .. }
             | ObservationValues::PO { soil_type, .. }
             | ObservationValues::MW { soil_type, .. }
-            | ObservationValues::KO { soil_type, .. } => {
+            | ObservationValues::KO { soil_type, .. }
+            | ObservationValues::NO { soil_type, .. }
+            | ObservationValues::NE { soil_type, .. } => {
                 if let ParseResult::Parsed(ref soil) = soil_type {
                     Some(soil)
                 } else {
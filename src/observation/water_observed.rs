@@ -0,0 +1,70 @@
+use crate::{ParseResult, TryParse};
+
+use std::fmt;
+
+#[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaterObserved {
+    // Depth from the ground surface. Falls back to the depth of the observation row.
+    pub depth: ParseResult<f32>,
+    pub token: ParseResult<WaterObservedToken>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WaterObservedToken {
+    #[default]
+    Unknown,
+    VP, // Finnish: Pohjavesi
+    VO, // Finnish: Orsivesi
+    VA, // Finnish: Arteesinen vesi
+}
+
+impl WaterObserved {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn elevation(&self, start_elevation: f32) -> Option<f32> {
+        match self.depth {
+            ParseResult::Parsed(depth) => Some(start_elevation - depth),
+            _ => None,
+        }
+    }
+}
+
+impl TryParse for WaterObservedToken {
+    fn try_parse(input: &str) -> Result<Self, String> {
+        match input.trim().to_uppercase().as_str() {
+            "VP" => Ok(WaterObservedToken::VP),
+            "VO" => Ok(WaterObservedToken::VO),
+            "VA" => Ok(WaterObservedToken::VA),
+            _ => Err(input.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for WaterObserved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = match &self.token {
+            ParseResult::Parsed(token) => token.to_string(),
+            _ => WaterObservedToken::Unknown.to_string(),
+        };
+        match self.depth {
+            ParseResult::Parsed(depth) => write!(f, "{} observed at {:.2} m", token, depth),
+            _ => write!(f, "{} observed", token),
+        }
+    }
+}
+
+impl fmt::Display for WaterObservedToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token_str = match self {
+            WaterObservedToken::VP => "Groundwater", // Finnish: Pohjavesi
+            WaterObservedToken::VO => "Perched water", // Finnish: Orsivesi
+            WaterObservedToken::VA => "Artesian water", // Finnish: Arteesinen vesi
+            WaterObservedToken::Unknown => "Water",
+        };
+        write!(f, "{}", token_str)
+    }
+}
@@ -2,6 +2,11 @@ use chrono::NaiveDate;
 use std::fmt::{self, Display, Formatter};
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "status", content = "value", rename_all = "snake_case")
+)]
 pub enum ParseResult<T> {
     #[default]
     None,
@@ -34,6 +39,14 @@ impl<T> ParseResult<T> {
         matches!(self, ParseResult::Fallback(_))
     }
 
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> ParseResult<U> {
+        match self {
+            ParseResult::Parsed(value) => ParseResult::Parsed(f(value)),
+            ParseResult::Fallback(original) => ParseResult::Fallback(original),
+            ParseResult::None => ParseResult::None,
+        }
+    }
+
     pub fn unwrap_fallback(self) -> Option<String> {
         if let ParseResult::Fallback(msg) = self {
             Some(msg)
@@ -76,6 +89,13 @@ impl TryParse for f32 {
     }
 }
 
+impl TryParse for f64 {
+    fn try_parse(input: &str) -> Result<Self, String> {
+        let normalized = input.replace(',', ".");
+        normalized.parse::<f64>().map_err(|_| input.to_string())
+    }
+}
+
 impl<T: TryParse> ParseResult<T> {
     pub fn parse(input: &str) -> Self {
         if input == "-" {
@@ -0,0 +1,689 @@
+pub(crate) mod datum;
+pub(crate) mod ellipsoid;
+pub(crate) mod height_grid;
+pub(crate) mod local_system;
+pub(crate) mod projection;
+pub(crate) mod triangle_network;
+pub(crate) mod wkt;
+
+use crate::transformation::{datum::Datum, ellipsoid::Ellipsoid, projection::TransverseMercator};
+use crate::{CoordinateSystem, ElevationSystem, HeightGrid, LocalSystem, TriangleNetwork};
+
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum Plane {
+    // Latitude as x and longitude as y, in degrees
+    Geographic(Datum),
+    Projected(Datum, TransverseMercator),
+}
+
+impl Plane {
+    fn datum(&self) -> Datum {
+        match self {
+            Plane::Geographic(datum) | Plane::Projected(datum, _) => *datum,
+        }
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            Plane::Geographic(_) => (x, y),
+            Plane::Projected(_, projection) => projection.inverse(x, y),
+        }
+    }
+
+    fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        match self {
+            Plane::Geographic(_) => (lat, lon),
+            Plane::Projected(_, projection) => projection.forward(lat, lon),
+        }
+    }
+}
+
+impl CoordinateSystem {
+    pub(crate) fn plane(&self) -> Result<Plane, String> {
+        let kkj = |zone: f64| {
+            Plane::Projected(
+                Datum::Kkj,
+                TransverseMercator::new(
+                    Ellipsoid::HAYFORD,
+                    18.0 + 3.0 * zone,
+                    1.0,
+                    zone * 1_000_000.0 + 500_000.0,
+                ),
+            )
+        };
+        let gk = |meridian: f64| {
+            Plane::Projected(
+                Datum::EurefFin,
+                TransverseMercator::new(
+                    Ellipsoid::GRS80,
+                    meridian,
+                    1.0,
+                    meridian * 1_000_000.0 + 500_000.0,
+                ),
+            )
+        };
+        let tm = |meridian: f64| {
+            Plane::Projected(
+                Datum::EurefFin,
+                TransverseMercator::new(Ellipsoid::GRS80, meridian, 0.9996, 500_000.0),
+            )
+        };
+
+        match self {
+            CoordinateSystem::WGS84 => Ok(Plane::Geographic(Datum::EurefFin)),
+            CoordinateSystem::KKJ0 => Ok(kkj(0.0)),
+            CoordinateSystem::KKJ1 => Ok(kkj(1.0)),
+            CoordinateSystem::KKJ2 => Ok(kkj(2.0)),
+            CoordinateSystem::KKJ3 | CoordinateSystem::YKJ => Ok(kkj(3.0)),
+            CoordinateSystem::KKJ4 => Ok(kkj(4.0)),
+            CoordinateSystem::KKJ5 => Ok(kkj(5.0)),
+            CoordinateSystem::GK19 => Ok(gk(19.0)),
+            CoordinateSystem::GK20 => Ok(gk(20.0)),
+            CoordinateSystem::GK21 => Ok(gk(21.0)),
+            CoordinateSystem::GK22 => Ok(gk(22.0)),
+            CoordinateSystem::GK23 => Ok(gk(23.0)),
+            CoordinateSystem::GK24 => Ok(gk(24.0)),
+            CoordinateSystem::GK25 => Ok(gk(25.0)),
+            CoordinateSystem::GK26 => Ok(gk(26.0)),
+            CoordinateSystem::GK27 => Ok(gk(27.0)),
+            CoordinateSystem::GK28 => Ok(gk(28.0)),
+            CoordinateSystem::GK29 => Ok(gk(29.0)),
+            CoordinateSystem::GK30 => Ok(gk(30.0)),
+            CoordinateSystem::GK31 => Ok(gk(31.0)),
+            CoordinateSystem::TM34 => Ok(tm(21.0)),
+            CoordinateSystem::TM35 => Ok(tm(27.0)),
+            CoordinateSystem::TM36 => Ok(tm(33.0)),
+            // Municipal parameters are not bundled, they come from the
+            // municipality and are registered as local systems
+            CoordinateSystem::HKI | CoordinateSystem::VANTAA | CoordinateSystem::ESPOO => {
+                Err(format!(
+                    "No definition for the municipal system {}, add it with Transformer::with_local_system",
+                    self
+                ))
+            }
+            CoordinateSystem::Unknown => {
+                Err(format!("Transformation from/to {} is not supported", self))
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transformer {
+    // Used for KKJ <-> EUREF-FIN when given, otherwise the Helmert
+    // transformation is used. Points outside the network also fall back to it.
+    // The JHS 154 network is not bundled, read it with TriangleNetwork::from_files.
+    pub triangle_network: Option<TriangleNetwork>,
+    // N60 -> N2000 conversion is only available when a grid is given, e.g.
+    // one resampled from the official NLS conversion surface
+    pub n60_to_n2000: Option<HeightGrid>,
+    // N43 -> N60 conversion is only available when a grid is given
+    pub n43_to_n60: Option<HeightGrid>,
+    // Municipal systems (HKI, VANTAA, ESPOO) are resolved through these, no
+    // municipal parameters are bundled
+    pub local_systems: HashMap<CoordinateSystem, LocalSystem>,
+}
+
+impl Transformer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_triangle_network(network: TriangleNetwork) -> Self {
+        Transformer {
+            triangle_network: Some(network),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_local_system(
+        mut self,
+        system: CoordinateSystem,
+        local_system: LocalSystem,
+    ) -> Result<Self, String> {
+        local_system.base.plane()?;
+        self.local_systems.insert(system, local_system);
+        Ok(self)
+    }
+
+    // Coordinates follow the InfraModel convention: x is northing (or
+    // latitude) and y is easting (or longitude).
+    pub fn transform(
+        &self,
+        source: &CoordinateSystem,
+        target: &CoordinateSystem,
+        x: f64,
+        y: f64,
+    ) -> Result<(f64, f64), String> {
+        if let Some(local) = self.local_systems.get(source) {
+            if source == target {
+                return Ok((x, y));
+            }
+            let (x, y) = local.to_base(x, y);
+            return self.transform(&local.base, target, x, y);
+        }
+
+        if let Some(local) = self.local_systems.get(target) {
+            let (x, y) = self.transform(source, &local.base, x, y)?;
+            return Ok(local.from_base(x, y));
+        }
+
+        let source_plane = source.plane()?;
+        let target_plane = target.plane()?;
+
+        if source == target {
+            return Ok((x, y));
+        }
+
+        let (lat, lon) = source_plane.unproject(x, y);
+        let (source_datum, target_datum) = (source_plane.datum(), target_plane.datum());
+
+        let (lat, lon) = match (source_datum, target_datum) {
+            (Datum::Kkj, Datum::EurefFin) | (Datum::EurefFin, Datum::Kkj) => self
+                .transform_with_network(source_datum, lat, lon)
+                .unwrap_or_else(|| source_datum.convert(target_datum, lat, lon)),
+            _ => (lat, lon),
+        };
+
+        Ok(target_plane.project(lat, lon))
+    }
+
+    // Correction to add to an elevation in `source` to get it in `target`
+    // at the given horizontal position.
+    pub fn elevation_correction(
+        &self,
+        coordinate_system: &CoordinateSystem,
+        x: f64,
+        y: f64,
+        source: &ElevationSystem,
+        target: &ElevationSystem,
+    ) -> Result<f64, String> {
+        if source == target {
+            return Ok(0.0);
+        }
+
+        let (lat, lon) = self.transform(coordinate_system, &CoordinateSystem::WGS84, x, y)?;
+
+        Ok(self.to_n2000(coordinate_system, source, lat, lon)?
+            - self.to_n2000(coordinate_system, target, lat, lon)?)
+    }
+
+    fn to_n2000(
+        &self,
+        coordinate_system: &CoordinateSystem,
+        system: &ElevationSystem,
+        lat: f64,
+        lon: f64,
+    ) -> Result<f64, String> {
+        match system {
+            ElevationSystem::N2000 => Ok(0.0),
+            ElevationSystem::N60 => self.n60_correction(lat, lon),
+            ElevationSystem::N43 => {
+                Ok(self.n43_correction(lat, lon)? + self.n60_correction(lat, lon)?)
+            }
+            ElevationSystem::NN | ElevationSystem::LN => {
+                match self.local_systems.get(coordinate_system) {
+                    Some(LocalSystem {
+                        height_base: Some(base),
+                        height_offset,
+                        ..
+                    }) if !matches!(base, ElevationSystem::NN | ElevationSystem::LN) => {
+                        Ok(height_offset + self.to_n2000(coordinate_system, base, lat, lon)?)
+                    }
+                    _ => Err(format!(
+                        "No local height definition for {} in {}",
+                        system, coordinate_system
+                    )),
+                }
+            }
+            ElevationSystem::Unknown => {
+                Err(format!("Conversion from/to {} is not supported", system))
+            }
+        }
+    }
+
+    fn n60_correction(&self, lat: f64, lon: f64) -> Result<f64, String> {
+        self.n60_to_n2000
+            .as_ref()
+            .ok_or_else(|| "No N60 -> N2000 conversion grid given".to_string())?
+            .correction(lat, lon)
+            .ok_or_else(|| "Position is outside the N60 -> N2000 conversion grid".to_string())
+    }
+
+    fn n43_correction(&self, lat: f64, lon: f64) -> Result<f64, String> {
+        self.n43_to_n60
+            .as_ref()
+            .ok_or_else(|| "No N43 -> N60 conversion grid given".to_string())?
+            .correction(lat, lon)
+            .ok_or_else(|| "Position is outside the N43 -> N60 conversion grid".to_string())
+    }
+
+    fn transform_with_network(&self, source: Datum, lat: f64, lon: f64) -> Option<(f64, f64)> {
+        let network = self.triangle_network.as_ref()?;
+        let ykj = CoordinateSystem::YKJ.plane().ok()?;
+        let tm35 = CoordinateSystem::TM35.plane().ok()?;
+
+        match source {
+            Datum::Kkj => {
+                let (x, y) = ykj.project(lat, lon);
+                let (x, y) = network.kkj_to_etrs(x, y)?;
+                Some(tm35.unproject(x, y))
+            }
+            Datum::EurefFin => {
+                let (x, y) = tm35.project(lat, lon);
+                let (x, y) = network.etrs_to_kkj(x, y)?;
+                Some(ykj.unproject(x, y))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64), tolerance: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() < tolerance && (actual.1 - expected.1).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn projections() {
+        let transformer = Transformer::new();
+
+        // Control values on the GRS80 ellipsoid
+        let points = [
+            (CoordinateSystem::TM35, 60.0, 27.0, 6651411.190, 500000.000),
+            (CoordinateSystem::TM35, 60.5, 24.5, 6709705.098, 362687.741),
+            (
+                CoordinateSystem::GK25,
+                60.17,
+                24.94,
+                6673014.666,
+                25496669.192,
+            ),
+        ];
+
+        for (system, lat, lon, x, y) in points {
+            let projected = transformer
+                .transform(&CoordinateSystem::WGS84, &system, lat, lon)
+                .unwrap();
+            assert_close(projected, (x, y), 0.002);
+
+            let geographic = transformer
+                .transform(&system, &CoordinateSystem::WGS84, x, y)
+                .unwrap();
+            assert_close(geographic, (lat, lon), 1e-7);
+        }
+    }
+
+    // The expected YKJ values are computed independently from the JHS 153
+    // parameters (Krüger series and the position vector Helmert formula), so
+    // this checks the implementation. The Helmert transformation itself only
+    // reproduces the official KKJ coordinates to about one metre.
+    #[test]
+    fn kkj_helmert() {
+        let transformer = Transformer::new();
+        let tm35 = (6672000.0, 385800.0);
+
+        let ykj = transformer
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::YKJ,
+                tm35.0,
+                tm35.1,
+            )
+            .unwrap();
+        assert_close(ykj, (6674801.942, 3385923.563), 0.002);
+
+        let kkj2 = transformer
+            .transform(
+                &CoordinateSystem::YKJ,
+                &CoordinateSystem::KKJ2,
+                ykj.0,
+                ykj.1,
+            )
+            .unwrap();
+        let back = transformer
+            .transform(
+                &CoordinateSystem::KKJ2,
+                &CoordinateSystem::TM35,
+                kkj2.0,
+                kkj2.1,
+            )
+            .unwrap();
+        assert_close(back, tm35, 0.001);
+    }
+
+    // Synthetic three point network for the triangle-wise affine
+    // interpolation, the JHS 154 network is read with TriangleNetwork::from_files
+    #[test]
+    fn triangle_network_interpolation() {
+        let points = "\
+            A 6600000 3300000 6597000 300000
+            B 6600000 3500000 6597100 500000
+            C 6800000 3400000 6797200 400000
+        ";
+        let network = TriangleNetwork::parse(points, "A B C").unwrap();
+
+        // Common points map exactly onto each other
+        for point in &network.points {
+            assert_close(
+                network.kkj_to_etrs(point.kkj.0, point.kkj.1).unwrap(),
+                point.etrs,
+                1e-6,
+            );
+            assert_close(
+                network.etrs_to_kkj(point.etrs.0, point.etrs.1).unwrap(),
+                point.kkj,
+                1e-6,
+            );
+        }
+
+        let transformer = Transformer::with_triangle_network(network);
+
+        // Midway between A and B, and a quarter of the way towards C
+        let tm35 = transformer
+            .transform(
+                &CoordinateSystem::YKJ,
+                &CoordinateSystem::TM35,
+                6650000.0,
+                3400000.0,
+            )
+            .unwrap();
+        assert_close(tm35, (6647087.5, 400000.0), 0.001);
+
+        let ykj = transformer
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::YKJ,
+                tm35.0,
+                tm35.1,
+            )
+            .unwrap();
+        assert_close(ykj, (6650000.0, 3400000.0), 0.001);
+
+        // Outside the network the Helmert transformation is used
+        let outside = (6900000.0, 3400000.0);
+        let expected = Transformer::new()
+            .transform(
+                &CoordinateSystem::YKJ,
+                &CoordinateSystem::TM35,
+                outside.0,
+                outside.1,
+            )
+            .unwrap();
+        let fallback = transformer
+            .transform(
+                &CoordinateSystem::YKJ,
+                &CoordinateSystem::TM35,
+                outside.0,
+                outside.1,
+            )
+            .unwrap();
+        assert_close(fallback, expected, 1e-6);
+    }
+
+    #[test]
+    fn transformed_coordinates_precision() {
+        let coordinates = Coordinates {
+            x: ParseResult::Parsed(6672123.456),
+            y: ParseResult::Parsed(385123.789),
+            ..Default::default()
+        };
+        let spatial = Spatial {
+            coordinate_system: ParseResult::Parsed(CoordinateSystem::TM35),
+            elevation_system: ParseResult::None,
+        };
+
+        let gk25 = coordinates
+            .transform_to(&spatial, CoordinateSystem::GK25)
+            .unwrap();
+        let (ParseResult::Parsed(x), ParseResult::Parsed(y)) = (gk25.x, gk25.y) else {
+            panic!("coordinates were not transformed");
+        };
+
+        let back = Transformer::new()
+            .transform(&CoordinateSystem::GK25, &CoordinateSystem::TM35, x, y)
+            .unwrap();
+        assert_close(back, (6672123.456, 385123.789), 0.001);
+    }
+
+    #[test]
+    fn elevation_correction() {
+        let grid = HeightGrid::parse("60.0 24.0 1.0 1.0 2 2\n0.1 0.2\n0.3 0.4").unwrap();
+        assert!((grid.correction(60.5, 24.5).unwrap() - 0.25).abs() < 1e-9);
+        assert!(grid.correction(62.0, 24.5).is_none());
+
+        let transformer = Transformer {
+            n60_to_n2000: Some(grid),
+            ..Default::default()
+        };
+        let (x, y) = transformer
+            .transform(
+                &CoordinateSystem::WGS84,
+                &CoordinateSystem::TM35,
+                60.5,
+                24.5,
+            )
+            .unwrap();
+
+        let n60_to_n2000 = transformer
+            .elevation_correction(
+                &CoordinateSystem::TM35,
+                x,
+                y,
+                &ElevationSystem::N60,
+                &ElevationSystem::N2000,
+            )
+            .unwrap();
+        assert!((n60_to_n2000 - 0.25).abs() < 1e-6);
+
+        let n2000_to_n60 = transformer
+            .elevation_correction(
+                &CoordinateSystem::TM35,
+                x,
+                y,
+                &ElevationSystem::N2000,
+                &ElevationSystem::N60,
+            )
+            .unwrap();
+        assert!((n2000_to_n60 + 0.25).abs() < 1e-6);
+
+        // No grid is bundled, N60 heights need one from the caller
+        assert!(Transformer::new()
+            .elevation_correction(
+                &CoordinateSystem::TM35,
+                x,
+                y,
+                &ElevationSystem::N60,
+                &ElevationSystem::N2000,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn local_system() {
+        let defined = LocalSystem::new(
+            CoordinateSystem::GK25,
+            6_600_000.0,
+            25_400_000.0,
+            1.0001,
+            0.01,
+        )
+        .with_height(ElevationSystem::N2000, 0.5);
+        let local_points = [
+            (10_000.0, 20_000.0),
+            (70_000.0, 15_000.0),
+            (40_000.0, 90_000.0),
+        ];
+        let base_points: Vec<(f64, f64)> = local_points
+            .iter()
+            .map(|(x, y)| defined.to_base(*x, *y))
+            .collect();
+
+        let fitted =
+            LocalSystem::from_common_points(CoordinateSystem::GK25, &local_points, &base_points)
+                .unwrap()
+                .with_height(ElevationSystem::N2000, 0.5);
+        assert_close((fitted.a, fitted.b), (defined.a, defined.b), 1e-12);
+
+        let transformer = Transformer::new()
+            .with_local_system(CoordinateSystem::HKI, fitted)
+            .unwrap();
+
+        let tm35 = transformer
+            .transform(
+                &CoordinateSystem::HKI,
+                &CoordinateSystem::TM35,
+                50_000.0,
+                50_000.0,
+            )
+            .unwrap();
+        let hki = transformer
+            .transform(
+                &CoordinateSystem::TM35,
+                &CoordinateSystem::HKI,
+                tm35.0,
+                tm35.1,
+            )
+            .unwrap();
+        assert_close(hki, (50_000.0, 50_000.0), 0.001);
+
+        let correction = transformer
+            .elevation_correction(
+                &CoordinateSystem::HKI,
+                50_000.0,
+                50_000.0,
+                &ElevationSystem::LN,
+                &ElevationSystem::N2000,
+            )
+            .unwrap();
+        assert!((correction - 0.5).abs() < 1e-9);
+
+        let error = Transformer::new()
+            .transform(
+                &CoordinateSystem::VANTAA,
+                &CoordinateSystem::TM35,
+                50_000.0,
+                50_000.0,
+            )
+            .unwrap_err();
+        assert!(error.contains("with_local_system"));
+    }
+
+    #[test]
+    fn crs_identifiers() {
+        let spatial = Spatial {
+            coordinate_system: ParseResult::parse("EPSG:3879"),
+            elevation_system: ParseResult::parse("EPSG:3900"),
+        };
+        assert_eq!(
+            spatial.coordinate_system,
+            ParseResult::Parsed(CoordinateSystem::GK25)
+        );
+        assert_eq!(
+            spatial.elevation_system,
+            ParseResult::Parsed(ElevationSystem::N2000)
+        );
+        assert_eq!(spatial.epsg(), Some("EPSG:3879+3900".to_string()));
+
+        let wkt = spatial.to_wkt().unwrap();
+        assert!(wkt.starts_with("COMPD_CS["));
+        assert!(wkt.contains("PARAMETER[\"false_easting\",25500000]"));
+
+        assert_eq!(
+            ParseResult::<CoordinateSystem>::parse("ETRS-TM36"),
+            ParseResult::Parsed(CoordinateSystem::TM36)
+        );
+        assert!(ParseResult::<CoordinateSystem>::parse("EPSG:9999").is_fallback());
+    }
+
+    #[test]
+    fn epsg_codes() {
+        let gk = [
+            CoordinateSystem::GK19,
+            CoordinateSystem::GK20,
+            CoordinateSystem::GK21,
+            CoordinateSystem::GK22,
+            CoordinateSystem::GK23,
+            CoordinateSystem::GK24,
+            CoordinateSystem::GK25,
+            CoordinateSystem::GK26,
+            CoordinateSystem::GK27,
+            CoordinateSystem::GK28,
+            CoordinateSystem::GK29,
+            CoordinateSystem::GK30,
+            CoordinateSystem::GK31,
+        ];
+        for (index, system) in gk.iter().enumerate() {
+            let (etrs_gk, gk_fin) = (3126 + index as u32, 3873 + index as u32);
+            // The 500 km false easting of ETRS-GKnnFIN does not match the zone
+            assert_eq!(CoordinateSystem::from_epsg(etrs_gk), None);
+            assert!(
+                ParseResult::<CoordinateSystem>::parse(&format!("EPSG:{}", etrs_gk)).is_fallback()
+            );
+            assert_eq!(CoordinateSystem::from_epsg(gk_fin).as_ref(), Some(system));
+            assert_eq!(system.epsg(), Some(gk_fin));
+        }
+
+        let others = [
+            (CoordinateSystem::WGS84, 4326),
+            (CoordinateSystem::KKJ0, 3386),
+            (CoordinateSystem::KKJ1, 2391),
+            (CoordinateSystem::KKJ2, 2392),
+            (CoordinateSystem::YKJ, 2393),
+            (CoordinateSystem::KKJ4, 2394),
+            (CoordinateSystem::KKJ5, 3387),
+            (CoordinateSystem::TM34, 25834),
+            (CoordinateSystem::TM35, 3067),
+            (CoordinateSystem::TM36, 25836),
+        ];
+        for (system, code) in others {
+            assert_eq!(system.epsg(), Some(code));
+            assert_eq!(CoordinateSystem::from_epsg(code), Some(system));
+        }
+        assert_eq!(CoordinateSystem::KKJ3.epsg(), Some(2393));
+        assert_eq!(
+            CoordinateSystem::from_epsg(25835),
+            Some(CoordinateSystem::TM35)
+        );
+
+        // Every accepted code maps to a system that maps back to an
+        // equivalent code and keeps coordinates in place
+        let transformer = Transformer::default();
+        for code in 0..=32767 {
+            let Some(system) = CoordinateSystem::from_epsg(code) else {
+                continue;
+            };
+            let epsg = system.epsg().unwrap();
+            assert_eq!(CoordinateSystem::from_epsg(epsg), Some(system.clone()));
+
+            let (lat, lon) = (60.2, 24.9);
+            let (x, y) = transformer
+                .transform(&CoordinateSystem::WGS84, &system, lat, lon)
+                .unwrap();
+            let (back_lat, back_lon) = transformer
+                .transform(&system, &CoordinateSystem::WGS84, x, y)
+                .unwrap();
+            assert!((back_lat - lat).abs() < 1e-8 && (back_lon - lon).abs() < 1e-8);
+        }
+
+        for (system, code) in [
+            (ElevationSystem::N2000, 3900),
+            (ElevationSystem::N60, 5717),
+            (ElevationSystem::N43, 8675),
+        ] {
+            assert_eq!(system.epsg(), Some(code));
+            assert_eq!(ElevationSystem::from_epsg(code), Some(system.clone()));
+            assert!(system.to_wkt().unwrap().contains(&format!("\"{}\"", code)));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::transformation::ellipsoid::Ellipsoid;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Datum {
+    // ETRS89 realisation EUREF-FIN. WGS84 is treated as equal to it, the
+    // difference being well below the accuracy of ground investigation data.
+    EurefFin,
+    Kkj,
+}
+
+// Seven parameter Helmert transformation, position vector convention
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Helmert {
+    pub translation: [f64; 3],
+    // Rotations in arc seconds
+    pub rotation: [f64; 3],
+    // Scale correction in ppm
+    pub scale: f64,
+}
+
+impl Helmert {
+    // KKJ to EUREF-FIN (JHS 153), accuracy about one metre
+    pub const KKJ_TO_EUREF_FIN: Helmert = Helmert {
+        translation: [-96.062, -82.428, -121.753],
+        rotation: [4.801, 0.345, -1.376],
+        scale: 1.496,
+    };
+
+    pub fn apply(&self, xyz: [f64; 3]) -> [f64; 3] {
+        let [x, y, z] = xyz;
+        let [rx, ry, rz] = self.rotation.map(|r| (r / 3600.0).to_radians());
+        let m = 1.0 + self.scale * 1e-6;
+
+        [
+            self.translation[0] + m * (x - rz * y + ry * z),
+            self.translation[1] + m * (rz * x + y - rx * z),
+            self.translation[2] + m * (-ry * x + rx * y + z),
+        ]
+    }
+
+    pub fn apply_inverse(&self, xyz: [f64; 3]) -> [f64; 3] {
+        let mut result = xyz;
+
+        for _ in 0..3 {
+            let forward = self.apply(result);
+            for i in 0..3 {
+                result[i] -= forward[i] - xyz[i];
+            }
+        }
+
+        result
+    }
+}
+
+impl Datum {
+    pub fn ellipsoid(&self) -> Ellipsoid {
+        match self {
+            Datum::EurefFin => Ellipsoid::GRS80,
+            Datum::Kkj => Ellipsoid::HAYFORD,
+        }
+    }
+
+    // Geodetic coordinates in degrees from this datum to the target datum
+    pub fn convert(&self, target: Datum, lat: f64, lon: f64) -> (f64, f64) {
+        let xyz = self
+            .ellipsoid()
+            .to_geocentric(lat.to_radians(), lon.to_radians(), 0.0);
+
+        let xyz = match (self, target) {
+            (Datum::Kkj, Datum::EurefFin) => Helmert::KKJ_TO_EUREF_FIN.apply(xyz),
+            (Datum::EurefFin, Datum::Kkj) => Helmert::KKJ_TO_EUREF_FIN.apply_inverse(xyz),
+            _ => return (lat, lon),
+        };
+
+        let (lat, lon, _) = target.ellipsoid().to_geodetic(xyz);
+
+        (lat.to_degrees(), lon.to_degrees())
+    }
+}
@@ -0,0 +1,58 @@
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Ellipsoid {
+    pub a: f64,
+    pub f: f64,
+}
+
+impl Ellipsoid {
+    pub const GRS80: Ellipsoid = Ellipsoid {
+        a: 6_378_137.0,
+        f: 1.0 / 298.257_222_101,
+    };
+
+    // International 1924 (Hayford), used by KKJ
+    pub const HAYFORD: Ellipsoid = Ellipsoid {
+        a: 6_378_388.0,
+        f: 1.0 / 297.0,
+    };
+
+    pub fn e2(&self) -> f64 {
+        self.f * (2.0 - self.f)
+    }
+
+    pub fn to_geocentric(self, lat: f64, lon: f64, h: f64) -> [f64; 3] {
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let (sin_lon, cos_lon) = lon.sin_cos();
+        let n = self.a / (1.0 - self.e2() * sin_lat * sin_lat).sqrt();
+
+        [
+            (n + h) * cos_lat * cos_lon,
+            (n + h) * cos_lat * sin_lon,
+            (n * (1.0 - self.e2()) + h) * sin_lat,
+        ]
+    }
+
+    pub fn to_geodetic(self, xyz: [f64; 3]) -> (f64, f64, f64) {
+        let [x, y, z] = xyz;
+        let e2 = self.e2();
+        let p = (x * x + y * y).sqrt();
+        let lon = y.atan2(x);
+
+        let mut lat = z.atan2(p * (1.0 - e2));
+        let mut h = 0.0;
+
+        for _ in 0..10 {
+            let sin_lat = lat.sin();
+            let n = self.a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+            h = p / lat.cos() - n;
+            let next = z.atan2(p * (1.0 - e2 * n / (n + h)));
+            let converged = (next - lat).abs() < 1e-14;
+            lat = next;
+            if converged {
+                break;
+            }
+        }
+
+        (lat, lon, h)
+    }
+}
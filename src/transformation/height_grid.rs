@@ -0,0 +1,89 @@
+use std::fs;
+
+// Regular latitude/longitude grid of height corrections in metres, sampled
+// with bilinear interpolation.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeightGrid {
+    pub lat_min: f64,
+    pub lon_min: f64,
+    pub lat_step: f64,
+    pub lon_step: f64,
+    pub rows: usize,
+    pub cols: usize,
+    // Row-major, rows from south to north
+    pub values: Vec<f64>,
+}
+
+impl HeightGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Header "lat_min lon_min lat_step lon_step rows cols" followed by the
+    // values. Lines starting with '#' are ignored.
+    pub fn parse(input: &str) -> Result<HeightGrid, String> {
+        let mut tokens = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .flat_map(str::split_whitespace);
+
+        let mut next = |name: &str| -> Result<f64, String> {
+            let raw = tokens
+                .next()
+                .ok_or_else(|| format!("Height grid is missing {}", name))?;
+            raw.replace(',', ".")
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid height grid value '{}'", raw))
+        };
+
+        let mut grid = HeightGrid {
+            lat_min: next("lat_min")?,
+            lon_min: next("lon_min")?,
+            lat_step: next("lat_step")?,
+            lon_step: next("lon_step")?,
+            rows: next("rows")? as usize,
+            cols: next("cols")? as usize,
+            values: Vec::new(),
+        };
+
+        if grid.rows < 2 || grid.cols < 2 || grid.lat_step <= 0.0 || grid.lon_step <= 0.0 {
+            return Err("Invalid height grid dimensions".to_string());
+        }
+
+        for _ in 0..grid.rows * grid.cols {
+            grid.values.push(next("values")?);
+        }
+
+        Ok(grid)
+    }
+
+    pub fn from_file(path: &str) -> Result<HeightGrid, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+        Self::parse(&content)
+    }
+
+    pub fn correction(&self, lat: f64, lon: f64) -> Option<f64> {
+        let row = (lat - self.lat_min) / self.lat_step;
+        let col = (lon - self.lon_min) / self.lon_step;
+
+        if row < 0.0 || col < 0.0 || row > (self.rows - 1) as f64 || col > (self.cols - 1) as f64 {
+            return None;
+        }
+
+        let r0 = (row.floor() as usize).min(self.rows - 2);
+        let c0 = (col.floor() as usize).min(self.cols - 2);
+        let (dr, dc) = (row - r0 as f64, col - c0 as f64);
+
+        let value = |r: usize, c: usize| self.values[r * self.cols + c];
+
+        Some(
+            value(r0, c0) * (1.0 - dr) * (1.0 - dc)
+                + value(r0, c0 + 1) * (1.0 - dr) * dc
+                + value(r0 + 1, c0) * dr * (1.0 - dc)
+                + value(r0 + 1, c0 + 1) * dr * dc,
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{CoordinateSystem, ElevationSystem};
+
+// Municipal or other local system defined as a plane similarity
+// transformation to a base system:
+//   x_base = x0 + a * x - b * y
+//   y_base = y0 + b * x + a * y
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalSystem {
+    pub base: CoordinateSystem,
+    pub x0: f64,
+    pub y0: f64,
+    pub a: f64,
+    pub b: f64,
+    // Local height system, height in `height_base` = local height + `height_offset`
+    pub height_base: Option<ElevationSystem>,
+    pub height_offset: f64,
+}
+
+impl LocalSystem {
+    pub fn new(base: CoordinateSystem, x0: f64, y0: f64, scale: f64, rotation: f64) -> Self {
+        LocalSystem {
+            base,
+            x0,
+            y0,
+            a: scale * rotation.cos(),
+            b: scale * rotation.sin(),
+            height_base: None,
+            height_offset: 0.0,
+        }
+    }
+
+    pub fn with_height(mut self, height_base: ElevationSystem, height_offset: f64) -> Self {
+        self.height_base = Some(height_base);
+        self.height_offset = height_offset;
+        self
+    }
+
+    // Least squares fit from common points known in both the local and the base system
+    pub fn from_common_points(
+        base: CoordinateSystem,
+        local_points: &[(f64, f64)],
+        base_points: &[(f64, f64)],
+    ) -> Result<LocalSystem, String> {
+        if local_points.len() != base_points.len() {
+            return Err("Common point lists differ in length".to_string());
+        }
+
+        if local_points.len() < 2 {
+            return Err("At least two common points are needed".to_string());
+        }
+
+        let points: Vec<_> = local_points.iter().zip(base_points).collect();
+
+        let count = points.len() as f64;
+        let mean = |values: &[(f64, f64)]| {
+            let (sx, sy) = values
+                .iter()
+                .fold((0.0, 0.0), |acc, p| (acc.0 + p.0, acc.1 + p.1));
+            (sx / count, sy / count)
+        };
+        let (lx, ly) = mean(local_points);
+        let (bx, by) = mean(base_points);
+
+        let (mut sa, mut sb, mut norm) = (0.0, 0.0, 0.0);
+        for ((x, y), (xb, yb)) in points {
+            let (dx, dy) = (x - lx, y - ly);
+            let (dxb, dyb) = (xb - bx, yb - by);
+            sa += dx * dxb + dy * dyb;
+            sb += dx * dyb - dy * dxb;
+            norm += dx * dx + dy * dy;
+        }
+
+        if norm < f64::EPSILON {
+            return Err("Common points must not coincide".to_string());
+        }
+
+        let (a, b) = (sa / norm, sb / norm);
+
+        Ok(LocalSystem {
+            base,
+            x0: bx - a * lx + b * ly,
+            y0: by - b * lx - a * ly,
+            a,
+            b,
+            height_base: None,
+            height_offset: 0.0,
+        })
+    }
+
+    pub fn to_base(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.x0 + self.a * x - self.b * y,
+            self.y0 + self.b * x + self.a * y,
+        )
+    }
+
+    pub fn from_base(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = (x - self.x0, y - self.y0);
+        let norm = self.a * self.a + self.b * self.b;
+        (
+            (self.a * dx + self.b * dy) / norm,
+            (-self.b * dx + self.a * dy) / norm,
+        )
+    }
+}
@@ -0,0 +1,137 @@
+use crate::transformation::ellipsoid::Ellipsoid;
+
+// Transverse Mercator using the 6th order Krüger series, accurate to well
+// below a millimetre within the zone widths used in Finland.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TransverseMercator {
+    pub ellipsoid: Ellipsoid,
+    pub central_meridian: f64,
+    pub scale: f64,
+    pub false_easting: f64,
+    pub false_northing: f64,
+}
+
+impl TransverseMercator {
+    pub fn new(
+        ellipsoid: Ellipsoid,
+        central_meridian: f64,
+        scale: f64,
+        false_easting: f64,
+    ) -> Self {
+        TransverseMercator {
+            ellipsoid,
+            central_meridian,
+            scale,
+            false_easting,
+            false_northing: 0.0,
+        }
+    }
+
+    fn n(&self) -> f64 {
+        self.ellipsoid.f / (2.0 - self.ellipsoid.f)
+    }
+
+    fn rectifying_radius(&self) -> f64 {
+        let n = self.n();
+        let n2 = n * n;
+        self.ellipsoid.a / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0 + n2 * n2 * n2 / 256.0)
+    }
+
+    fn alpha(&self) -> [f64; 6] {
+        let n = self.n();
+        let (n2, n3, n4, n5, n6) = (n.powi(2), n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+        [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0 - 127.0 * n5 / 288.0
+                + 7891.0 * n6 / 37800.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0 + 281.0 * n5 / 630.0
+                - 1983433.0 * n6 / 1935360.0,
+            61.0 * n3 / 240.0 - 103.0 * n4 / 140.0
+                + 15061.0 * n5 / 26880.0
+                + 167603.0 * n6 / 181440.0,
+            49561.0 * n4 / 161280.0 - 179.0 * n5 / 168.0 + 6601661.0 * n6 / 7257600.0,
+            34729.0 * n5 / 80640.0 - 3418889.0 * n6 / 1995840.0,
+            212378941.0 * n6 / 319334400.0,
+        ]
+    }
+
+    fn beta(&self) -> [f64; 6] {
+        let n = self.n();
+        let (n2, n3, n4, n5, n6) = (n.powi(2), n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+        [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0 - 81.0 * n5 / 512.0
+                + 96199.0 * n6 / 604800.0,
+            n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0 + 46.0 * n5 / 105.0
+                - 1118711.0 * n6 / 3870720.0,
+            17.0 * n3 / 480.0 - 37.0 * n4 / 840.0 - 209.0 * n5 / 4480.0 + 5569.0 * n6 / 90720.0,
+            4397.0 * n4 / 161280.0 - 11.0 * n5 / 504.0 - 830251.0 * n6 / 7257600.0,
+            4583.0 * n5 / 161280.0 - 108847.0 * n6 / 3991680.0,
+            20648693.0 * n6 / 638668800.0,
+        ]
+    }
+
+    // Geodetic latitude and longitude in degrees to (northing, easting)
+    pub fn forward(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let e = self.ellipsoid.e2().sqrt();
+        let lat = lat.to_radians();
+        let d_lon = (lon - self.central_meridian).to_radians();
+
+        let tau = lat.tan();
+        let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        let tau_conformal = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+
+        let xi_prime = tau_conformal.atan2(d_lon.cos());
+        let eta_prime =
+            (d_lon.sin() / (tau_conformal * tau_conformal + d_lon.cos().powi(2)).sqrt()).asinh();
+
+        let mut xi = xi_prime;
+        let mut eta = eta_prime;
+        for (j, alpha) in self.alpha().iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi_prime).sin() * (k * eta_prime).cosh();
+            eta += alpha * (k * xi_prime).cos() * (k * eta_prime).sinh();
+        }
+
+        let a = self.scale * self.rectifying_radius();
+        (self.false_northing + a * xi, self.false_easting + a * eta)
+    }
+
+    // (northing, easting) to geodetic latitude and longitude in degrees
+    pub fn inverse(&self, northing: f64, easting: f64) -> (f64, f64) {
+        let e2 = self.ellipsoid.e2();
+        let e = e2.sqrt();
+        let a = self.scale * self.rectifying_radius();
+
+        let xi = (northing - self.false_northing) / a;
+        let eta = (easting - self.false_easting) / a;
+
+        let mut xi_prime = xi;
+        let mut eta_prime = eta;
+        for (j, beta) in self.beta().iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let tau_conformal =
+            xi_prime.sin() / (eta_prime.sinh().powi(2) + xi_prime.cos().powi(2)).sqrt();
+        let d_lon = eta_prime.sinh().atan2(xi_prime.cos());
+
+        let mut tau = tau_conformal;
+        for _ in 0..10 {
+            let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+            let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+            let delta = (tau_conformal - tau_i) / (1.0 + tau_i * tau_i).sqrt()
+                * (1.0 + (1.0 - e2) * tau * tau)
+                / ((1.0 - e2) * (1.0 + tau * tau).sqrt());
+            tau += delta;
+            if delta.abs() < 1e-14 {
+                break;
+            }
+        }
+
+        (
+            tau.atan().to_degrees(),
+            self.central_meridian + d_lon.to_degrees(),
+        )
+    }
+}
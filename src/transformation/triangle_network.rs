@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::fs;
+
+// Triangle-wise affine transformation between KKJ (YKJ) and ETRS-TM35FIN as
+// defined in JHS 154. Every triangle defines its own affine transformation
+// from the three common points, which makes the transformation continuous
+// across triangle edges.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TriangleNetwork {
+    pub points: Vec<CommonPoint>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommonPoint {
+    // YKJ (KKJ zone 3) coordinates
+    pub kkj: (f64, f64),
+    // ETRS-TM35FIN coordinates
+    pub etrs: (f64, f64),
+}
+
+impl TriangleNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Points: "id x_ykj y_ykj x_tm35 y_tm35" per line, triangles: "id1 id2 id3"
+    // per line, where x is northing and y is easting. Lines starting with '#'
+    // are ignored.
+    pub fn parse(points: &str, triangles: &str) -> Result<TriangleNetwork, String> {
+        let mut network = TriangleNetwork::new();
+        let mut ids: HashMap<String, usize> = HashMap::new();
+
+        for line in Self::data_lines(points) {
+            let params: Vec<&str> = line.split_whitespace().collect();
+            if params.len() < 5 {
+                return Err(format!("Invalid common point line: '{}'", line));
+            }
+
+            let values = params[1..5]
+                .iter()
+                .map(|v| v.replace(',', ".").parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| format!("Invalid common point line: '{}'", line))?;
+
+            ids.insert(params[0].to_string(), network.points.len());
+            network.points.push(CommonPoint {
+                kkj: (values[0], values[1]),
+                etrs: (values[2], values[3]),
+            });
+        }
+
+        for line in Self::data_lines(triangles) {
+            let params: Vec<&str> = line.split_whitespace().collect();
+            if params.len() < 3 {
+                return Err(format!("Invalid triangle line: '{}'", line));
+            }
+
+            let mut triangle = [0; 3];
+            for (i, id) in params[params.len() - 3..].iter().enumerate() {
+                triangle[i] = *ids
+                    .get(*id)
+                    .ok_or_else(|| format!("Unknown common point '{}' in triangle", id))?;
+            }
+            network.triangles.push(triangle);
+        }
+
+        Ok(network)
+    }
+
+    pub fn from_files(points_path: &str, triangles_path: &str) -> Result<TriangleNetwork, String> {
+        let points = fs::read_to_string(points_path)
+            .map_err(|e| format!("Failed to read file '{}': {}", points_path, e))?;
+        let triangles = fs::read_to_string(triangles_path)
+            .map_err(|e| format!("Failed to read file '{}': {}", triangles_path, e))?;
+        Self::parse(&points, &triangles)
+    }
+
+    pub fn kkj_to_etrs(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.transform(x, y, |p| p.kkj, |p| p.etrs)
+    }
+
+    pub fn etrs_to_kkj(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.transform(x, y, |p| p.etrs, |p| p.kkj)
+    }
+
+    fn transform(
+        &self,
+        x: f64,
+        y: f64,
+        source: impl Fn(&CommonPoint) -> (f64, f64),
+        target: impl Fn(&CommonPoint) -> (f64, f64),
+    ) -> Option<(f64, f64)> {
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|i| &self.points[i]);
+            let (w_a, w_b, w_c) = Self::barycentric((x, y), source(a), source(b), source(c))?;
+
+            if w_a >= -1e-9 && w_b >= -1e-9 && w_c >= -1e-9 {
+                let (ta, tb, tc) = (target(a), target(b), target(c));
+                return Some((
+                    w_a * ta.0 + w_b * tb.0 + w_c * tc.0,
+                    w_a * ta.1 + w_b * tb.1 + w_c * tc.1,
+                ));
+            }
+        }
+
+        None
+    }
+
+    fn barycentric(
+        p: (f64, f64),
+        a: (f64, f64),
+        b: (f64, f64),
+        c: (f64, f64),
+    ) -> Option<(f64, f64, f64)> {
+        let det = (b.1 - c.1) * (a.0 - c.0) + (c.0 - b.0) * (a.1 - c.1);
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let w_a = ((b.1 - c.1) * (p.0 - c.0) + (c.0 - b.0) * (p.1 - c.1)) / det;
+        let w_b = ((c.1 - a.1) * (p.0 - c.0) + (a.0 - c.0) * (p.1 - c.1)) / det;
+        Some((w_a, w_b, 1.0 - w_a - w_b))
+    }
+
+    fn data_lines(input: &str) -> impl Iterator<Item = &str> {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    }
+}
@@ -0,0 +1,98 @@
+use crate::transformation::{
+    datum::{Datum, Helmert},
+    Plane,
+};
+use crate::{CoordinateSystem, ElevationSystem, ParseResult, Spatial};
+
+impl Spatial {
+    // OGC WKT 1, compound when the elevation system is known
+    pub fn to_wkt(&self) -> Option<String> {
+        let horizontal = match &self.coordinate_system {
+            ParseResult::Parsed(system) => system.to_wkt()?,
+            _ => return None,
+        };
+
+        match &self.elevation_system {
+            ParseResult::Parsed(system) => match system.to_wkt() {
+                Some(vertical) => Some(format!(
+                    "COMPD_CS[\"{} + {}\",{},{}]",
+                    self.coordinate_system, self.elevation_system, horizontal, vertical
+                )),
+                None => Some(horizontal),
+            },
+            _ => Some(horizontal),
+        }
+    }
+}
+
+impl CoordinateSystem {
+    pub fn to_wkt(&self) -> Option<String> {
+        let plane = self.plane().ok()?;
+        let epsg = self.epsg()?;
+
+        match plane {
+            Plane::Geographic(_) => Some(format!(
+                "GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563]],\
+                 PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433],AUTHORITY[\"EPSG\",\"{}\"]]",
+                epsg
+            )),
+            Plane::Projected(datum, projection) => Some(format!(
+                "PROJCS[\"{}\",{},PROJECTION[\"Transverse_Mercator\"],\
+                 PARAMETER[\"latitude_of_origin\",0],PARAMETER[\"central_meridian\",{}],\
+                 PARAMETER[\"scale_factor\",{}],PARAMETER[\"false_easting\",{}],\
+                 PARAMETER[\"false_northing\",{}],UNIT[\"metre\",1],AUTHORITY[\"EPSG\",\"{}\"]]",
+                self,
+                Self::geogcs_wkt(datum),
+                projection.central_meridian,
+                projection.scale,
+                projection.false_easting,
+                projection.false_northing,
+                epsg
+            )),
+        }
+    }
+
+    fn geogcs_wkt(datum: Datum) -> String {
+        match datum {
+            Datum::EurefFin => {
+                "GEOGCS[\"ETRS89\",DATUM[\"European_Terrestrial_Reference_System_1989\",\
+                 SPHEROID[\"GRS 1980\",6378137,298.257222101]],PRIMEM[\"Greenwich\",0],\
+                 UNIT[\"degree\",0.0174532925199433],AUTHORITY[\"EPSG\",\"4258\"]]"
+                    .to_string()
+            }
+            Datum::Kkj => {
+                let helmert = Helmert::KKJ_TO_EUREF_FIN;
+                format!(
+                    "GEOGCS[\"KKJ\",DATUM[\"Kartastokoordinaattijarjestelma_1966\",\
+                     SPHEROID[\"International 1924\",6378388,297],TOWGS84[{},{},{},{},{},{},{}]],\
+                     PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433],AUTHORITY[\"EPSG\",\"4123\"]]",
+                    helmert.translation[0],
+                    helmert.translation[1],
+                    helmert.translation[2],
+                    helmert.rotation[0],
+                    helmert.rotation[1],
+                    helmert.rotation[2],
+                    helmert.scale
+                )
+            }
+        }
+    }
+}
+
+impl ElevationSystem {
+    pub fn to_wkt(&self) -> Option<String> {
+        let datum = match self {
+            ElevationSystem::N2000 => "Finnish Height System 2000",
+            ElevationSystem::N60 => "Helsinki 1960",
+            ElevationSystem::N43 => "Helsinki 1943",
+            _ => return None,
+        };
+
+        Some(format!(
+            "VERT_CS[\"{} height\",VERT_DATUM[\"{}\",2005],UNIT[\"metre\",1],AXIS[\"Up\",UP],AUTHORITY[\"EPSG\",\"{}\"]]",
+            self,
+            datum,
+            self.epsg()?
+        ))
+    }
+}